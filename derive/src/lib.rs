@@ -0,0 +1,354 @@
+//! The `CloudflareConfig` and `FieldNames` derive macros for
+//! [figment2-cloudflare-workers](https://docs.rs/figment2-cloudflare-workers).
+//!
+//! Use them through the `derive` feature of that crate, which re-exports
+//! them next to the traits they implement.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr, Token};
+
+/// Derive `CloudflareConfig`, declaring the binding each field of a struct
+/// with named fields is read from.
+///
+/// Field names follow serde's `rename` and `rename_all` attributes, and
+/// fields serde skips are left out. `Option` fields and fields serde
+/// defaults are declared optional. Each binding defaults to the uppercased
+/// field name; `#[binding(...)]` adjusts it:
+///
+/// - `#[binding(name = "LEGACY_KEY")]` reads the field from `LEGACY_KEY`.
+/// - `#[binding(secret)]` declares the field secret, as
+///   `CloudflareWorkersBindings::secret` does, and reads it through the
+///   secret accessor alone.
+/// - `#[binding(var)]` reads the field through the var accessor alone.
+#[proc_macro_derive(CloudflareConfig, attributes(binding))]
+pub fn derive_cloudflare_config(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `FieldNames`, listing the fields of a struct with named fields
+/// under the names serde deserialises them by.
+///
+/// Field names follow serde's `rename` and `rename_all` attributes, and
+/// fields serde skips are left out.
+#[proc_macro_derive(FieldNames)]
+pub fn derive_field_names(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_field_names(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let mut bindings = Vec::new();
+    for DeserializedField {
+        field,
+        name,
+        optional,
+    } in deserialized_fields(input, "CloudflareConfig")?
+    {
+        let binding = BindingAttributes::parse(field)?;
+        let binding_name = binding.name.unwrap_or_else(|| name.to_uppercase());
+        let kind = match binding.kind {
+            Some(Kind::Secret) => Some(quote!(.secret())),
+            Some(Kind::Var) => Some(quote!(.var())),
+            None => None,
+        };
+        let optional = optional.then(|| quote!(.optional()));
+        bindings.push(quote! {
+            ::figment2_cloudflare_workers::FieldBinding::new(#name, #binding_name) #kind #optional
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::figment2_cloudflare_workers::CloudflareConfig
+            for #ident #type_generics #where_clause
+        {
+            fn bindings() -> &'static [::figment2_cloudflare_workers::FieldBinding] {
+                const BINDINGS: &[::figment2_cloudflare_workers::FieldBinding] = &[#(#bindings),*];
+                BINDINGS
+            }
+        }
+    })
+}
+
+fn expand_field_names(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let names = deserialized_fields(input, "FieldNames")?
+        .into_iter()
+        .map(|field| field.name);
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::figment2_cloudflare_workers::FieldNames
+            for #ident #type_generics #where_clause
+        {
+            fn field_names() -> &'static [&'static str] {
+                &[#(#names),*]
+            }
+        }
+    })
+}
+
+/// A field serde deserialises.
+struct DeserializedField<'f> {
+    field: &'f syn::Field,
+    /// The name serde deserialises the field by.
+    name: String,
+    /// Whether the field may be missing.
+    optional: bool,
+}
+
+/// The fields of `input` that serde deserialises, rejecting anything but a
+/// struct with named fields when deriving `derive`.
+fn deserialized_fields<'f>(
+    input: &'f DeriveInput,
+    derive: &str,
+) -> syn::Result<Vec<DeserializedField<'f>>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            format!("`{derive}` can only be derived for structs"),
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            input,
+            format!("`{derive}` can only be derived for structs with named fields"),
+        ));
+    };
+
+    let container = Container::parse(input)?;
+    let mut deserialized = Vec::new();
+    for field in &fields.named {
+        let serde = SerdeField::parse(field, derive)?;
+        if serde.skip {
+            continue;
+        }
+        let ident = field.ident.as_ref().expect("named field");
+        let name = serde.rename.unwrap_or_else(|| {
+            let ident = ident.to_string();
+            let ident = ident.trim_start_matches("r#");
+            container
+                .rename_all
+                .as_deref()
+                .map_or_else(|| ident.to_owned(), |rule| apply_rename_rule(rule, ident))
+        });
+        deserialized.push(DeserializedField {
+            field,
+            name,
+            optional: container.default || serde.default || is_option(&field.ty),
+        });
+    }
+    Ok(deserialized)
+}
+
+/// The serde attributes of a field that decide its name and whether it is
+/// deserialised at all.
+#[derive(Default)]
+struct SerdeField {
+    rename: Option<String>,
+    skip: bool,
+    default: bool,
+}
+
+impl SerdeField {
+    fn parse(field: &syn::Field, derive: &str) -> syn::Result<Self> {
+        let mut serde = Self::default();
+        for attribute in field
+            .attrs
+            .iter()
+            .filter(|attribute| attribute.path().is_ident("serde"))
+        {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    serde.rename = deserialize_value(&meta)?.or(serde.rename.take());
+                } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_deserializing") {
+                    serde.skip = true;
+                } else if meta.path.is_ident("default") {
+                    serde.default = true;
+                    skip_meta(&meta)?;
+                } else if meta.path.is_ident("flatten") {
+                    return Err(meta.error(format!(
+                        "flattened fields are not supported by `{derive}`; \
+                         declare their fields on the outer struct",
+                    )));
+                } else {
+                    skip_meta(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(serde)
+    }
+}
+
+/// The serde attributes of the struct that affect its fields.
+#[derive(Default)]
+struct Container {
+    rename_all: Option<String>,
+    default: bool,
+}
+
+impl Container {
+    fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut container = Self::default();
+        for attribute in input
+            .attrs
+            .iter()
+            .filter(|attribute| attribute.path().is_ident("serde"))
+        {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename_all") {
+                    let span = meta.path.clone();
+                    if let Some(value) = deserialize_value(&meta)? {
+                        if !RENAME_RULES.contains(&value.as_str()) {
+                            return Err(syn::Error::new_spanned(
+                                span,
+                                format!("unknown `rename_all` rule `{value}`"),
+                            ));
+                        }
+                        container.rename_all = Some(value);
+                    }
+                } else {
+                    if meta.path.is_ident("default") {
+                        container.default = true;
+                    }
+                    skip_meta(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(container)
+    }
+}
+
+/// Whether `ty` is spelled as an `Option`.
+fn is_option(ty: &syn::Type) -> bool {
+    matches!(
+        ty,
+        syn::Type::Path(path)
+            if path.qself.is_none()
+                && path.path.segments.last().is_some_and(|segment| segment.ident == "Option")
+    )
+}
+
+/// Read `key = "value"` or the `deserialize` half of
+/// `key(serialize = "...", deserialize = "...")`.
+fn deserialize_value(meta: &syn::meta::ParseNestedMeta<'_>) -> syn::Result<Option<String>> {
+    if meta.input.peek(Token![=]) {
+        return Ok(Some(meta.value()?.parse::<LitStr>()?.value()));
+    }
+    let mut value = None;
+    meta.parse_nested_meta(|nested| {
+        let literal = nested.value()?.parse::<LitStr>()?;
+        if nested.path.is_ident("deserialize") {
+            value = Some(literal.value());
+        }
+        Ok(())
+    })?;
+    Ok(value)
+}
+
+/// Consume a serde attribute this macro does not care about.
+fn skip_meta(meta: &syn::meta::ParseNestedMeta<'_>) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        content.parse::<TokenStream2>()?;
+    }
+    Ok(())
+}
+
+const RENAME_RULES: &[&str] = &[
+    "lowercase",
+    "UPPERCASE",
+    "PascalCase",
+    "camelCase",
+    "snake_case",
+    "SCREAMING_SNAKE_CASE",
+    "kebab-case",
+    "SCREAMING-KEBAB-CASE",
+];
+
+/// Apply a serde `rename_all` rule to a snake-case field name.
+fn apply_rename_rule(rule: &str, field: &str) -> String {
+    let pascal = || {
+        field
+            .split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars.next().map_or_else(String::new, |first| {
+                    first.to_uppercase().chain(chars).collect()
+                })
+            })
+            .collect::<String>()
+    };
+    match rule {
+        "lowercase" => field.to_lowercase(),
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => field.to_uppercase(),
+        "PascalCase" => pascal(),
+        "camelCase" => {
+            let pascal = pascal();
+            let mut chars = pascal.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_lowercase().chain(chars).collect()
+            })
+        }
+        "kebab-case" => field.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => field.replace('_', "-").to_uppercase(),
+        _ => field.to_owned(),
+    }
+}
+
+/// The accessor a field declares it is bound through.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Var,
+    Secret,
+}
+
+/// The `#[binding(...)]` attributes of a field.
+#[derive(Default)]
+struct BindingAttributes {
+    name: Option<String>,
+    kind: Option<Kind>,
+}
+
+impl BindingAttributes {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let mut binding = Self::default();
+        for attribute in field
+            .attrs
+            .iter()
+            .filter(|attribute| attribute.path().is_ident("binding"))
+        {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    binding.name = Some(meta.value()?.parse::<LitStr>()?.value());
+                    Ok(())
+                } else if let Some(kind) = [("secret", Kind::Secret), ("var", Kind::Var)]
+                    .into_iter()
+                    .find_map(|(ident, kind)| meta.path.is_ident(ident).then_some(kind))
+                {
+                    if binding.kind.is_some_and(|declared| declared != kind) {
+                        return Err(meta.error("a binding cannot be both `secret` and `var`"));
+                    }
+                    binding.kind = Some(kind);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `name = \"...\"`, `secret` or `var`"))
+                }
+            })?;
+        }
+        Ok(binding)
+    }
+}
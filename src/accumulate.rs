@@ -0,0 +1,33 @@
+use figment2::{
+    value::{Dict, Map},
+    Error, Metadata, Profile, Provider,
+};
+
+use crate::{CloudflareWorkersBindings, MergeMode};
+
+/// The fields a [`CloudflareWorkersBindings`] provider was told to
+/// [`accumulate`](CloudflareWorkersBindings::accumulate), as a provider of
+/// their own.
+///
+/// Admerge it into a figment, so that its lists are appended to those of
+/// earlier providers rather than replacing them.
+#[derive(Clone, Copy)]
+pub struct Accumulated<'p, 'a> {
+    provider: &'p CloudflareWorkersBindings<'a>,
+}
+
+impl<'p, 'a> Accumulated<'p, 'a> {
+    pub(crate) fn new(provider: &'p CloudflareWorkersBindings<'a>) -> Self {
+        Self { provider }
+    }
+}
+
+impl Provider for Accumulated<'_, '_> {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("Cloudflare Worker environment (accumulated)")
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        self.provider.emit(MergeMode::Accumulated)
+    }
+}
@@ -0,0 +1,151 @@
+use std::future::Future;
+
+use figment2::Error;
+use serde::Serialize;
+use worker::{Env, Method, Request, Response};
+
+use crate::{token::tokens_match, CachedConfig, Redacted};
+
+/// An operator endpoint over a [`CachedConfig`], so configuration can be
+/// inspected and refreshed without redeploying:
+///
+/// - `GET` serves the configuration as JSON, with every value replaced by
+///   [`REDACTED`](crate::REDACTED) except those [revealed](Self::reveal);
+/// - `POST` to a path ending in `/reload` extracts the configuration anew
+///   and swaps it into the cache, serving the new value.
+///
+/// Every request must carry `Authorization: Bearer <token>`, where the token
+/// is the value of a secret binding; requests without it are answered
+/// `401`, and every request is refused while the secret is not set or is
+/// empty.
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{AdminEndpoint, CachedConfig, CloudflareWorkersBindings, ConfigStore};
+///
+/// static CONFIG: CachedConfig<Config> = CachedConfig::new();
+///
+/// router.on_async("/__admin/*path", |request, context| async move {
+///     AdminEndpoint::new(&CONFIG, "ADMIN_TOKEN")
+///         .reveal("api_base_url")
+///         .handle(&request, &context.env, || async {
+///             let kv = ConfigStore::new(&context.env, "CONFIG")?.load::<Config>().await?;
+///             Figment::new()
+///                 .merge(CloudflareWorkersBindings::from_sources::<Config>(&[&kv, &context.env]))
+///                 .extract()
+///         })
+///         .await
+/// })
+/// ```
+///
+/// A reload only swaps the configuration of the isolate that serves it;
+/// other isolates keep theirs until they reload or are recycled. A failed
+/// reload is answered `500` and leaves the cached configuration in place.
+#[derive(Debug)]
+pub struct AdminEndpoint<'a, T> {
+    config: &'a CachedConfig<T>,
+    token_binding: String,
+    revealed: Vec<String>,
+}
+
+impl<'a, T: Serialize> AdminEndpoint<'a, T> {
+    /// Serve and reload `config`, authenticating requests against the
+    /// secret bound as `token_binding`.
+    #[must_use]
+    pub fn new(config: &'a CachedConfig<T>, token_binding: &str) -> Self {
+        Self {
+            config,
+            token_binding: token_binding.to_owned(),
+            revealed: Vec::new(),
+        }
+    }
+
+    /// Serve the value at the dotted `path`, and every value under it, as
+    /// it is rather than masked.
+    #[must_use]
+    pub fn reveal(mut self, path: impl Into<String>) -> Self {
+        self.revealed.push(path.into());
+        self
+    }
+
+    /// Serve the values at every dotted path in `paths` as they are.
+    #[must_use]
+    pub fn reveal_all<P: Into<String>>(mut self, paths: impl IntoIterator<Item = P>) -> Self {
+        self.revealed.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Answer `request`, extracting the configuration with `extract` when
+    /// reloading, or when serving it before anything is cached.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the response cannot be built.
+    pub async fn handle<F, Fut>(
+        &self,
+        request: &Request,
+        env: &Env,
+        extract: F,
+    ) -> worker::Result<Response>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let expected = env
+            .secret(&self.token_binding)
+            .map(|secret| secret.to_string())
+            .unwrap_or_default();
+        if expected.is_empty() {
+            return Response::error(
+                format!(
+                    "`{}` is not set; the admin endpoint is disabled",
+                    self.token_binding
+                ),
+                503,
+            );
+        }
+        let presented = request
+            .headers()
+            .get("Authorization")?
+            .and_then(|header| header.strip_prefix("Bearer ").map(str::to_owned));
+        if !presented.is_some_and(|token| tokens_match(&token, &expected)) {
+            let mut response = Response::error("Unauthorized", 401)?;
+            response.headers_mut().set("WWW-Authenticate", "Bearer")?;
+            return Ok(response);
+        }
+
+        let reload = request.path().ends_with("/reload");
+        let config = match (request.method(), reload) {
+            (Method::Get, false) => match self.config.cached() {
+                Some(config) => Ok(config),
+                None => self.reload(extract).await,
+            },
+            (Method::Post, true) => self.reload(extract).await,
+            _ => return Response::error("Method Not Allowed", 405),
+        };
+        let mut response = match config {
+            Ok(config) => {
+                Response::from_json(&Redacted::revealing(config.as_ref(), &self.revealed))?
+            }
+            Err(error) => Response::from_json(&ReloadFailure {
+                error: error.to_string(),
+            })?
+            .with_status(500),
+        };
+        response.headers_mut().set("Cache-Control", "no-store")?;
+        Ok(response)
+    }
+
+    async fn reload<F, Fut>(&self, extract: F) -> Result<std::sync::Arc<T>, Error>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        Ok(self.config.replace(extract().await?))
+    }
+}
+
+/// The body of a failed reload.
+#[derive(Serialize)]
+struct ReloadFailure {
+    error: String,
+}
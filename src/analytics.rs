@@ -0,0 +1,70 @@
+use figment2::Error;
+use worker::{AnalyticsEngineDataPointBuilder, AnalyticsEngineDataset};
+
+/// Writes a data point to a [Workers Analytics Engine] dataset whenever a
+/// configuration fails to load, giving platform teams fleet-wide visibility
+/// into misconfigured deployments. Passed to
+/// [`CloudflareWorkersBindings::analytics`](crate::CloudflareWorkersBindings::analytics):
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, FailureAnalytics};
+///
+/// let analytics = FailureAnalytics::new(env.analytics_engine("CONFIG_FAILURES")?)
+///     .worker_version(version_id);
+/// let provider = CloudflareWorkersBindings::from_struct::<Config>(&env).analytics(analytics);
+/// ```
+///
+/// Each data point is indexed by the worker version and holds:
+///
+/// - `blob1`: the worker version, or an empty string if not set.
+/// - `blob2`: the missing required bindings, comma-separated.
+/// - `blob3`: the error loading failed with.
+/// - `double1`: the number of missing required bindings.
+///
+/// So the deployments missing bindings can be queried with e.g.
+/// `SELECT blob1, blob2, count() FROM config_failures WHERE double1 > 0 GROUP BY blob1, blob2`.
+/// Data points name bindings, never their values.
+///
+/// [Workers Analytics Engine]: https://developers.cloudflare.com/analytics/analytics-engine/
+#[derive(Clone, Debug)]
+pub struct FailureAnalytics {
+    dataset: AnalyticsEngineDataset,
+    version: String,
+}
+
+impl FailureAnalytics {
+    /// Write failures to `dataset`.
+    #[must_use]
+    pub fn new(dataset: AnalyticsEngineDataset) -> Self {
+        Self {
+            dataset,
+            version: String::new(),
+        }
+    }
+
+    /// Record failures as coming from the worker version `version`, e.g. the
+    /// `id` of the version metadata binding.
+    #[must_use]
+    pub fn worker_version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Write a data point for a configuration that failed to load with
+    /// `error`, missing the required bindings `missing`, e.g. one that
+    /// resolved but could not be extracted.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the data point cannot be written.
+    pub fn record(&self, missing: &[String], error: &Error) -> worker::Result<()> {
+        let count = u32::try_from(missing.len()).unwrap_or(u32::MAX);
+        AnalyticsEngineDataPointBuilder::new()
+            .indexes([self.version.as_str()])
+            .add_blob(self.version.as_str())
+            .add_blob(missing.join(","))
+            .add_blob(error.to_string())
+            .add_double(count)
+            .write_to(&self.dataset)
+    }
+}
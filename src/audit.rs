@@ -0,0 +1,338 @@
+use std::fmt;
+
+use figment2::Error;
+use serde::{Deserialize, Serialize};
+
+/// The value of the `audit` key that marks a console line as an
+/// [`AuditEvent`].
+pub const AUDIT_SCHEMA: &str = "figment2-cloudflare-workers";
+
+/// The version of the [`AuditEvent`] schema, bumped whenever a field changes
+/// meaning or is removed.
+pub const AUDIT_VERSION: u32 = 1;
+
+/// Where a provider reports [`AuditEvent`]s, passed to
+/// [`CloudflareWorkersBindings::audit`](crate::CloudflareWorkersBindings::audit).
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{Audit, CloudflareWorkersBindings};
+///
+/// let audit = Audit::new("checkout-api").fingerprint(salt.as_bytes());
+/// // Merging reports `config.loaded`, or `config.failed` if resolution fails.
+/// let provider = CloudflareWorkersBindings::from_struct::<Config>(&env).audit(audit.clone());
+/// let config: Config = Figment::new().merge(provider).extract()?;
+/// ```
+///
+/// Failures the provider does not see, such as a document read from KV that
+/// does not form the type extracted from it, are reported with
+/// [`failed`](Self::failed):
+///
+/// ```rust,ignore
+/// let limits: Limits = Figment::from(Json::string(&document))
+///     .extract()
+///     .inspect_err(|error| audit.emit(&audit.failed(error)))?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct Audit {
+    source: String,
+    #[cfg(feature = "fingerprint")]
+    salt: Option<Vec<u8>>,
+    sink: fn(&str),
+}
+
+impl Audit {
+    /// Report events under `source`, e.g. the name of the worker or of the
+    /// configuration section.
+    #[must_use]
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            #[cfg(feature = "fingerprint")]
+            salt: None,
+            sink: crate::write_to_console,
+        }
+    }
+
+    /// Write events to `sink`, one JSON line per call, rather than to the
+    /// console, or to standard error off wasm32.
+    #[must_use]
+    pub fn sink(mut self, sink: fn(&str)) -> Self {
+        self.sink = sink;
+        self
+    }
+
+    /// Include the [fingerprint](crate::Snapshot::fingerprint) of the loaded
+    /// configuration, salted with `salt`, so a collector can spot workers
+    /// that disagree.
+    #[cfg(feature = "fingerprint")]
+    #[must_use]
+    pub fn fingerprint(mut self, salt: &[u8]) -> Self {
+        self.salt = Some(salt.to_vec());
+        self
+    }
+
+    /// The source events are reported under.
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Write `event` to the sink as a JSON line.
+    pub fn emit(&self, event: &AuditEvent) {
+        (self.sink)(&event.to_string());
+    }
+
+    /// The event of a configuration that failed to load with `error`, e.g.
+    /// one that resolved but could not be extracted.
+    #[must_use]
+    pub fn failed(&self, error: &Error) -> AuditEvent {
+        self.event(0, Vec::new(), None).with_error(error)
+    }
+
+    /// A `config.loaded` event, which [`AuditEvent::with_error`] turns into
+    /// a failure.
+    pub(crate) fn event(
+        &self,
+        fields: usize,
+        missing: Vec<String>,
+        fingerprint: Option<String>,
+    ) -> AuditEvent {
+        AuditEvent {
+            audit: AUDIT_SCHEMA.to_owned(),
+            version: AUDIT_VERSION,
+            event: AuditKind::ConfigLoaded,
+            source: self.source.clone(),
+            fields,
+            missing,
+            fingerprint,
+            error: None,
+        }
+    }
+
+    #[cfg(feature = "fingerprint")]
+    pub(crate) fn salt(&self) -> Option<&[u8]> {
+        self.salt.as_deref()
+    }
+}
+
+/// What an [`AuditEvent`] reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditKind {
+    /// Every required binding resolved.
+    #[serde(rename = "config.loaded")]
+    ConfigLoaded,
+    /// Resolution or extraction failed.
+    #[serde(rename = "config.failed")]
+    ConfigFailed,
+}
+
+/// A structured record of a configuration load, written to the console as
+/// one JSON line so a [tail worker] can collect config-change and
+/// config-failure events across a fleet.
+///
+/// Every line carries `"audit": "figment2-cloudflare-workers"` and the schema
+/// [`version`](AUDIT_VERSION); the other keys are:
+///
+/// - `event`: `config.loaded` or `config.failed`.
+/// - `source`: the [`Audit`] source.
+/// - `fields`: the number of fields that resolved, or 0 if loading failed.
+/// - `missing`: the required bindings that are not bound.
+/// - `fingerprint`: the salted fingerprint of the configuration, if loaded
+///   and requested, otherwise `null`.
+/// - `error`: the error loading failed with, otherwise `null`.
+///
+/// ```json
+/// {"audit":"figment2-cloudflare-workers","version":1,"event":"config.failed","source":"checkout-api","fields":0,"missing":["API_KEY"],"fingerprint":null,"error":"required bindings are missing: `API_KEY`"}
+/// ```
+///
+/// Events name bindings, never their values. A tail worker parses the
+/// messages of a trace's logs back into events with [`AuditEvent::parse`].
+///
+/// [tail worker]: https://developers.cloudflare.com/workers/observability/logs/tail-workers/
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    audit: String,
+    version: u32,
+    event: AuditKind,
+    source: String,
+    fields: usize,
+    missing: Vec<String>,
+    fingerprint: Option<String>,
+    error: Option<String>,
+}
+
+impl AuditEvent {
+    /// Parse a console line back into an event, returning `None` for lines
+    /// that are not audit events of this schema.
+    #[must_use]
+    pub fn parse(line: &str) -> Option<Self> {
+        serde_json::from_str::<Self>(line)
+            .ok()
+            .filter(|event| event.audit == AUDIT_SCHEMA)
+    }
+
+    /// What the event reports.
+    #[must_use]
+    pub fn kind(&self) -> AuditKind {
+        self.event
+    }
+
+    /// The schema version the event was written with.
+    #[must_use]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// The [`Audit`] source the event was reported under.
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The number of fields that resolved.
+    #[must_use]
+    pub fn fields(&self) -> usize {
+        self.fields
+    }
+
+    /// The required bindings that are not bound.
+    #[must_use]
+    pub fn missing(&self) -> &[String] {
+        &self.missing
+    }
+
+    /// The salted fingerprint of the loaded configuration, if requested.
+    #[must_use]
+    pub fn fingerprint(&self) -> Option<&str> {
+        self.fingerprint.as_deref()
+    }
+
+    /// The error loading failed with, if it did.
+    #[must_use]
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Write the event to the console as a JSON line, where tail workers
+    /// receive it, or to standard error off wasm32. [`Audit::emit`] writes
+    /// it to the sink of an [`Audit`] instead.
+    pub fn emit(&self) {
+        crate::write_to_console(&self.to_string());
+    }
+
+    pub(crate) fn with_error(mut self, error: &Error) -> Self {
+        self.event = AuditKind::ConfigFailed;
+        self.error = Some(error.to_string());
+        self
+    }
+}
+
+/// Displays as the single JSON line [`emit`](AuditEvent::emit) writes.
+impl fmt::Display for AuditEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(self).map_err(|_| fmt::Error)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "test-util")]
+    mod provider {
+        use figment2::{Figment, Provider};
+        use serde::Deserialize;
+
+        use std::cell::RefCell;
+
+        use crate::{Audit, AuditEvent, AuditKind, CloudflareWorkersBindings, MockBindings};
+
+        thread_local! {
+            /// The lines written to [`record`] on this thread.
+            static EMITTED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+        }
+
+        fn record(line: &str) {
+            EMITTED.with(|emitted| emitted.borrow_mut().push(line.to_owned()));
+        }
+
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Config {
+            api_base_url: String,
+            api_key: String,
+            timeout: Option<u32>,
+        }
+
+        fn emitted(run: impl FnOnce()) -> Vec<AuditEvent> {
+            EMITTED.with(|emitted| emitted.borrow_mut().clear());
+            run();
+            EMITTED
+                .with(RefCell::take)
+                .iter()
+                .map(|line| AuditEvent::parse(line).expect("an audit event"))
+                .collect()
+        }
+
+        #[test]
+        fn a_resolved_provider_reports_its_load() {
+            let bindings = MockBindings::new()
+                .with_var("API_BASE_URL", "https://api.example.com/v1")
+                .with_secret("API_KEY", "super-secret-key");
+            let audit = Audit::new("checkout-api").sink(record);
+            let events = emitted(|| {
+                Figment::new()
+                    .merge(CloudflareWorkersBindings::from_struct::<Config>(&bindings).audit(audit))
+                    .extract::<Config>()
+                    .unwrap();
+            });
+            assert_eq!(events.len(), 1, "{events:?}");
+            let event = &events[0];
+            assert_eq!(event.kind(), AuditKind::ConfigLoaded);
+            assert_eq!(event.source(), "checkout-api");
+            assert_eq!(event.fields(), 2);
+            assert!(event.missing().is_empty());
+            assert_eq!(event.fingerprint(), None);
+            assert_eq!(event.error(), None);
+            assert!(!event.to_string().contains("super-secret-key"));
+        }
+
+        #[cfg(feature = "fingerprint")]
+        #[test]
+        fn a_resolved_provider_reports_its_fingerprint() {
+            let bindings = MockBindings::new()
+                .with_var("API_BASE_URL", "https://api.example.com/v1")
+                .with_secret("API_KEY", "super-secret-key");
+            let provider = || CloudflareWorkersBindings::from_struct::<Config>(&bindings);
+            let events = emitted(|| {
+                provider()
+                    .audit(Audit::new("checkout-api").sink(record).fingerprint(b"salt"))
+                    .data()
+                    .unwrap();
+            });
+            let expected = provider().fingerprint(b"salt").unwrap();
+            assert_eq!(events[0].fingerprint(), Some(expected.as_str()));
+        }
+
+        #[test]
+        fn a_failed_provider_reports_the_missing_bindings() {
+            let bindings =
+                MockBindings::new().with_var("API_BASE_URL", "https://api.example.com/v1");
+            let events = emitted(|| {
+                CloudflareWorkersBindings::from_struct::<Config>(&bindings)
+                    .require("api_key")
+                    .audit(Audit::new("checkout-api").sink(record))
+                    .data()
+                    .unwrap_err();
+            });
+            assert_eq!(events.len(), 1, "{events:?}");
+            let event = &events[0];
+            assert_eq!(event.kind(), AuditKind::ConfigFailed);
+            assert_eq!(event.fields(), 0);
+            assert_eq!(event.missing(), ["API_KEY"]);
+            assert_eq!(
+                event.error(),
+                Some("required bindings are missing: `API_KEY`")
+            );
+            assert_eq!(AuditEvent::parse(&event.to_string()).as_ref(), Some(event));
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use figment2::Error;
+use serde::de::DeserializeOwned;
+
+use crate::{extract_config, BindingSource};
+
+/// Configuration extracted once per isolate and shared by every request it
+/// serves.
+///
+/// Bindings do not change while an isolate is alive, so re-extracting them
+/// on every request is wasted work. Keep a `CachedConfig` in a `static` and
+/// ask it for the configuration instead; only the first request (and the
+/// first one after [`invalidate`](Self::invalidate)) reads the bindings:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::CachedConfig;
+///
+/// static CONFIG: CachedConfig<Config> = CachedConfig::new();
+///
+/// #[event(fetch)]
+/// async fn fetch(request: Request, env: Env, _context: Context) -> Result<Response> {
+///     let config = CONFIG.get(&env)?;
+///     // ...
+/// }
+/// ```
+///
+/// The configuration is handed out as an [`Arc`], so a request keeps the
+/// value it started with even if the cache is invalidated meanwhile.
+#[derive(Debug, Default)]
+pub struct CachedConfig<T> {
+    value: Mutex<Option<Arc<T>>>,
+}
+
+impl<T> CachedConfig<T> {
+    /// Create an empty cache.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            value: Mutex::new(None),
+        }
+    }
+
+    /// The cached configuration, or the one returned by `extract`, which is
+    /// then cached.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `extract` fails; nothing is cached then, so the next call
+    /// tries again.
+    pub fn get_or_extract(
+        &self,
+        extract: impl FnOnce() -> Result<T, Error>,
+    ) -> Result<Arc<T>, Error> {
+        if let Some(value) = self.cached() {
+            return Ok(value);
+        }
+        let value = Arc::new(extract()?);
+        *self.lock() = Some(Arc::clone(&value));
+        Ok(value)
+    }
+
+    /// The cached configuration, if it has been extracted.
+    #[must_use]
+    pub fn cached(&self) -> Option<Arc<T>> {
+        self.lock().clone()
+    }
+
+    /// Cache `value` in place of the current configuration, e.g. one
+    /// reloaded from a source that has changed, and return it.
+    pub fn replace(&self, value: T) -> Arc<T> {
+        let value = Arc::new(value);
+        *self.lock() = Some(Arc::clone(&value));
+        value
+    }
+
+    /// Drop the cached configuration, so the next request extracts it anew.
+    pub fn invalidate(&self) {
+        *self.lock() = None;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Arc<T>>> {
+        // A panic elsewhere cannot leave the `Option` half-written.
+        self.value.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T: DeserializeOwned + 'static> CachedConfig<T> {
+    /// The cached configuration, or the one extracted from the bindings of
+    /// `source` with [`extract_config`], which is then cached.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the configuration is not cached and cannot be extracted.
+    pub fn get(&self, source: &dyn BindingSource) -> Result<Arc<T>, Error> {
+        self.get_or_extract(|| extract_config(source))
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt;
+
+use figment2::{Error, Figment, Provider};
+use serde::de::DeserializeOwned;
+
+type Check<T> = Box<dyn Fn(&T) -> Result<(), String>>;
+
+/// A canary configuration source, such as a KV namespace or document that
+/// configuration pushes land in first, layered over the primary sources
+/// only if the configuration it produces passes validation checks.
+///
+/// A canary that fails to extract, or that a check rejects, is dropped,
+/// and the configuration is extracted from the primary sources alone, so a
+/// bad push degrades to the last known good configuration instead of
+/// taking the fleet down. The rejection is recorded in the
+/// [`CanaryOutcome`] (and, with the `log` feature, logged as a warning):
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{Canary, FigmentExt};
+///
+/// let outcome = Canary::new(Figment::from_cloudflare::<Config>(&env), canary_kv)
+///     .check(|config: &Config| {
+///         if config.max_connections <= 500 {
+///             Ok(())
+///         } else {
+///             Err(format!("{} connections would overload the origin", config.max_connections))
+///         }
+///     })
+///     .extract()?;
+/// if let Some(rejection) = outcome.rejection() {
+///     console_warn!("canary configuration rejected: {rejection}");
+/// }
+/// let config = outcome.into_config();
+/// ```
+///
+/// Both extractions are lossy, as with [`extract_config`](crate::extract_config).
+pub struct Canary<T> {
+    primary: Figment,
+    /// The canary layered over the primary sources.
+    layered: Figment,
+    checks: Vec<Check<T>>,
+}
+
+impl<T: DeserializeOwned> Canary<T> {
+    /// Layer `canary` over `primary`, pending its checks.
+    #[must_use]
+    pub fn new(primary: Figment, canary: impl Provider) -> Self {
+        Self {
+            layered: primary.clone().merge(canary),
+            primary,
+            checks: Vec::new(),
+        }
+    }
+
+    /// Reject the canary if `check` fails on the configuration extracted
+    /// with it. A check fails by returning any displayable error, e.g. a
+    /// `String`.
+    #[must_use]
+    pub fn check<E: fmt::Display>(mut self, check: impl Fn(&T) -> Result<(), E> + 'static) -> Self {
+        self.checks.push(Box::new(move |config| {
+            check(config).map_err(|error| error.to_string())
+        }));
+        self
+    }
+
+    /// Extract the configuration with the canary if it passes every check,
+    /// or from the primary sources otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the canary is rejected and the primary sources do not form a
+    /// valid `T` either.
+    pub fn extract(&self) -> Result<CanaryOutcome<T>, Error> {
+        let rejection = match self.layered.extract_lossy::<T>() {
+            Ok(config) => match self.checks.iter().find_map(|check| check(&config).err()) {
+                None => {
+                    return Ok(CanaryOutcome {
+                        config,
+                        rejection: None,
+                    })
+                }
+                Some(reason) => reason,
+            },
+            Err(error) => error.to_string(),
+        };
+        #[cfg(feature = "log")]
+        log::warn!(
+            "canary configuration rejected; falling back to the primary sources: {rejection}"
+        );
+        Ok(CanaryOutcome {
+            config: self.primary.extract_lossy()?,
+            rejection: Some(rejection),
+        })
+    }
+}
+
+impl<T> fmt::Debug for Canary<T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The figments may hold secrets.
+        formatter
+            .debug_struct("Canary")
+            .field("checks", &self.checks.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The configuration a [`Canary`] extracted, and why the canary was
+/// rejected, if it was.
+#[derive(Clone, Debug)]
+pub struct CanaryOutcome<T> {
+    config: T,
+    rejection: Option<String>,
+}
+
+impl<T> CanaryOutcome<T> {
+    /// The configuration.
+    #[must_use]
+    pub fn config(&self) -> &T {
+        &self.config
+    }
+
+    /// The configuration, by value.
+    #[must_use]
+    pub fn into_config(self) -> T {
+        self.config
+    }
+
+    /// Whether the canary was applied.
+    #[must_use]
+    pub fn applied(&self) -> bool {
+        self.rejection.is_none()
+    }
+
+    /// Why the canary was rejected: the failed check's error, or the
+    /// extraction error.
+    #[must_use]
+    pub fn rejection(&self) -> Option<&str> {
+        self.rejection.as_deref()
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use figment2::Error;
+use serde::de::DeserializeOwned;
+
+use crate::{clock, extract_config, BindingSource};
+
+/// What a [`ConfigCell`] does after an extraction fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryPolicy {
+    /// Cache nothing, so the next access extracts again.
+    Immediately,
+    /// Answer every access within the duration with the cached error, then
+    /// extract again, so a broken deployment does not pay for a failing
+    /// extraction on every request.
+    After(Duration),
+    /// Answer every later access with the cached error, for configuration
+    /// that cannot recover without a new deployment.
+    Never,
+}
+
+/// A configuration extracted on first access and shared by every later
+/// request in the isolate, with its errors cached by a [`RetryPolicy`].
+///
+/// Keep it in a `static` and ask it for the configuration from each
+/// handler; the extraction may be synchronous, as with
+/// [`get`](Self::get), or asynchronous, e.g. reading KV first, with
+/// [`get_or_init_async`](Self::get_or_init_async):
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{ConfigCell, RetryPolicy};
+///
+/// static CONFIG: ConfigCell<Config> =
+///     ConfigCell::with_retry(RetryPolicy::After(Duration::from_secs(10)));
+///
+/// #[event(fetch)]
+/// async fn fetch(request: Request, env: Env, _context: Context) -> Result<Response> {
+///     let config = CONFIG.get(&env)?;
+///     // ...
+/// }
+/// ```
+///
+/// Unlike a [`CachedConfig`](crate::CachedConfig), which retries every
+/// failed extraction at once, a cell can remember a failure. Requests that
+/// race an asynchronous first extraction may each run it; the first value
+/// stored is kept and handed to all of them.
+#[derive(Debug)]
+pub struct ConfigCell<T> {
+    state: Mutex<CellState<T>>,
+    retry: RetryPolicy,
+}
+
+#[derive(Debug)]
+enum CellState<T> {
+    Empty,
+    Ready(Arc<T>),
+    /// The error of the last extraction and the time, in milliseconds since
+    /// the epoch, from which to extract again.
+    Failed(Box<Error>, f64),
+}
+
+impl<T> ConfigCell<T> {
+    /// Create an empty cell that retries failed extractions
+    /// [immediately](RetryPolicy::Immediately).
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_retry(RetryPolicy::Immediately)
+    }
+
+    /// Create an empty cell that caches failed extractions by `retry`.
+    #[must_use]
+    pub const fn with_retry(retry: RetryPolicy) -> Self {
+        Self {
+            state: Mutex::new(CellState::Empty),
+            retry,
+        }
+    }
+
+    /// The configuration, if it has been extracted.
+    #[must_use]
+    pub fn cached(&self) -> Option<Arc<T>> {
+        match &*self.lock() {
+            CellState::Ready(value) => Some(Arc::clone(value)),
+            CellState::Empty | CellState::Failed(..) => None,
+        }
+    }
+
+    /// The configuration, extracted with `extract` on first access.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `extract` fails, or with the cached error of an earlier
+    /// extraction while the [`RetryPolicy`] holds it.
+    pub fn get_or_init(&self, extract: impl FnOnce() -> Result<T, Error>) -> Result<Arc<T>, Error> {
+        if let Some(cached) = self.cached_result() {
+            return cached;
+        }
+        self.store(extract())
+    }
+
+    /// The configuration, extracted by awaiting `extract` on first access.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `extract` fails, or with the cached error of an earlier
+    /// extraction while the [`RetryPolicy`] holds it.
+    pub async fn get_or_init_async<F, Fut>(&self, extract: F) -> Result<Arc<T>, Error>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        if let Some(cached) = self.cached_result() {
+            return cached;
+        }
+        self.store(extract().await)
+    }
+
+    /// Forget the configuration or cached error, so the next access
+    /// extracts again.
+    pub fn reset(&self) {
+        *self.lock() = CellState::Empty;
+    }
+
+    /// The value or error to answer with without extracting, if any.
+    fn cached_result(&self) -> Option<Result<Arc<T>, Error>> {
+        match &*self.lock() {
+            CellState::Empty => None,
+            CellState::Ready(value) => Some(Ok(Arc::clone(value))),
+            CellState::Failed(error, retry_at) => {
+                (clock::now_ms() < *retry_at).then(|| Err((**error).clone()))
+            }
+        }
+    }
+
+    /// Record the outcome of an extraction by the retry policy.
+    fn store(&self, result: Result<T, Error>) -> Result<Arc<T>, Error> {
+        let mut state = self.lock();
+        if let CellState::Ready(value) = &*state {
+            return Ok(Arc::clone(value));
+        }
+        match result {
+            Ok(value) => {
+                let value = Arc::new(value);
+                *state = CellState::Ready(Arc::clone(&value));
+                Ok(value)
+            }
+            Err(error) => {
+                *state = match self.retry {
+                    RetryPolicy::Immediately => CellState::Empty,
+                    RetryPolicy::After(delay) => CellState::Failed(
+                        Box::new(error.clone()),
+                        clock::now_ms() + delay.as_secs_f64() * 1000.0,
+                    ),
+                    RetryPolicy::Never => CellState::Failed(Box::new(error.clone()), f64::INFINITY),
+                };
+                Err(error)
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CellState<T>> {
+        // A panic elsewhere cannot leave the state half-written.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> Default for ConfigCell<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: DeserializeOwned + 'static> ConfigCell<T> {
+    /// The configuration, extracted from the bindings of `source` with
+    /// [`extract_config`] on first access.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the configuration cannot be extracted, or with the cached
+    /// error of an earlier extraction while the [`RetryPolicy`] holds it.
+    pub fn get(&self, source: &dyn BindingSource) -> Result<Arc<T>, Error> {
+        self.get_or_init(|| extract_config(source))
+    }
+}
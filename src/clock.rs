@@ -0,0 +1,21 @@
+/// Milliseconds since the Unix epoch, for timing lookups and retries.
+///
+/// Inside the Workers runtime the clock only advances across I/O, so time
+/// spent in synchronous code, such as reading vars from the `Env`, reads as
+/// zero there.
+pub(crate) fn now_ms() -> f64 {
+    #[cfg(all(target_arch = "wasm32", feature = "worker"))]
+    {
+        worker::js_sys::Date::now()
+    }
+    #[cfg(all(target_arch = "wasm32", not(feature = "worker")))]
+    {
+        0.0
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+    }
+}
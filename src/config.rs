@@ -0,0 +1,271 @@
+use std::{marker::PhantomData, rc::Rc, sync::Arc};
+
+use figment2::Error;
+use serde::de::DeserializeOwned;
+
+use crate::{BindingSource, CloudflareWorkersBindings};
+
+/// A configuration struct that declares the binding each field is read from.
+///
+/// Derive it with the `derive` feature; attributes on the fields then
+/// express what the runtime discovery of
+/// [`from_struct`](crate::CloudflareWorkersBindings::from_struct) cannot,
+/// such as bindings named unlike their fields:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareConfig, CloudflareWorkersBindings};
+///
+/// #[derive(Deserialize, CloudflareConfig)]
+/// struct Config {
+///     #[binding(var)]
+///     database_url: String,
+///     #[binding(name = "LEGACY_KEY", secret)]
+///     api_key: String,
+/// }
+///
+/// let provider = CloudflareWorkersBindings::from_config::<Config>(&env);
+/// ```
+///
+/// A field declared a var or a secret is read through that accessor alone,
+/// bypassing any [prefetch](crate::BindingSource::prefetch), and resolution
+/// fails if the binding only exists as the other kind. The check only
+/// catches a misprovisioned binding with sources that distinguish the
+/// kinds, such as `MockBindings` or a wrangler `SecretsFile` in tests: the
+/// Workers runtime exposes vars and secrets alike, as plain strings, so a
+/// live [`worker::Env`] answers both accessors for either and the check
+/// never fires against it.
+pub trait CloudflareConfig {
+    /// The fields of the struct and the bindings they are read from.
+    fn bindings() -> &'static [FieldBinding];
+}
+
+/// The names a type's fields are deserialised by, for
+/// [`from_field_names`](crate::CloudflareWorkersBindings::from_field_names).
+///
+/// [`from_struct`](crate::CloudflareWorkersBindings::from_struct) discovers
+/// field names by running a dummy deserialisation, which only works for
+/// `#[derive(Deserialize)]` structs. Declaring the names instead also covers
+/// types with hand-written `Deserialize` impls, and skips the discovery
+/// entirely. Derive it with the `derive` feature:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, FieldNames};
+///
+/// #[derive(Deserialize, FieldNames)]
+/// struct Config {
+///     database_url: String,
+///     max_connections: u16,
+/// }
+///
+/// let provider = CloudflareWorkersBindings::from_field_names::<Config>(&env);
+/// ```
+///
+/// The [`from_struct!`](crate::from_struct!) macro prefers the names a type
+/// declares, and falls back to discovering them for any other
+/// `#[derive(Deserialize)]` struct. The generic `from_struct` function
+/// always discovers them, as it cannot tell whether its type parameter
+/// implements `FieldNames`.
+///
+/// Wrappers such as `Option<T>`, `Box<T>` and `Arc<T>` list the names of `T`.
+pub trait FieldNames {
+    /// The names of the fields, as serde deserialises them.
+    fn field_names() -> &'static [&'static str];
+}
+
+impl FieldNames for () {
+    fn field_names() -> &'static [&'static str] {
+        &[]
+    }
+}
+
+macro_rules! forward_field_names {
+    ($($wrapper:ty),*) => {
+        $(impl<T: FieldNames + ?Sized> FieldNames for $wrapper {
+            fn field_names() -> &'static [&'static str] {
+                T::field_names()
+            }
+        })*
+    };
+}
+
+forward_field_names!(Box<T>, Rc<T>, Arc<T>);
+
+impl<T: FieldNames> FieldNames for Option<T> {
+    fn field_names() -> &'static [&'static str] {
+        T::field_names()
+    }
+}
+
+/// Create a provider for the fields of a type, listed through its
+/// [`FieldNames`] impl if it has one, and otherwise discovered as
+/// [`from_struct`](crate::CloudflareWorkersBindings::from_struct) discovers
+/// them:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::from_struct;
+///
+/// let config: Config = Figment::from(from_struct!(Config, &env)).extract()?;
+/// ```
+///
+/// The choice is made where the macro is invoked, on the concrete type, so
+/// the type must be named there rather than be a generic parameter.
+#[macro_export]
+macro_rules! from_struct {
+    ($type:ty, $source:expr $(,)?) => {{
+        #[allow(unused_imports)]
+        use $crate::{DeclaredFieldNames as _, DiscoveredFieldNames as _};
+        (&&$crate::FieldsOf::<$type>(::core::marker::PhantomData)).provider($source)
+    }};
+}
+
+/// The type whose fields [`from_struct!`](crate::from_struct!) reads.
+///
+/// Method resolution tries `&&FieldsOf<T>` before `&FieldsOf<T>`, so
+/// [`DeclaredFieldNames`], implemented for the former where `T` implements
+/// [`FieldNames`], wins over [`DiscoveredFieldNames`].
+#[doc(hidden)]
+pub struct FieldsOf<T: ?Sized>(pub PhantomData<T>);
+
+#[doc(hidden)]
+pub trait DeclaredFieldNames {
+    fn provider<'a>(&self, source: &'a dyn BindingSource) -> CloudflareWorkersBindings<'a>;
+}
+
+impl<T: FieldNames + 'static> DeclaredFieldNames for &FieldsOf<T> {
+    fn provider<'a>(&self, source: &'a dyn BindingSource) -> CloudflareWorkersBindings<'a> {
+        CloudflareWorkersBindings::from_field_names::<T>(source)
+    }
+}
+
+#[doc(hidden)]
+pub trait DiscoveredFieldNames {
+    fn provider<'a>(&self, source: &'a dyn BindingSource) -> CloudflareWorkersBindings<'a>;
+}
+
+impl<T: DeserializeOwned + 'static> DiscoveredFieldNames for FieldsOf<T> {
+    fn provider<'a>(&self, source: &'a dyn BindingSource) -> CloudflareWorkersBindings<'a> {
+        CloudflareWorkersBindings::from_struct::<T>(source)
+    }
+}
+
+/// A tuple of configuration structs whose fields
+/// [`from_structs`](crate::CloudflareWorkersBindings::from_structs) resolves
+/// together, implemented for tuples of up to eight
+/// `#[derive(Deserialize)]` structs.
+pub trait ConfigStructs {
+    /// The union of the discovered field names of the structs, in order,
+    /// each listed once.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the fields of any of the structs cannot be discovered.
+    fn field_names() -> Result<Vec<&'static str>, Error>;
+}
+
+macro_rules! config_structs {
+    ($($struct:ident),+) => {
+        impl<$($struct: DeserializeOwned + 'static),+> ConfigStructs for ($($struct,)+) {
+            fn field_names() -> Result<Vec<&'static str>, Error> {
+                let mut names = Vec::new();
+                $(
+                    for name in crate::discover_field_names::<$struct>()? {
+                        if !names.contains(name) {
+                            names.push(*name);
+                        }
+                    }
+                )+
+                Ok(names)
+            }
+        }
+    };
+}
+
+config_structs!(A);
+config_structs!(A, B);
+config_structs!(A, B, C);
+config_structs!(A, B, C, D);
+config_structs!(A, B, C, D, E);
+config_structs!(A, B, C, D, E, F);
+config_structs!(A, B, C, D, E, F, G);
+config_structs!(A, B, C, D, E, F, G, H);
+
+/// How one field of a [`CloudflareConfig`] is bound.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldBinding {
+    field: &'static str,
+    binding: &'static str,
+    secret: bool,
+    var: bool,
+    optional: bool,
+}
+
+impl FieldBinding {
+    /// Read `field` from the binding named `binding`.
+    #[must_use]
+    pub const fn new(field: &'static str, binding: &'static str) -> Self {
+        Self {
+            field,
+            binding,
+            secret: false,
+            var: false,
+            optional: false,
+        }
+    }
+
+    /// Declare the field secret: it is treated as if passed to
+    /// [`CloudflareWorkersBindings::secret`](crate::CloudflareWorkersBindings::secret),
+    /// and read through the secret accessor alone.
+    #[must_use]
+    pub const fn secret(mut self) -> Self {
+        self.secret = true;
+        self.var = false;
+        self
+    }
+
+    /// Declare the field a plain-text var, read through the var accessor
+    /// alone.
+    #[must_use]
+    pub const fn var(mut self) -> Self {
+        self.var = true;
+        self.secret = false;
+        self
+    }
+
+    /// Declare the field optional, e.g. an `Option` or a field with a serde
+    /// default, so it need not be bound.
+    #[must_use]
+    pub const fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    /// The name of the field, as serde deserialises it.
+    #[must_use]
+    pub const fn field(&self) -> &'static str {
+        self.field
+    }
+
+    /// The name of the binding the field is read from.
+    #[must_use]
+    pub const fn binding(&self) -> &'static str {
+        self.binding
+    }
+
+    /// Whether the field is declared secret.
+    #[must_use]
+    pub const fn is_secret(&self) -> bool {
+        self.secret
+    }
+
+    /// Whether the field is declared a var.
+    #[must_use]
+    pub const fn is_var(&self) -> bool {
+        self.var
+    }
+
+    /// Whether the field is declared optional.
+    #[must_use]
+    pub const fn is_optional(&self) -> bool {
+        self.optional
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+
+use figment2::Error;
+use serde::{de::DeserializeOwned, Deserialize};
+use worker::{wasm_bindgen::JsValue, D1Database, D1PreparedStatement, Env};
+
+use crate::{BindingError, BindingSource};
+
+/// The table a [`D1ConfigStore`] keeps values in, unless changed with
+/// [`D1ConfigStore::table`].
+pub const DEFAULT_D1_TABLE: &str = "config";
+
+/// Configuration values stored as rows of a D1 table, one per binding name.
+///
+/// Like a KV `ConfigStore`, a store is first [`load`](Self::load)ed into
+/// [`D1Bindings`], a [`BindingSource`] to layer with the environment, and
+/// values are written back with [`put`](Self::put). [`migrate`](Self::migrate)
+/// creates the table, or brings one created by an earlier version up to
+/// date, so no SQL needs to be written by hand:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, D1ConfigStore};
+///
+/// let store = D1ConfigStore::new(&env, "DB")?;
+/// store.migrate().await?;
+/// store.put("MAX_CONNECTIONS", "20").await?;
+/// let d1 = store.load::<Config>().await?;
+/// let config: Config = Figment::new()
+///     .merge(CloudflareWorkersBindings::from_sources::<Config>(&[&d1, &env]))
+///     .extract()?;
+/// ```
+///
+/// The table has the schema:
+///
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS config (
+///     key TEXT PRIMARY KEY NOT NULL,
+///     value TEXT NOT NULL,
+///     updated_at INTEGER NOT NULL DEFAULT 0
+/// )
+/// ```
+///
+/// where `updated_at` is the Unix time of the last write.
+#[derive(Debug)]
+pub struct D1ConfigStore {
+    binding: String,
+    database: D1Database,
+    table: String,
+}
+
+impl D1ConfigStore {
+    /// Read configuration from the D1 database bound as `binding`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `binding` is not a D1 database binding.
+    pub fn new(env: &Env, binding: &str) -> Result<Self, Error> {
+        let database = env
+            .d1(binding)
+            .map_err(|error| Error::from(format!("D1 database `{binding}`: {error}")))?;
+        Ok(Self {
+            binding: binding.to_owned(),
+            database,
+            table: DEFAULT_D1_TABLE.to_owned(),
+        })
+    }
+
+    /// Keep values in the table `table` instead of [`DEFAULT_D1_TABLE`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if `table` is not a plain SQL identifier: ASCII letters,
+    /// digits and underscores, not starting with a digit.
+    pub fn table(mut self, table: impl Into<String>) -> Result<Self, Error> {
+        let table = table.into();
+        if !is_identifier(&table) {
+            return Err(Error::from(format!(
+                "D1 table `{table}` is not a plain SQL identifier"
+            )));
+        }
+        self.table = table;
+        Ok(self)
+    }
+
+    /// Create the table if it does not exist, and add the columns that
+    /// tables created by earlier versions lack. Safe to run on every
+    /// deployment, or on every cold start.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a statement fails.
+    pub async fn migrate(&self) -> Result<(), Error> {
+        let table = &self.table;
+        self.run(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {table} (\
+             key TEXT PRIMARY KEY NOT NULL, \
+             value TEXT NOT NULL, \
+             updated_at INTEGER NOT NULL DEFAULT 0)"
+            ),
+            &[],
+        )
+        .await?;
+        let columns: Vec<Column> = self
+            .query("SELECT name FROM pragma_table_info(?1)", &[table])
+            .await?;
+        if !columns.iter().any(|column| column.name == "updated_at") {
+            self.run(
+                &format!("ALTER TABLE {table} ADD COLUMN updated_at INTEGER NOT NULL DEFAULT 0"),
+                &[],
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Read the rows for the fields of `T`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the fields of `T` cannot be discovered (see
+    /// [`from_struct`](crate::CloudflareWorkersBindings::from_struct)), or
+    /// the query fails.
+    pub async fn load<T: DeserializeOwned + 'static>(&self) -> Result<D1Bindings, Error> {
+        let fields = crate::struct_fields::<T>()?;
+        let mut bindings = self.load_all().await?;
+        bindings
+            .values
+            .retain(|key, _| fields.iter().any(|field| field.binding == key.as_str()));
+        Ok(bindings)
+    }
+
+    /// Read every row of the table.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the query fails.
+    pub async fn load_all(&self) -> Result<D1Bindings, Error> {
+        let rows: Vec<Row> = self
+            .query(&format!("SELECT key, value FROM {}", self.table), &[])
+            .await?;
+        Ok(D1Bindings {
+            values: rows.into_iter().map(|row| (row.key, row.value)).collect(),
+        })
+    }
+
+    /// Write `value` under `key`, replacing any earlier value.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the statement fails.
+    pub async fn put(&self, key: &str, value: &str) -> Result<(), Error> {
+        self.put_all(&[(key, value)]).await
+    }
+
+    /// Write every key and value of `values` in one batch, which D1 applies
+    /// as a transaction: either every value is written or none is.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a statement fails.
+    pub async fn put_all(&self, values: &[(&str, &str)]) -> Result<(), Error> {
+        let upsert = format!(
+            "INSERT INTO {} (key, value, updated_at) VALUES (?1, ?2, unixepoch()) \
+             ON CONFLICT (key) DO UPDATE SET \
+             value = excluded.value, updated_at = excluded.updated_at",
+            self.table,
+        );
+        let statements = values
+            .iter()
+            .map(|(key, value)| self.statement(&upsert, &[key, value]))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.database
+            .batch(statements)
+            .await
+            .map_err(|error| self.error(&error))?;
+        Ok(())
+    }
+
+    /// Delete the value under `key`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the statement fails.
+    pub async fn delete(&self, key: &str) -> Result<(), Error> {
+        self.run(
+            &format!("DELETE FROM {} WHERE key = ?1", self.table),
+            &[key],
+        )
+        .await
+    }
+
+    /// `sql` with the parameters `?1`, `?2`, … bound to `values`.
+    fn statement(&self, sql: &str, values: &[&str]) -> Result<D1PreparedStatement, Error> {
+        let values: Vec<JsValue> = values.iter().map(|value| JsValue::from(*value)).collect();
+        self.database
+            .prepare(sql)
+            .bind(&values)
+            .map_err(|error| self.error(&error))
+    }
+
+    async fn run(&self, sql: &str, values: &[&str]) -> Result<(), Error> {
+        self.statement(sql, values)?
+            .run()
+            .await
+            .map_err(|error| self.error(&error))?;
+        Ok(())
+    }
+
+    async fn query<T: DeserializeOwned>(
+        &self,
+        sql: &str,
+        values: &[&str],
+    ) -> Result<Vec<T>, Error> {
+        self.statement(sql, values)?
+            .all()
+            .await
+            .and_then(|result| result.results())
+            .map_err(|error| self.error(&error))
+    }
+
+    fn error(&self, error: &worker::Error) -> Error {
+        Error::from(format!(
+            "D1 table `{}` in `{}`: {error}",
+            self.table, self.binding
+        ))
+    }
+}
+
+/// Values loaded from a [`D1ConfigStore`].
+///
+/// Every key resolves as a var and secret lookups always miss, since rows
+/// are not secrets.
+#[derive(Clone, Debug, Default)]
+pub struct D1Bindings {
+    values: HashMap<String, String>,
+}
+
+impl BindingSource for D1Bindings {
+    fn var(&self, name: &str) -> Result<Option<String>, BindingError> {
+        Ok(self.values.get(name).cloned())
+    }
+
+    fn secret(&self, _name: &str) -> Result<Option<String>, BindingError> {
+        Ok(None)
+    }
+
+    fn names(&self) -> Option<Vec<String>> {
+        Some(self.values.keys().cloned().collect())
+    }
+}
+
+/// Whether `name` is a plain SQL identifier, safe to splice into a
+/// statement unquoted.
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|char: char| char.is_ascii_digit())
+        && name
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '_')
+}
+
+/// A row of a [`D1ConfigStore`] table.
+#[derive(Deserialize)]
+struct Row {
+    key: String,
+    value: String,
+}
+
+/// A row of `pragma_table_info`.
+#[derive(Deserialize)]
+struct Column {
+    name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_plain_identifiers_name_tables() {
+        for name in [DEFAULT_D1_TABLE, "config", "_config_2"] {
+            assert!(is_identifier(name), "{name}");
+        }
+        for name in [
+            "",
+            "2config",
+            "config-values",
+            "config; DROP TABLE x",
+            "\"config\"",
+        ] {
+            assert!(!is_identifier(name), "{name}");
+        }
+    }
+}
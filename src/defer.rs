@@ -0,0 +1,32 @@
+use figment2::{
+    value::{Dict, Map},
+    Error, Metadata, Profile, Provider,
+};
+
+use crate::{CloudflareWorkersBindings, MergeMode};
+
+/// The fields a [`CloudflareWorkersBindings`] provider was told to
+/// [`defer`](CloudflareWorkersBindings::defer), as a provider of their own.
+///
+/// Join it into a figment, so that its values only fill in what earlier
+/// providers left unset.
+#[derive(Clone, Copy)]
+pub struct Deferred<'p, 'a> {
+    provider: &'p CloudflareWorkersBindings<'a>,
+}
+
+impl<'p, 'a> Deferred<'p, 'a> {
+    pub(crate) fn new(provider: &'p CloudflareWorkersBindings<'a>) -> Self {
+        Self { provider }
+    }
+}
+
+impl Provider for Deferred<'_, '_> {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("Cloudflare Worker environment (deferred)")
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        self.provider.emit(MergeMode::Deferred)
+    }
+}
@@ -0,0 +1,534 @@
+use std::fmt;
+
+use figment2::Error;
+use serde::{
+    de::{self, DeserializeOwned, DeserializeSeed, Visitor},
+    forward_to_deserialize_any, Deserializer, Serialize,
+};
+
+/// One binding a configuration type reads, as reported by [`describe`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct BindingSpec {
+    field: String,
+    binding: String,
+    #[serde(rename = "type")]
+    ty: String,
+    required: Option<bool>,
+}
+
+impl BindingSpec {
+    /// The name of the field, as serde deserialises it.
+    #[must_use]
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// The name of the binding the field is read from.
+    #[must_use]
+    pub fn binding(&self) -> &str {
+        &self.binding
+    }
+
+    /// The type the field expects, in serde's data model: `string`, `bool`,
+    /// `u16`, `option<string>`, `seq`, `struct` and so on, or `either` for a
+    /// figment `Either`.
+    #[must_use]
+    pub fn ty(&self) -> &str {
+        &self.ty
+    }
+
+    /// Whether extraction fails without the field, i.e. it is neither an
+    /// `Option` nor defaulted by serde, or `None` if that cannot be told;
+    /// see [`describe`].
+    #[must_use]
+    pub fn is_required(&self) -> Option<bool> {
+        self.required
+    }
+}
+
+impl fmt::Display for BindingSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self.required {
+            Some(true) => "required",
+            Some(false) => "optional",
+            None => "unknown",
+        };
+        write!(f, "{} ({}, {status})", self.binding, self.ty)
+    }
+}
+
+/// Describe the bindings [`from_struct`](crate::CloudflareWorkersBindings::from_struct)
+/// reads for `T`: each field's binding name, expected type, and whether it
+/// is required, so an app can log them at startup or render a
+/// "required bindings" page.
+///
+/// ```rust,ignore
+/// for spec in figment2_cloudflare_workers::describe::<Config>()? {
+///     console_log!("{spec}"); // e.g. `MAX_CONNECTIONS (u16, optional)`
+/// }
+/// ```
+///
+/// Types and defaults are found by deserialising `T` from synthetic values,
+/// leaving out those a field rejects, such as an empty string for a `Url`.
+/// serde tells missing fields in the order they are declared, so whether a
+/// field is required cannot be told, and is reported as unknown, when it is
+/// declared after a required field whose synthetic value is rejected.
+///
+/// # Errors
+///
+/// Fails if the fields of `T` cannot be discovered.
+pub fn describe<T: DeserializeOwned + 'static>() -> Result<Vec<BindingSpec>, Error> {
+    let fields = crate::discover_field_names::<T>()?;
+    Ok(fields
+        .iter()
+        .map(|field| {
+            let ty = field_type::<T>(field);
+            BindingSpec {
+                field: (*field).to_owned(),
+                binding: field.to_uppercase(),
+                required: is_required::<T>(fields, field),
+                ty,
+            }
+        })
+        .collect())
+}
+
+/// Whether `T` fails to deserialise without `field`, as
+/// [`BindingSpec::is_required`] reports it.
+///
+/// The other fields are given synthetic values, and left out when they
+/// reject them. A field missing after that which serde checks after `field`
+/// shows that `field` itself was not missed.
+fn is_required<T: DeserializeOwned>(fields: &[&'static str], field: &'static str) -> Option<bool> {
+    let position = |name| fields.iter().position(|other| *other == name);
+    let mut keys: Vec<_> = fields
+        .iter()
+        .copied()
+        .filter(|other| *other != field)
+        .collect();
+    loop {
+        match T::deserialize(Entries {
+            keys: keys.clone(),
+            current: None,
+            record: None,
+        }) {
+            Ok(_) => return Some(false),
+            Err(ProbeError::Missing(missing)) if missing == field => return Some(true),
+            Err(ProbeError::Missing(missing)) => {
+                return (position(missing)? > position(field)?).then_some(false)
+            }
+            Err(ProbeError::Rejected(key)) if keys.contains(&key) => {
+                keys.retain(|other| *other != key);
+            }
+            Err(_) => return None,
+        }
+    }
+}
+
+/// The type `field` of `T` expects, as [`BindingSpec::ty`] reports it.
+pub(crate) fn field_type<T: DeserializeOwned>(field: &'static str) -> String {
+    let mut ty = String::new();
+    let _ = T::deserialize(Entries {
+        keys: vec![field],
+        current: None,
+        record: Some(&mut ty),
+    });
+    ty
+}
+
+/// What the visitor of figment's own `Value` expects. Figment's `Either`
+/// deserialises a pseudo-struct with it, then tries each side on the value.
+const FIGMENT_VALUE: &str = "any valid figment value";
+
+/// What a visitor expects, as a string.
+struct Expecting<'v, V>(&'v V);
+
+impl<'de, V: Visitor<'de>> fmt::Display for Expecting<'_, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.expecting(f)
+    }
+}
+
+/// The error of a probing deserialisation, recording the first missing
+/// field or the entry whose synthetic value was rejected.
+#[derive(Debug)]
+enum ProbeError {
+    Missing(&'static str),
+    Rejected(&'static str),
+    Other,
+}
+
+impl fmt::Display for ProbeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(field) => write!(f, "missing field `{field}`"),
+            Self::Rejected(field) => write!(f, "invalid value for field `{field}`"),
+            Self::Other => f.write_str("probe"),
+        }
+    }
+}
+
+impl std::error::Error for ProbeError {}
+
+impl de::Error for ProbeError {
+    fn custom<T: fmt::Display>(_message: T) -> Self {
+        Self::Other
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self::Missing(field)
+    }
+}
+
+/// A synthetic map with the keys `keys`, consumed front to back, whose
+/// values are synthesised or, if `record` is set, describe their type into
+/// it. `current` is the key whose value is next.
+struct Entries<'r> {
+    keys: Vec<&'static str>,
+    current: Option<&'static str>,
+    record: Option<&'r mut String>,
+}
+
+impl<'de> Deserializer<'de> for Entries<'_> {
+    type Error = ProbeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> de::MapAccess<'de> for Entries<'_> {
+    type Error = ProbeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        if self.keys.is_empty() {
+            return Ok(None);
+        }
+        let key = self.keys.remove(0);
+        self.current = Some(key);
+        seed.deserialize(de::value::BorrowedStrDeserializer::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let recording = self.record.is_some();
+        seed.deserialize(Probe {
+            record: self.record.as_deref_mut(),
+        })
+        .map_err(|error| match (self.current, recording) {
+            (Some(key), false) => ProbeError::Rejected(key),
+            _ => error,
+        })
+    }
+}
+
+/// A synthetic sequence of `len` values.
+struct Elements(usize);
+
+impl<'de> de::SeqAccess<'de> for Elements {
+    type Error = ProbeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        if self.0 == 0 {
+            return Ok(None);
+        }
+        self.0 -= 1;
+        seed.deserialize(Probe { record: None }).map(Some)
+    }
+}
+
+/// A value that, if `record` is set, describes the type it is deserialised
+/// as and fails, and otherwise synthesises a zero value of that type.
+struct Probe<'r> {
+    record: Option<&'r mut String>,
+}
+
+impl Probe<'_> {
+    /// Record `ty`, failing if recording, or else produce `value`.
+    fn value<T>(
+        self,
+        ty: &str,
+        value: impl FnOnce() -> Result<T, ProbeError>,
+    ) -> Result<T, ProbeError> {
+        match self.record {
+            Some(record) => {
+                record.push_str(ty);
+                Err(ProbeError::Other)
+            }
+            None => value(),
+        }
+    }
+}
+
+/// Probe methods that record `$ty` or visit a fixed zero value.
+macro_rules! probe_values {
+    ($($method:ident => $ty:literal, $visit:ident($($zero:expr)?);)*) => {
+        $(fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            self.value($ty, || visitor.$visit($($zero)?))
+        })*
+    };
+}
+
+impl<'de> Deserializer<'de> for Probe<'_> {
+    type Error = ProbeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.value("any", || visitor.visit_unit())
+    }
+
+    probe_values! {
+        deserialize_bool => "bool", visit_bool(false);
+        deserialize_i8 => "i8", visit_i8(0);
+        deserialize_i16 => "i16", visit_i16(0);
+        deserialize_i32 => "i32", visit_i32(0);
+        deserialize_i64 => "i64", visit_i64(0);
+        deserialize_u8 => "u8", visit_u8(0);
+        deserialize_u16 => "u16", visit_u16(0);
+        deserialize_u32 => "u32", visit_u32(0);
+        deserialize_u64 => "u64", visit_u64(0);
+        deserialize_f32 => "f32", visit_f32(0.0);
+        deserialize_f64 => "f64", visit_f64(0.0);
+        deserialize_char => "char", visit_char(' ');
+        deserialize_str => "string", visit_str("");
+        deserialize_string => "string", visit_str("");
+        deserialize_bytes => "bytes", visit_bytes(&[]);
+        deserialize_byte_buf => "bytes", visit_bytes(&[]);
+        deserialize_unit => "unit", visit_unit();
+        deserialize_identifier => "string", visit_str("");
+        deserialize_ignored_any => "any", visit_unit();
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.record {
+            Some(record) => {
+                record.push_str("option<");
+                let inner = visitor.visit_some(Probe {
+                    record: Some(&mut *record),
+                });
+                record.push('>');
+                inner
+            }
+            None => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value("unit", || visitor.visit_unit())
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.value("seq", || visitor.visit_seq(Elements(0)))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value("tuple", || visitor.visit_seq(Elements(len)))
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value("tuple", || visitor.visit_seq(Elements(len)))
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.value("map", || {
+            visitor.visit_map(Entries {
+                keys: Vec::new(),
+                current: None,
+                record: None,
+            })
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let ty = if Expecting(&visitor).to_string() == FIGMENT_VALUE {
+            "either"
+        } else {
+            "struct"
+        };
+        self.value(ty, || {
+            visitor.visit_map(Entries {
+                keys: fields.to_vec(),
+                current: None,
+                record: None,
+            })
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value("enum", || {
+            let variant = variants.first().ok_or(ProbeError::Other)?;
+            visitor.visit_enum(Variant(variant))
+        })
+    }
+}
+
+/// The first variant of a synthetic enum value.
+struct Variant(&'static str);
+
+impl<'de> de::EnumAccess<'de> for Variant {
+    type Error = ProbeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let variant = seed.deserialize(de::value::BorrowedStrDeserializer::new(self.0))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Variant {
+    type Error = ProbeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(Probe { record: None })
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(Elements(len))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(Entries {
+            keys: fields.to_vec(),
+            current: None,
+            record: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU16;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    fn required<T: DeserializeOwned + 'static>() -> Vec<(String, Option<bool>)> {
+        describe::<T>()
+            .unwrap()
+            .into_iter()
+            .map(|spec| (spec.field, spec.required))
+            .collect()
+    }
+
+    #[test]
+    fn defaulted_fields_beside_rejected_values_are_optional() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Config {
+            #[serde(default)]
+            port: u16,
+            workers: NonZeroU16,
+            name: Option<String>,
+            #[serde(default)]
+            verbose: bool,
+        }
+
+        assert_eq!(
+            required::<Config>(),
+            [
+                ("port".to_owned(), Some(false)),
+                ("workers".to_owned(), Some(true)),
+                ("name".to_owned(), None),
+                ("verbose".to_owned(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn fields_are_probed_with_the_others_present() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Config {
+            url: String,
+            #[serde(default)]
+            port: u16,
+            timeout: Option<NonZeroU16>,
+            retries: u8,
+        }
+
+        assert_eq!(
+            required::<Config>(),
+            [
+                ("url".to_owned(), Some(true)),
+                ("port".to_owned(), Some(false)),
+                ("timeout".to_owned(), Some(false)),
+                ("retries".to_owned(), Some(true)),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_requirements_are_displayed() {
+        let spec = BindingSpec {
+            field: "name".to_owned(),
+            binding: "NAME".to_owned(),
+            ty: "string".to_owned(),
+            required: None,
+        };
+        assert_eq!(spec.to_string(), "NAME (string, unknown)");
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+use figment2::{value::Value, Error, Metadata, Profile};
+use serde::{
+    de::{
+        self, value::SeqDeserializer, DeserializeSeed, IntoDeserializer, MapAccess, Unexpected,
+        Visitor,
+    },
+    forward_to_deserialize_any, Deserialize, Deserializer,
+};
+
+use crate::BindingSource;
+
+/// Deserialise `T` straight from the bindings in `source`, without a
+/// figment:
+///
+/// ```rust,ignore
+/// let config: Config = figment2_cloudflare_workers::from_env(&env)?;
+/// ```
+///
+/// Each field is read from its uppercased binding, var first, as
+/// [`CloudflareWorkersBindings`](crate::CloudflareWorkersBindings) does by
+/// default, and its value is parsed as the field's type asks: numbers from
+/// their text, booleans as [`extract_config`](crate::extract_config) reads
+/// them, sequences from comma-separated lists, and unit enum variants by
+/// name. Missing bindings, and bindings that cannot
+/// be read as strings, are left out, so `Option` and `#[serde(default)]`
+/// fields fall back as usual.
+///
+/// This does the work of one provider in one pass, for workers whose
+/// configuration comes from bindings alone; the provider is still needed
+/// for layering, defaults, diagnostics and the other options it is built
+/// with.
+///
+/// # Errors
+///
+/// Fails if a required binding is missing or a value does not parse as its
+/// field's type, naming the field.
+pub fn from_env<'de, T: Deserialize<'de>>(source: &dyn BindingSource) -> Result<T, Error> {
+    T::deserialize(BindingsDeserializer::new(source))
+}
+
+/// A [`Deserializer`] of a struct from the bindings in a [`BindingSource`];
+/// see [`from_env`].
+pub struct BindingsDeserializer<'s> {
+    source: &'s dyn BindingSource,
+}
+
+impl<'s> BindingsDeserializer<'s> {
+    /// Deserialise from the bindings in `source`.
+    #[must_use]
+    pub fn new(source: &'s dyn BindingSource) -> Self {
+        Self { source }
+    }
+}
+
+impl<'de> Deserializer<'de> for BindingsDeserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::from(
+            "bindings can only be deserialised into a struct of named fields",
+        ))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_map(Bindings {
+            source: self.source,
+            prefetched: self.source.prefetch(),
+            fields: fields.iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple tuple_struct map enum
+        identifier ignored_any
+    }
+}
+
+/// The bound fields of a struct, as the entries of a map.
+struct Bindings<'s> {
+    source: &'s dyn BindingSource,
+    /// Every binding, if the source reads them in one pass.
+    prefetched: Option<HashMap<String, String>>,
+    fields: std::slice::Iter<'static, &'static str>,
+    /// The field whose key was just visited, and its binding's value.
+    value: Option<(&'static str, String)>,
+}
+
+impl Bindings<'_> {
+    /// The value of `binding`, as a var or else as a secret.
+    fn lookup(&self, binding: &str) -> Option<String> {
+        if let Some(prefetched) = &self.prefetched {
+            return prefetched.get(binding).cloned();
+        }
+        let var = self.source.var(binding).ok().flatten();
+        var.or_else(|| self.source.secret(binding).ok().flatten())
+    }
+}
+
+impl<'de> MapAccess<'de> for Bindings<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        while let Some(field) = self.fields.next() {
+            if let Some(value) = self.lookup(&field.to_uppercase()) {
+                self.value = Some((field, value));
+                return seed.deserialize(field.into_deserializer()).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let (field, value) = self.value.take().expect("a key was visited first");
+        seed.deserialize(BindingValue(value)).map_err(|mut error| {
+            error.path.insert(0, field.to_owned());
+            error.profile = Some(Profile::Default);
+            error.metadata = Some(Metadata::named(format!(
+                "binding `{}`",
+                field.to_uppercase()
+            )));
+            error
+        })
+    }
+}
+
+/// The value of one binding, parsed as the type it is deserialised into.
+struct BindingValue(String);
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            let value = self.0.trim().parse().map_err(|_| {
+                <Error as de::Error>::invalid_value(Unexpected::Str(&self.0), &visitor)
+            })?;
+            visitor.$visit(value)
+        }
+    )*};
+}
+
+impl<'de> Deserializer<'de> for BindingValue {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    // As `extract_lossy` reads them: `true`, `yes`, `on` or `1`, and
+    // `false`, `no`, `off` or `0`, in any case.
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match Value::from(self.0.trim()).to_bool_lossy() {
+            Some(value) => visitor.visit_bool(value),
+            None => Err(<Error as de::Error>::invalid_value(
+                Unexpected::Str(&self.0),
+                &visitor,
+            )),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let items = self
+            .0
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| BindingValue(item.to_owned()));
+        let mut items = SeqDeserializer::new(items);
+        let value = visitor.visit_seq(&mut items)?;
+        items.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self.0.trim().into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, Error> for BindingValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
@@ -0,0 +1,131 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::Serialize;
+
+use crate::{render, Snapshot, REDACTED};
+
+/// Compare two snapshots, e.g. the configuration before and after a hot
+/// reload, key by key.
+///
+/// Nested keys are dotted. Values of fields that are secret in either
+/// snapshot are reported as [`REDACTED`], so the diff can be logged as-is;
+/// a changed secret still shows up as a change.
+///
+/// ```rust,ignore
+/// let diff = figment2_cloudflare_workers::diff(&previous, &current);
+/// if !diff.is_empty() {
+///     console_log!("configuration changed:\n{diff}");
+/// }
+/// ```
+#[must_use]
+pub fn diff(old: &Snapshot, new: &Snapshot) -> ConfigDiff {
+    let show = |key: &str, value: String| {
+        let field = key.split('.').next().unwrap_or(key);
+        if old.is_secret(field) || new.is_secret(field) {
+            REDACTED.to_owned()
+        } else {
+            value
+        }
+    };
+
+    let (old, mut new) = (render::flatten(old.values()), render::flatten(new.values()));
+    let mut diff = ConfigDiff::default();
+    for (key, old_value) in old {
+        match new.remove(&key) {
+            None => {
+                let old_value = show(&key, old_value);
+                diff.removed.insert(key, old_value);
+            }
+            Some(new_value) if new_value != old_value => {
+                let change = Change {
+                    old: show(&key, old_value),
+                    new: show(&key, new_value),
+                };
+                diff.changed.insert(key, change);
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, new_value) in new {
+        let new_value = show(&key, new_value);
+        diff.added.insert(key, new_value);
+    }
+    diff
+}
+
+/// The keys added, removed and changed between two snapshots; see [`diff`].
+///
+/// Serialises as `{ "added": { key: value }, "removed": { key: value },
+/// "changed": { key: { "old": value, "new": value } } }` for structured
+/// logs, and displays as one `+`, `-` or `~` line per key.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ConfigDiff {
+    added: BTreeMap<String, String>,
+    removed: BTreeMap<String, String>,
+    changed: BTreeMap<String, Change>,
+}
+
+/// The old and new value of a changed key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Change {
+    old: String,
+    new: String,
+}
+
+impl ConfigDiff {
+    /// Whether the snapshots hold the same keys and values.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Keys present only in the new snapshot, with their values.
+    pub fn added(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.added
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Keys present only in the old snapshot, with their values.
+    pub fn removed(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.removed
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Keys whose values differ, with the change.
+    pub fn changed(&self) -> impl Iterator<Item = (&str, &Change)> {
+        self.changed
+            .iter()
+            .map(|(key, change)| (key.as_str(), change))
+    }
+}
+
+impl Change {
+    /// The value in the old snapshot.
+    #[must_use]
+    pub fn old_value(&self) -> &str {
+        &self.old
+    }
+
+    /// The value in the new snapshot.
+    #[must_use]
+    pub fn new_value(&self) -> &str {
+        &self.new
+    }
+}
+
+impl fmt::Display for ConfigDiff {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, value) in self.added() {
+            writeln!(formatter, "+ {key} = {value:?}")?;
+        }
+        for (key, value) in self.removed() {
+            writeln!(formatter, "- {key} = {value:?}")?;
+        }
+        for (key, change) in self.changed() {
+            writeln!(formatter, "~ {key}: {:?} -> {:?}", change.old, change.new)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+use std::{collections::BTreeMap, io::Read, path::Path};
+
+use figment2::Error;
+
+use crate::{BindingError, BindingSource};
+
+/// A [`BindingSource`] reading a `.env` file, such as the `.dev.vars` file
+/// `wrangler dev` reads, without touching the process environment.
+///
+/// Chain it as the last fallback, so that developer overrides fill in only
+/// the bindings nothing else provides, looked up under the same names,
+/// casings and aliases as every other source:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, DotenvFile, ProcessEnv};
+///
+/// // .env: DATABASE_URL=postgres://localhost
+/// let dotenv = DotenvFile::find()?;
+/// let config: Config = Figment::new()
+///     .merge(CloudflareWorkersBindings::from_sources::<Config>(&[&ProcessEnv, &dotenv]))
+///     .extract()?;
+/// ```
+///
+/// Every name in the file answers both var and secret lookups. Later lines
+/// override earlier ones, and `${NAME}` references are expanded as
+/// `dotenvy` expands them.
+#[derive(Clone, Debug, Default)]
+pub struct DotenvFile {
+    bindings: BTreeMap<String, String>,
+}
+
+impl DotenvFile {
+    /// Parse the contents of a `.env` file.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a line is not a valid assignment.
+    pub fn parse(contents: &str) -> Result<Self, Error> {
+        Self::from_reader(contents.as_bytes(), ".env file")
+    }
+
+    /// Read the `.env` file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file cannot be read, or a line is not a valid
+    /// assignment.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let describe = || format!(".env file `{}`", path.display());
+        let file = std::fs::File::open(path)
+            .map_err(|error| Error::from(format!("{}: {error}", describe())))?;
+        Self::from_reader(file, &describe())
+    }
+
+    /// Read the `.env` file in the current directory or the nearest of its
+    /// ancestors, or no bindings if there is none, so that checkouts
+    /// without one, such as on CI, read the same bindings as production.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a file is found but cannot be read, or a line is not a
+    /// valid assignment.
+    pub fn find() -> Result<Self, Error> {
+        let dir = std::env::current_dir()
+            .map_err(|error| Error::from(format!("current directory: {error}")))?;
+        Self::find_from(&dir)
+    }
+
+    /// Read the `.env` file in `dir` or the nearest of its ancestors, or no
+    /// bindings if there is none.
+    fn find_from(dir: &Path) -> Result<Self, Error> {
+        match dir
+            .ancestors()
+            .map(|dir| dir.join(".env"))
+            .find(|path| path.is_file())
+        {
+            Some(path) => Self::read(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// The names and values in the file, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.bindings
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    fn from_reader<R: Read>(reader: R, describe: &str) -> Result<Self, Error> {
+        let bindings = dotenvy::from_read_iter(reader)
+            .collect::<Result<_, _>>()
+            .map_err(|error| Error::from(format!("invalid {describe}: {error}")))?;
+        Ok(Self { bindings })
+    }
+}
+
+impl BindingSource for DotenvFile {
+    fn var(&self, name: &str) -> Result<Option<String>, BindingError> {
+        Ok(self.bindings.get(name).cloned())
+    }
+
+    fn secret(&self, name: &str) -> Result<Option<String>, BindingError> {
+        Ok(self.bindings.get(name).cloned())
+    }
+
+    fn names(&self) -> Option<Vec<String>> {
+        Some(self.bindings.keys().cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use figment2::Figment;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::CloudflareWorkersBindings;
+
+    /// A fresh directory under the system's temporary directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "figment2-cloudflare-workers-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn later_lines_override_earlier_ones() {
+        let dotenv =
+            DotenvFile::parse("API_KEY=first\n# comment\nAPI_KEY=second\nEMPTY=\n").unwrap();
+        assert_eq!(
+            dotenv.iter().collect::<Vec<_>>(),
+            [("API_KEY", "second"), ("EMPTY", "")]
+        );
+        assert_eq!(dotenv.var("API_KEY").unwrap().as_deref(), Some("second"));
+        assert_eq!(dotenv.secret("API_KEY").unwrap().as_deref(), Some("second"));
+        assert_eq!(dotenv.var("MISSING").unwrap(), None);
+    }
+
+    #[test]
+    fn references_are_expanded() {
+        let dotenv = DotenvFile::parse(
+            "API_HOST=api.example.com\nAPI_BASE_URL=https://${API_HOST}/v1\nQUOTED='${API_HOST}'\n",
+        )
+        .unwrap();
+        assert_eq!(
+            dotenv.var("API_BASE_URL").unwrap().as_deref(),
+            Some("https://api.example.com/v1")
+        );
+        assert_eq!(
+            dotenv.var("QUOTED").unwrap().as_deref(),
+            Some("${API_HOST}")
+        );
+    }
+
+    #[test]
+    fn invalid_lines_are_rejected() {
+        let error = DotenvFile::parse("API_KEY=key\nNOT AN ASSIGNMENT\n").unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("invalid .env file: Error parsing line"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn missing_files_fail_to_read() {
+        let path = temp_dir("missing").join(".env");
+        let error = DotenvFile::read(&path).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with(&format!(".env file `{}`: ", path.display())),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn the_nearest_file_is_found() {
+        let dir = temp_dir("found");
+        let nested = dir.join("crates").join("app");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.join(".env"), "API_KEY=from-root\n").unwrap();
+        assert_eq!(
+            DotenvFile::find_from(&nested)
+                .unwrap()
+                .var("API_KEY")
+                .unwrap()
+                .as_deref(),
+            Some("from-root")
+        );
+
+        std::fs::write(nested.join(".env"), "API_KEY=from-app\n").unwrap();
+        assert_eq!(
+            DotenvFile::find_from(&nested)
+                .unwrap()
+                .var("API_KEY")
+                .unwrap()
+                .as_deref(),
+            Some("from-app")
+        );
+    }
+
+    #[test]
+    fn no_bindings_are_found_without_a_file() {
+        let dir = temp_dir("absent");
+        let dotenv = DotenvFile::find_from(&dir).unwrap();
+        assert_eq!(dotenv.names(), Some(Vec::new()));
+    }
+
+    #[test]
+    fn a_fallback_fills_in_only_unbound_fields() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Config {
+            api_key: String,
+            max_retries: u8,
+        }
+
+        let primary = DotenvFile::parse("API_KEY=production-key\n").unwrap();
+        let dotenv = DotenvFile::parse("API_KEY=developer-key\nMAX_RETRIES=3\n").unwrap();
+        let provider = CloudflareWorkersBindings::from_struct::<Config>(&primary).fallback(&dotenv);
+        let config: Config = Figment::from(&provider).extract_lossy().unwrap();
+        assert_eq!(
+            config,
+            Config {
+                api_key: "production-key".to_owned(),
+                max_retries: 3,
+            }
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use figment2::Error;
+
+/// The prefix marking a value as AES-256-GCM encrypted.
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+const NONCE_LEN: usize = 12;
+
+/// Encrypt `plaintext`, the value of `binding`, into the `enc:v1:...` format
+/// understood by
+/// [`decrypt_with`](crate::CloudflareWorkersBindings::decrypt_with).
+///
+/// The output is [`ENCRYPTED_PREFIX`] followed by the standard base64
+/// encoding of `nonce` and the AES-256-GCM ciphertext. A `nonce` must never be
+/// reused with the same `key`. The binding name is authenticated alongside
+/// the ciphertext, so a value only decrypts under the binding it was
+/// encrypted for and cannot be moved to another field.
+///
+/// # Panics
+///
+/// Panics if `plaintext` exceeds the AES-GCM message size limit of 64 GiB.
+#[must_use]
+pub fn encrypt_value(
+    key: &[u8; 32],
+    nonce: &[u8; NONCE_LEN],
+    binding: &str,
+    plaintext: &str,
+) -> String {
+    let payload = Payload {
+        msg: plaintext.as_bytes(),
+        aad: binding.as_bytes(),
+    };
+    let ciphertext = Aes256Gcm::new(key.into())
+        .encrypt(Nonce::from_slice(nonce), payload)
+        .expect("plaintext within the AES-GCM message size limit");
+    let mut payload = nonce.to_vec();
+    payload.extend(ciphertext);
+    format!("{ENCRYPTED_PREFIX}{}", STANDARD.encode(payload))
+}
+
+/// A decryption key read from a worker binding holding the standard base64
+/// encoding of 32 random bytes.
+pub(crate) struct DecryptionKey(Aes256Gcm);
+
+impl DecryptionKey {
+    pub(crate) fn decode(binding: &str, encoded: &str) -> Result<Self, Error> {
+        let key = STANDARD
+            .decode(encoded.trim())
+            .ok()
+            .filter(|key| key.len() == 32)
+            .ok_or_else(|| {
+                Error::from(format!(
+                    "decryption key binding `{binding}` is not a base64-encoded 32-byte key"
+                ))
+            })?;
+        Ok(Self(
+            Aes256Gcm::new_from_slice(&key).expect("key length checked"),
+        ))
+    }
+
+    /// Decrypt `value`, read for `binding`, if it carries
+    /// [`ENCRYPTED_PREFIX`], returning it unchanged otherwise.
+    pub(crate) fn decrypt(&self, binding: &str, value: String) -> Result<String, Error> {
+        let Some(encoded) = value.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(value);
+        };
+        STANDARD
+            .decode(encoded)
+            .ok()
+            .filter(|payload| payload.len() > NONCE_LEN)
+            .and_then(|payload| {
+                let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+                let payload = Payload {
+                    msg: ciphertext,
+                    aad: binding.as_bytes(),
+                };
+                self.0.decrypt(Nonce::from_slice(nonce), payload).ok()
+            })
+            .and_then(|plaintext| String::from_utf8(plaintext).ok())
+            .ok_or_else(|| Error::from(format!("failed to decrypt binding `{binding}`")))
+    }
+}
+
+pub(crate) fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; 32] = [7; 32];
+    const NONCE: [u8; NONCE_LEN] = [9; NONCE_LEN];
+
+    fn key() -> DecryptionKey {
+        DecryptionKey::decode("CONFIG_KEY", &STANDARD.encode(KEY)).unwrap()
+    }
+
+    #[test]
+    fn values_decrypt_under_their_own_binding() {
+        let encrypted = encrypt_value(&KEY, &NONCE, "API_TOKEN", "token");
+        assert!(is_encrypted(&encrypted));
+        assert_eq!(key().decrypt("API_TOKEN", encrypted).unwrap(), "token");
+    }
+
+    #[test]
+    fn values_moved_to_another_binding_fail_to_decrypt() {
+        let encrypted = encrypt_value(&KEY, &NONCE, "API_TOKEN", "token");
+        assert_eq!(
+            key()
+                .decrypt("ADMIN_TOKEN", encrypted)
+                .unwrap_err()
+                .to_string(),
+            "failed to decrypt binding `ADMIN_TOKEN`"
+        );
+    }
+
+    #[test]
+    fn plain_values_pass_through() {
+        assert_eq!(
+            key().decrypt("API_TOKEN", "plain".to_owned()).unwrap(),
+            "plain"
+        );
+    }
+
+    #[cfg(feature = "test-util")]
+    mod provider {
+        use figment2::Provider;
+        use serde::Deserialize;
+
+        use super::*;
+        use crate::{CloudflareWorkersBindings, MockBindings};
+
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Config {
+            api_token: String,
+        }
+
+        fn resolve(
+            bindings: &MockBindings,
+            fallback: &MockBindings,
+        ) -> Result<String, figment2::Error> {
+            let data = CloudflareWorkersBindings::from_struct::<Config>(bindings)
+                .fallback(fallback)
+                .decrypt_with("CONFIG_KEY")
+                .data()?;
+            Ok(data.values().next().unwrap()["api_token"]
+                .as_str()
+                .unwrap()
+                .to_owned())
+        }
+
+        fn token() -> String {
+            encrypt_value(&KEY, &NONCE, "API_TOKEN", "token")
+        }
+
+        #[test]
+        fn the_key_is_read_from_a_primary_secret() {
+            let bindings = MockBindings::new()
+                .with_var("API_TOKEN", token())
+                .with_secret("CONFIG_KEY", STANDARD.encode(KEY));
+            assert_eq!(resolve(&bindings, &MockBindings::new()).unwrap(), "token");
+        }
+
+        #[test]
+        fn keys_in_vars_or_fallbacks_are_refused() {
+            let missing =
+                "decryption key binding `CONFIG_KEY` is not a secret of the primary source";
+            let bindings = MockBindings::new()
+                .with_var("API_TOKEN", token())
+                .with_var("CONFIG_KEY", STANDARD.encode(KEY));
+            assert_eq!(
+                resolve(&bindings, &MockBindings::new())
+                    .unwrap_err()
+                    .to_string(),
+                missing
+            );
+
+            let bindings = MockBindings::new().with_var("API_TOKEN", token());
+            let fallback = MockBindings::new().with_secret("CONFIG_KEY", STANDARD.encode(KEY));
+            assert_eq!(
+                resolve(&bindings, &fallback).unwrap_err().to_string(),
+                missing
+            );
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use figment2::{Error, Figment};
+use serde::de::DeserializeOwned;
+
+use crate::{BindingSource, CloudflareWorkersBindings, MergeMode, TaggedValue};
+
+/// Shorthands for merging [`CloudflareWorkersBindings`] into a [`Figment`].
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::FigmentExt;
+///
+/// let config: Config = Figment::from_cloudflare::<Config>(&env).extract()?;
+///
+/// let config: Config = Figment::from(Serialized::defaults(Config::default()))
+///     .with_cloudflare::<Config>(&env)
+///     .extract()?;
+/// ```
+pub trait FigmentExt {
+    /// Create a figment with just the bindings for the fields of `T`.
+    #[must_use]
+    fn from_cloudflare<T: DeserializeOwned + 'static>(source: &dyn BindingSource) -> Self;
+
+    /// Merge in the bindings for the fields of `T`, so they take precedence
+    /// over the providers already in the figment.
+    #[must_use]
+    fn with_cloudflare<T: DeserializeOwned + 'static>(self, source: &dyn BindingSource) -> Self;
+
+    /// Merge in `provider`, joining its
+    /// [`deferred`](CloudflareWorkersBindings::defer) fields instead so that
+    /// they only fill in values the figment does not already have, and
+    /// admerging its [`accumulated`](CloudflareWorkersBindings::accumulate)
+    /// ones so that their lists are appended to the figment's.
+    #[must_use]
+    fn merge_bindings(self, provider: &CloudflareWorkersBindings<'_>) -> Self;
+
+    /// Merge in `provider` as [`merge_bindings`](Self::merge_bindings)
+    /// does, one value at a time, so that each is tagged with metadata of
+    /// its own naming where it came from. The metadata's
+    /// [`source`](figment2::Metadata::source) is a custom one such as
+    /// ``secret `API_KEY` ``, ``var `API_BASE_URL` (fallback 1)`` or
+    /// ``default for `max_retries` ``:
+    ///
+    /// ```rust,ignore
+    /// let figment = Figment::new().merge_tagged(&provider);
+    /// let source = figment
+    ///     .find_metadata("api_key")
+    ///     .and_then(|metadata| metadata.source.as_ref()?.custom());
+    /// assert_eq!(source, Some("secret `API_KEY`"));
+    /// ```
+    ///
+    /// The bindings are resolved once, when merged.
+    #[must_use]
+    fn merge_tagged(self, provider: &CloudflareWorkersBindings<'_>) -> Self;
+}
+
+impl FigmentExt for Figment {
+    fn from_cloudflare<T: DeserializeOwned + 'static>(source: &dyn BindingSource) -> Self {
+        Figment::from(CloudflareWorkersBindings::from_struct::<T>(source))
+    }
+
+    fn with_cloudflare<T: DeserializeOwned + 'static>(self, source: &dyn BindingSource) -> Self {
+        self.merge(CloudflareWorkersBindings::from_struct::<T>(source))
+    }
+
+    fn merge_bindings(self, provider: &CloudflareWorkersBindings<'_>) -> Self {
+        self.merge(provider)
+            .join(provider.deferred())
+            .admerge(provider.accumulated())
+    }
+
+    fn merge_tagged(self, provider: &CloudflareWorkersBindings<'_>) -> Self {
+        match provider.emit_tagged() {
+            Ok(values) => values
+                .into_iter()
+                .fold(self, |figment, value| match value.mode {
+                    MergeMode::Merged => figment.merge(value),
+                    MergeMode::Deferred => figment.join(value),
+                    MergeMode::Accumulated => figment.admerge(value),
+                }),
+            Err(error) => self.merge(TaggedValue::failed(error)),
+        }
+    }
+}
+
+/// Extract `T` from its bindings alone, for the common case where no other
+/// provider is in the stack.
+///
+/// Extraction is lossy (see [`Figment::extract_lossy`]), so numeric and
+/// boolean fields can be read from string bindings:
+///
+/// ```rust,ignore
+/// #[derive(Deserialize)]
+/// struct Config {
+///     database_url: String,
+///     max_connections: u16,
+/// }
+///
+/// let config: Config = figment2_cloudflare_workers::extract_config(&env)?;
+/// ```
+///
+/// # Errors
+///
+/// Fails if the bindings cannot be resolved or do not form a valid `T`.
+pub fn extract_config<T: DeserializeOwned + 'static>(
+    source: &dyn BindingSource,
+) -> Result<T, Error> {
+    Figment::from_cloudflare::<T>(source).extract_lossy()
+}
@@ -0,0 +1,60 @@
+use std::{ops::Deref, sync::Arc};
+
+use axum::{
+    extract::{rejection::ExtensionRejection, FromRequestParts},
+    http::request::Parts,
+    Extension,
+};
+
+/// An axum extractor for the configuration a [`ConfigLayer`](crate::ConfigLayer)
+/// inserted into the request, so handlers declare the configuration they
+/// need as an argument:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CachedConfig, Config, ConfigLayer};
+///
+/// static CONFIG: CachedConfig<AppConfig> = CachedConfig::new();
+///
+/// #[event(fetch)]
+/// async fn fetch(request: HttpRequest, env: Env, _context: Context) -> Result<axum::http::Response<Body>> {
+///     let layer = ConfigLayer::from_cache(&CONFIG, &env).map_err(|error| Error::RustError(error.to_string()))?;
+///     Ok(Router::new().route("/", get(greet)).layer(layer).call(request).await?)
+/// }
+///
+/// async fn greet(Config(config): Config<AppConfig>) -> String {
+///     config.greeting.clone()
+/// }
+/// ```
+///
+/// Building the layer from a `static` [`CachedConfig`](crate::CachedConfig)
+/// extracts the configuration once per isolate. Extraction fails with a
+/// `500 Internal Server Error` if no layer inserted a `T`.
+#[derive(Debug)]
+pub struct Config<T>(pub Arc<T>);
+
+impl<T> Clone for Config<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> Deref for Config<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T, S> FromRequestParts<S> for Config<T>
+where
+    T: Send + Sync + 'static,
+    S: Send + Sync,
+{
+    type Rejection = ExtensionRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Extension(config) = Extension::<Arc<T>>::from_request_parts(parts, state).await?;
+        Ok(Self(config))
+    }
+}
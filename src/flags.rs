@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use figment2::{
+    value::{Dict, Map, Value},
+    Error, Metadata, Profile, Provider,
+};
+
+use crate::{BindingSource, ConfigStore};
+
+/// The prefix of the KV keys and vars a flag is read from: the flag
+/// `new_checkout` is read from `FLAG_NEW_CHECKOUT`.
+pub const FLAG_PREFIX: &str = "FLAG_";
+
+/// The key [`FeatureFlags`] nest their values under as a figment
+/// [`Provider`].
+pub const FLAGS_KEY: &str = "flags";
+
+/// Feature flags stored in Workers KV, with defaults from the worker's
+/// vars.
+///
+/// Each declared flag is read from the KV key [`FLAG_PREFIX`] followed by
+/// its uppercased name, falling back to the var of the same name, so flags
+/// ship with a default in `wrangler.toml` and are flipped at runtime by
+/// writing to KV. Reads go through a [`ConfigStore`], so its
+/// [`max_age`](ConfigStore::max_age) keeps flags in the isolate between
+/// requests:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{ConfigStore, FeatureFlags};
+///
+/// let store = ConfigStore::new(&env, "FLAGS")?.max_age(Duration::from_secs(30));
+/// let flags = FeatureFlags::load(&store, &env, &["new_checkout", "checkout_layout"]).await?;
+/// if flags.enabled("new_checkout") {
+///     let layout = flags.variant("checkout_layout").unwrap_or("classic");
+///     // ...
+/// }
+/// ```
+///
+/// A flag is a boolean flag when it is read with
+/// [`enabled`](Self::enabled), and a variant flag, naming one of several
+/// behaviours, when read with [`variant`](Self::variant). As a
+/// [`Provider`], the flags are emitted under [`FLAGS_KEY`], so they can also
+/// be extracted into the configuration struct:
+///
+/// ```rust,ignore
+/// #[derive(Deserialize)]
+/// struct Config {
+///     database_url: String,
+///     flags: Flags,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Flags {
+///     new_checkout: bool,
+///     checkout_layout: String,
+/// }
+///
+/// let config: Config = Figment::from_cloudflare::<Config>(&env).merge(&flags).extract_lossy()?;
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeatureFlags {
+    values: BTreeMap<String, String>,
+}
+
+impl FeatureFlags {
+    /// Read the flags `names` from `store`, falling back to the vars of
+    /// `defaults`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a KV read fails, or a var exists but cannot be read.
+    pub async fn load(
+        store: &ConfigStore,
+        defaults: &dyn BindingSource,
+        names: &[&str],
+    ) -> Result<Self, Error> {
+        let keys: Vec<String> = names.iter().map(|name| flag_key(name)).collect();
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+        let stored = store.load_keys(&keys).await?;
+        let mut values = BTreeMap::new();
+        for (name, key) in names.iter().zip(keys) {
+            let value = match stored.var(key) {
+                Ok(Some(value)) => Some(value),
+                _ => defaults
+                    .var(key)
+                    .map_err(|error| Error::from(format!("flag `{name}`: {error}")))?,
+            };
+            if let Some(value) = value {
+                values.insert((*name).to_owned(), value);
+            }
+        }
+        Ok(Self { values })
+    }
+
+    /// Whether the flag `name` is on: set to `true`, `1`, `yes` or `on`, in
+    /// any case. Flags that are unset, or set to anything else, are off.
+    #[must_use]
+    pub fn enabled(&self, name: &str) -> bool {
+        self.values.get(name).is_some_and(|value| {
+            ["true", "1", "yes", "on"]
+                .iter()
+                .any(|on| value.trim().eq_ignore_ascii_case(on))
+        })
+    }
+
+    /// The value of the variant flag `name`, if it is set.
+    #[must_use]
+    pub fn variant(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// The names of the flags that are set, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+}
+
+impl Provider for FeatureFlags {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("Cloudflare Workers KV feature flags")
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        let flags: Dict = self
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), Value::from(value.clone())))
+            .collect();
+        Ok(Profile::Default.collect(Dict::from([(FLAGS_KEY.to_owned(), Value::from(flags))])))
+    }
+}
+
+/// The key and var the flag `name` is read from.
+fn flag_key(name: &str) -> String {
+    format!("{FLAG_PREFIX}{}", name.to_uppercase())
+}
@@ -0,0 +1,444 @@
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use figment2::{
+    value::{Num, Value},
+    Error,
+};
+
+use crate::{CloudflareConfig, FieldBinding, WranglerToml};
+
+/// Generates the source of a configuration struct from the vars a
+/// `wrangler.toml` declares and the names of the worker's secrets, to
+/// bootstrap adoption in an existing project.
+///
+/// Fields are named after their lowercased bindings and typed after their
+/// values: numbers and booleans (literal, or spelled as strings) become
+/// `u64`, `i64`, `f64` and `bool`, other strings become `String`, and
+/// structured vars become `figment2::value::Value`. Secrets are typed
+/// `secrecy::SecretString`. Extract the struct lossily (e.g. with
+/// [`extract_config`](crate::extract_config)) so that numbers and booleans
+/// can be parsed from string bindings:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{SecretsFile, StructGenerator, WranglerToml};
+///
+/// let manifest = WranglerToml::read("wrangler.toml")?;
+/// let secrets = SecretsFile::read("secrets.json")?;
+/// let source = StructGenerator::new(&manifest, "Config")
+///     .environment("production")
+///     .secrets(secrets.iter().map(|(name, _)| name))
+///     .generate()?;
+/// std::fs::write("src/config.rs", source)?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct StructGenerator<'m> {
+    manifest: &'m WranglerToml,
+    name: String,
+    environment: Option<String>,
+    secrets: Vec<String>,
+}
+
+impl<'m> StructGenerator<'m> {
+    /// Generate a struct named `name` from the top-level vars of `manifest`.
+    #[must_use]
+    pub fn new(manifest: &'m WranglerToml, name: impl Into<String>) -> Self {
+        Self {
+            manifest,
+            name: name.into(),
+            environment: None,
+            secrets: Vec::new(),
+        }
+    }
+
+    /// Use the vars of the `[env.<environment>]` environment instead.
+    #[must_use]
+    pub fn environment(mut self, environment: impl Into<String>) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
+
+    /// Add fields for the secrets named `names`, e.g. those of a
+    /// [`SecretsFile`](crate::SecretsFile).
+    #[must_use]
+    pub fn secrets<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.secrets.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// The source of the struct, ordered by binding name.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the environment is not declared.
+    pub fn generate(&self) -> Result<String, Error> {
+        let vars = self.manifest.vars(self.environment.as_deref())?;
+        let mut fields: BTreeMap<&str, &str> = vars
+            .iter()
+            .map(|(binding, value)| (binding.as_str(), rust_type(value)))
+            .collect();
+        for secret in &self.secrets {
+            fields.insert(secret, "secrecy::SecretString");
+        }
+
+        let mut source = String::from("#[derive(Debug, serde::Deserialize)]\n");
+        let _ = writeln!(source, "pub struct {} {{", self.name);
+        for (binding, ty) in fields {
+            let field = binding.to_lowercase();
+            let ident: String = field
+                .chars()
+                .map(|char| {
+                    if char.is_ascii_alphanumeric() {
+                        char
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            let ident = if ident.starts_with(|char: char| char.is_ascii_digit()) {
+                format!("_{ident}")
+            } else if ["crate", "self", "super"].contains(&ident.as_str()) {
+                format!("{ident}_")
+            } else if KEYWORDS.contains(&ident.as_str()) {
+                format!("r#{ident}")
+            } else {
+                ident
+            };
+            if ident.trim_start_matches("r#") != field {
+                let _ = writeln!(source, "    #[serde(rename = {field:?})]");
+            }
+            let _ = writeln!(source, "    pub {ident}: {ty},");
+        }
+        source.push_str("}\n");
+        Ok(source)
+    }
+}
+
+/// The Rust type for a var declared with `value`.
+fn rust_type(value: &Value) -> &'static str {
+    match value {
+        Value::Bool(..) => "bool",
+        Value::Num(_, Num::F32(_) | Num::F64(_)) => "f64",
+        Value::Num(_, number)
+            if number.to_u128().is_some() || number.to_i128().is_some_and(|number| number >= 0) =>
+        {
+            "u64"
+        }
+        Value::Num(..) => "i64",
+        Value::String(_, string) if string.parse::<bool>().is_ok() => "bool",
+        Value::String(_, string) if string.parse::<u64>().is_ok() => "u64",
+        Value::String(_, string) if string.parse::<i64>().is_ok() => "i64",
+        Value::String(_, string) if string.parse::<f64>().is_ok() => "f64",
+        Value::String(..) | Value::Char(..) | Value::Empty(..) => "String",
+        Value::Array(_, values)
+            if values
+                .iter()
+                .all(|value| matches!(value, Value::String(..))) =>
+        {
+            "Vec<String>"
+        }
+        Value::Dict(..) | Value::Array(..) => "figment2::value::Value",
+    }
+}
+
+/// Lowercase identifiers that must be written as raw identifiers.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Generates provisioning stubs for a [`CloudflareConfig`]: a `wrangler.toml`
+/// `[vars]` block for its vars and a `wrangler secret put` command for each
+/// of its secrets, so provisioning docs never drift from the code.
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::WranglerStub;
+///
+/// let stub = WranglerStub::new::<Config>().environment("production");
+/// println!("{}", stub.vars());
+/// for command in stub.secret_commands() {
+///     println!("{command}");
+/// }
+/// ```
+///
+/// Optional fields are emitted commented out, and every value is left empty
+/// to be filled in.
+#[derive(Clone, Debug)]
+pub struct WranglerStub {
+    bindings: &'static [FieldBinding],
+    environment: Option<String>,
+}
+
+impl WranglerStub {
+    /// Generate stubs for the bindings `T` declares.
+    #[must_use]
+    pub fn new<T: CloudflareConfig>() -> Self {
+        Self {
+            bindings: T::bindings(),
+            environment: None,
+        }
+    }
+
+    /// Target the `[env.<environment>]` environment instead of the top level.
+    #[must_use]
+    pub fn environment(mut self, environment: impl Into<String>) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
+
+    /// The `[vars]` block declaring every binding not declared secret.
+    #[must_use]
+    pub fn vars(&self) -> String {
+        let mut block = match &self.environment {
+            None => String::from("[vars]\n"),
+            Some(environment) => format!("[env.{}.vars]\n", toml_key(environment)),
+        };
+        for binding in self.bindings.iter().filter(|binding| !binding.is_secret()) {
+            let comment = if binding.is_optional() { "# " } else { "" };
+            let _ = writeln!(block, "{comment}{} = \"\"", toml_key(binding.binding()));
+        }
+        block
+    }
+
+    /// A `wrangler secret put` command for every binding declared secret,
+    /// with the binding and environment quoted for a POSIX shell.
+    #[must_use]
+    pub fn secret_commands(&self) -> Vec<String> {
+        self.bindings
+            .iter()
+            .filter(|binding| binding.is_secret())
+            .map(|binding| {
+                let mut command = format!("wrangler secret put {}", shell_word(binding.binding()));
+                if let Some(environment) = &self.environment {
+                    let _ = write!(command, " --env {}", shell_word(environment));
+                }
+                command
+            })
+            .collect()
+    }
+}
+
+/// `key` as a TOML key, quoted unless it is a bare key.
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '_' || char == '-');
+    if bare {
+        return key.to_owned();
+    }
+    let mut quoted = String::from('"');
+    for char in key.chars() {
+        match char {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            char if char.is_control() => {
+                let _ = write!(quoted, "\\u{:04X}", u32::from(char));
+            }
+            char => quoted.push(char),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// `word` as a single word of a POSIX shell command, single-quoted unless
+/// it holds only characters the shell takes literally.
+fn shell_word(word: &str) -> String {
+    let literal = !word.is_empty()
+        && word
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || "_-.,:/@%+=".contains(char));
+    if literal {
+        word.to_owned()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(
+        toml: &str,
+        configure: impl FnOnce(StructGenerator<'_>) -> StructGenerator<'_>,
+    ) -> String {
+        let manifest = WranglerToml::from_toml(toml).unwrap();
+        configure(StructGenerator::new(&manifest, "Config"))
+            .generate()
+            .unwrap()
+    }
+
+    #[test]
+    fn fields_are_typed_after_their_values() {
+        let source = generate(
+            r#"
+            [vars]
+            API_BASE_URL = "https://api.example.com/v1"
+            MAX_RETRIES = 3
+            OFFSET = -2
+            SAMPLE_RATE = 0.5
+            VERBOSE = true
+            PORT = "8080"
+            DEBUG = "false"
+            DELTA = "-7"
+            RATIO = "1.5"
+            ORIGINS = ["https://example.com"]
+            LIMITS = { burst = 10 }
+            MIXED = ["a", 1]
+            "#,
+            |generator| generator.secrets(["API_KEY"]),
+        );
+        assert_eq!(
+            source,
+            "\
+#[derive(Debug, serde::Deserialize)]
+pub struct Config {
+    pub api_base_url: String,
+    pub api_key: secrecy::SecretString,
+    pub debug: bool,
+    pub delta: i64,
+    pub limits: figment2::value::Value,
+    pub max_retries: u64,
+    pub mixed: figment2::value::Value,
+    pub offset: i64,
+    pub origins: Vec<String>,
+    pub port: u64,
+    pub ratio: f64,
+    pub sample_rate: f64,
+    pub verbose: bool,
+}
+"
+        );
+    }
+
+    #[test]
+    fn bindings_that_are_not_identifiers_are_renamed() {
+        let source = generate(
+            r#"
+            [vars]
+            TYPE = "a"
+            SELF = "b"
+            2FA_ISSUER = "c"
+            "API-HOST" = "d"
+            "cache.ttl" = "e"
+            "#,
+            |generator| generator,
+        );
+        assert_eq!(
+            source,
+            "\
+#[derive(Debug, serde::Deserialize)]
+pub struct Config {
+    #[serde(rename = \"2fa_issuer\")]
+    pub _2fa_issuer: String,
+    #[serde(rename = \"api-host\")]
+    pub api_host: String,
+    #[serde(rename = \"self\")]
+    pub self_: String,
+    pub r#type: String,
+    #[serde(rename = \"cache.ttl\")]
+    pub cache_ttl: String,
+}
+"
+        );
+    }
+
+    #[test]
+    fn environments_supply_their_own_vars() {
+        let toml = r#"
+            [vars]
+            MAX_RETRIES = "3"
+
+            [env.production.vars]
+            REGION = "eu"
+            "#;
+        let source = generate(toml, |generator| generator.environment("production"));
+        assert_eq!(
+            source,
+            "\
+#[derive(Debug, serde::Deserialize)]
+pub struct Config {
+    pub region: String,
+}
+"
+        );
+        let manifest = WranglerToml::from_toml(toml).unwrap();
+        let error = StructGenerator::new(&manifest, "Config")
+            .environment("staging")
+            .generate()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "wrangler.toml declares no `staging` environment"
+        );
+    }
+
+    struct Config;
+
+    impl CloudflareConfig for Config {
+        fn bindings() -> &'static [FieldBinding] {
+            const BINDINGS: &[FieldBinding] = &[
+                FieldBinding::new("api_base_url", "API_BASE_URL"),
+                FieldBinding::new("timeout", "TIMEOUT").optional(),
+                FieldBinding::new("cache_ttl", "cache.ttl"),
+                FieldBinding::new("api_key", "API_KEY").secret(),
+                FieldBinding::new("token", "TOKEN; rm -rf ~").secret(),
+            ];
+            BINDINGS
+        }
+    }
+
+    #[test]
+    fn stubs_declare_vars_and_put_secrets() {
+        let stub = WranglerStub::new::<Config>();
+        assert_eq!(
+            stub.vars(),
+            "\
+[vars]
+API_BASE_URL = \"\"
+# TIMEOUT = \"\"
+\"cache.ttl\" = \"\"
+"
+        );
+        assert_eq!(
+            stub.secret_commands(),
+            [
+                "wrangler secret put API_KEY",
+                "wrangler secret put 'TOKEN; rm -rf ~'",
+            ]
+        );
+    }
+
+    #[test]
+    fn stubs_quote_the_environment() {
+        let stub = WranglerStub::new::<Config>().environment("it's $(prod)");
+        assert!(stub.vars().starts_with("[env.\"it's $(prod)\".vars]\n"));
+        assert_eq!(
+            stub.secret_commands()[0],
+            "wrangler secret put API_KEY --env 'it'\\''s $(prod)'"
+        );
+        let stub = WranglerStub::new::<Config>().environment("production");
+        assert!(stub.vars().starts_with("[env.production.vars]\n"));
+        assert_eq!(
+            stub.secret_commands()[0],
+            "wrangler secret put API_KEY --env production"
+        );
+    }
+
+    #[test]
+    fn quoted_toml_keys_parse_back() {
+        for key in ["cache.ttl", "say \"hi\"", "back\\slash", "tab\there", ""] {
+            let toml = format!("{} = 1", toml_key(key));
+            let table: toml::Table = toml::from_str(&toml).unwrap();
+            assert!(table.contains_key(key), "{toml}");
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::fmt::Write as _;
+
+use figment2::{
+    providers::{Format, Json},
+    value::{Dict, Value},
+    Figment,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{render, CloudflareWorkersBindings};
+
+/// Assert that the configuration `T` resolved from `provider` matches the
+/// golden JSON document `expected_json`.
+///
+/// The extracted `T` is serialised back and compared key by key (nested
+/// keys are dotted, array elements indexed) with the golden document.
+/// Scalars are compared in their string form, as bindings carry them, so `3`
+/// matches `"3"`, and extraction is lossy, so numeric and boolean fields can
+/// be read from string bindings. On mismatch, the panic message lists every
+/// differing key; values of secret fields (see
+/// [`CloudflareWorkersBindings::secret`]) are shown only as a SHA-256
+/// digest, so golden files and CI logs never need to print them:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{assert_config_matches, MockBindings};
+///
+/// let bindings = MockBindings::new()
+///     .with_var("DATABASE_URL", "postgres://localhost")
+///     .with_secret("API_KEY", "test-key");
+/// assert_config_matches::<Config>(
+///     &CloudflareWorkersBindings::from_struct::<Config>(&bindings).secret("api_key"),
+///     include_str!("golden/config.json"),
+/// );
+/// ```
+///
+/// # Panics
+///
+/// Panics if the golden document is not a JSON object, if extraction fails,
+/// or if the configurations differ.
+#[track_caller]
+pub fn assert_config_matches<T>(provider: &CloudflareWorkersBindings<'_>, expected_json: &str)
+where
+    T: DeserializeOwned + Serialize + 'static,
+{
+    let expected: Dict = Json::from_str(expected_json)
+        .unwrap_or_else(|error| panic!("golden configuration is not a JSON object: {error}"));
+    let snapshot = provider
+        .snapshot()
+        .unwrap_or_else(|error| panic!("failed to resolve configuration: {error}"));
+    let config: T = Figment::from(&snapshot)
+        .extract_lossy()
+        .unwrap_or_else(|error| panic!("failed to extract configuration: {error}"));
+    let Ok(Value::Dict(_, actual)) = Value::serialize(&config) else {
+        panic!("configuration did not serialise to a struct");
+    };
+
+    let (expected, actual) = (render::flatten(&expected), render::flatten(&actual));
+    let show = |key: &str, value: &str| {
+        let field = key.split('.').next().unwrap_or(key);
+        if snapshot.is_secret(field) {
+            digest(value)
+        } else {
+            format!("{value:?}")
+        }
+    };
+
+    let mut differences = String::new();
+    for (key, expected_value) in &expected {
+        match actual.get(key) {
+            None => {
+                let _ = writeln!(
+                    differences,
+                    "  {key}: missing, expected {}",
+                    show(key, expected_value)
+                );
+            }
+            Some(actual_value) if actual_value != expected_value => {
+                let _ = writeln!(
+                    differences,
+                    "  {key}: expected {}, found {}",
+                    show(key, expected_value),
+                    show(key, actual_value)
+                );
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, actual_value) in &actual {
+        if !expected.contains_key(key) {
+            let _ = writeln!(
+                differences,
+                "  {key}: unexpected {}",
+                show(key, actual_value)
+            );
+        }
+    }
+
+    assert!(
+        differences.is_empty(),
+        "configuration does not match the golden document:\n{differences}"
+    );
+}
+
+fn digest(value: &str) -> String {
+    format!(
+        "sha256:{}",
+        render::hex(&Sha256::digest(value.as_bytes())[..8])
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::MockBindings;
+
+    #[derive(Deserialize, Serialize)]
+    struct Config {
+        database_url: String,
+        api_key: String,
+        max_retries: u8,
+    }
+
+    fn bindings() -> MockBindings {
+        MockBindings::new()
+            .with_var("DATABASE_URL", "postgres://localhost")
+            .with_secret("API_KEY", "test-key")
+            .with_var("MAX_RETRIES", "3")
+    }
+
+    /// The message `assert_config_matches` panics with.
+    fn mismatch(provider: &CloudflareWorkersBindings<'_>, expected_json: &str) -> String {
+        let panic = catch_unwind(AssertUnwindSafe(|| {
+            assert_config_matches::<Config>(provider, expected_json);
+        }))
+        .expect_err("the configurations matched");
+        panic
+            .downcast_ref::<String>()
+            .expect("the panic carried a message")
+            .clone()
+    }
+
+    #[test]
+    fn matching_configurations_pass() {
+        let bindings = bindings();
+        assert_config_matches::<Config>(
+            &CloudflareWorkersBindings::from_struct::<Config>(&bindings).secret("api_key"),
+            r#"{"database_url": "postgres://localhost", "api_key": "test-key", "max_retries": 3}"#,
+        );
+    }
+
+    #[test]
+    fn every_difference_is_listed() {
+        let bindings = bindings();
+        let message = mismatch(
+            &CloudflareWorkersBindings::from_struct::<Config>(&bindings),
+            r#"{"database_url": "postgres://db", "api_key": "test-key", "timeout": 30}"#,
+        );
+        assert_eq!(
+            message,
+            "configuration does not match the golden document:\n\
+             \x20 database_url: expected \"postgres://db\", found \"postgres://localhost\"\n\
+             \x20 timeout: missing, expected \"30\"\n\
+             \x20 max_retries: unexpected \"3\"\n"
+        );
+    }
+
+    #[test]
+    fn secrets_are_shown_as_digests() {
+        let bindings = bindings();
+        let message = mismatch(
+            &CloudflareWorkersBindings::from_struct::<Config>(&bindings).secret("api_key"),
+            r#"{"database_url": "postgres://localhost", "api_key": "golden-key", "max_retries": 3}"#,
+        );
+        assert_eq!(
+            message,
+            "configuration does not match the golden document:\n\
+             \x20 api_key: expected sha256:62518b7ee254c542, found sha256:62af8704764faf8e\n"
+        );
+        assert!(!message.contains("test-key") && !message.contains("golden-key"));
+    }
+
+    #[test]
+    #[should_panic(expected = "golden configuration is not a JSON object")]
+    fn golden_documents_must_be_objects() {
+        let bindings = bindings();
+        assert_config_matches::<Config>(
+            &CloudflareWorkersBindings::from_struct::<Config>(&bindings),
+            "[]",
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+
+use figment2::{Error, Figment};
+use futures_util::StreamExt;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use worker::{
+    wasm_bindgen::JsValue, Env, Headers, Method, Request, RequestInit, Response, State, Stub,
+    WebSocketPair, WebsocketEvent,
+};
+
+use crate::{BindingError, BindingSource, SharedConfig};
+
+/// The storage key a [`ConfigHub`] keeps its state under.
+const STATE_KEY: &str = "config";
+
+/// The origin of requests to a hub; Durable Objects ignore it.
+const HUB_URL: &str = "https://config-hub/";
+
+/// The body of a Durable Object that holds configuration values and pushes
+/// every change to the isolates [following](HubClient::follow) it, so they
+/// refresh within moments of a publish instead of when a TTL runs out.
+///
+/// Declare the Durable Object class in the worker and forward its requests
+/// to the hub:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::ConfigHub;
+///
+/// #[durable_object]
+/// pub struct ConfigObject {
+///     hub: ConfigHub,
+/// }
+///
+/// impl DurableObject for ConfigObject {
+///     fn new(state: State, _env: Env) -> Self {
+///         Self { hub: ConfigHub::new(state) }
+///     }
+///
+///     async fn fetch(&self, request: Request) -> Result<Response> {
+///         self.hub.fetch(request).await
+///     }
+/// }
+/// ```
+///
+/// The hub answers `GET` with its [`HubBindings`], replaces its values with
+/// the JSON object of a `PUT`, and accepts WebSocket upgrades from
+/// followers, which it sends `{"version":…}` after each change. Sockets
+/// are accepted through the hibernation API, so idle followers do not keep
+/// the object in memory.
+#[derive(Debug)]
+pub struct ConfigHub {
+    state: State,
+}
+
+impl ConfigHub {
+    /// Serve configuration from the storage of the Durable Object whose
+    /// state is `state`.
+    #[must_use]
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+
+    /// Answer a request forwarded to the Durable Object.
+    ///
+    /// # Errors
+    ///
+    /// Fails if storage or the WebSocket cannot be used.
+    pub async fn fetch(&self, mut request: Request) -> worker::Result<Response> {
+        let upgrade = request.headers().get("Upgrade")?;
+        match request.method() {
+            Method::Get
+                if upgrade.is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket")) =>
+            {
+                let pair = WebSocketPair::new()?;
+                self.state.accept_web_socket(&pair.server);
+                Response::from_websocket(pair.client)
+            }
+            Method::Get => Response::from_json(&self.load().await?),
+            Method::Put => {
+                let Ok(values) = request.json::<HashMap<String, String>>().await else {
+                    return Response::error("expected a JSON object of string values", 400);
+                };
+                let bindings = HubBindings {
+                    version: self.load().await?.version + 1,
+                    values,
+                };
+                self.state.storage().put(STATE_KEY, &bindings).await?;
+                let notice = HubNotice {
+                    version: bindings.version,
+                };
+                for socket in self.state.get_websockets() {
+                    // A follower that has gone away is dropped by the runtime.
+                    let _ = socket.send(&notice);
+                }
+                Response::from_json(&bindings)
+            }
+            _ => Response::error("Method Not Allowed", 405),
+        }
+    }
+
+    async fn load(&self) -> worker::Result<HubBindings> {
+        Ok(self
+            .state
+            .storage()
+            .get(STATE_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+}
+
+/// A worker's handle on a [`ConfigHub`], to read and publish its values and
+/// to follow its changes.
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, HubClient, SharedConfig};
+///
+/// static CONFIG: SharedConfig<Config> = SharedConfig::new();
+///
+/// let hub = HubClient::new(&env, "CONFIG_HUB", "production")?;
+/// context.wait_until(async move {
+///     let _ = hub
+///         .follow(&CONFIG, |values| {
+///             Figment::new()
+///                 .merge(CloudflareWorkersBindings::from_sources::<Config>(&[values, &env]))
+///         })
+///         .await;
+/// });
+/// ```
+///
+/// Isolates cannot be addressed from outside, so a follower keeps a
+/// WebSocket open to the hub for as long as the future driving it lives,
+/// e.g. a request's `wait_until` window; isolates not following at the time
+/// of a change pick it up the next time they load.
+#[derive(Debug)]
+pub struct HubClient {
+    stub: Stub,
+    name: String,
+}
+
+impl HubClient {
+    /// Talk to the hub named `name` in the Durable Object namespace bound as
+    /// `binding`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `binding` is not a Durable Object namespace binding.
+    pub fn new(env: &Env, binding: &str, name: &str) -> Result<Self, Error> {
+        let stub = env
+            .durable_object(binding)
+            .and_then(|namespace| namespace.get_by_name(name))
+            .map_err(|error| Error::from(format!("Durable Object `{binding}`: {error}")))?;
+        Ok(Self {
+            stub,
+            name: name.to_owned(),
+        })
+    }
+
+    /// Read the hub's current values.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the hub cannot be reached or answers with an error.
+    pub async fn load(&self) -> Result<HubBindings, Error> {
+        let mut response = self
+            .stub
+            .fetch_with_str(HUB_URL)
+            .await
+            .map_err(|error| self.error(&error))?;
+        response.json().await.map_err(|error| self.error(&error))
+    }
+
+    /// Replace the hub's values with `values`, notifying its followers, and
+    /// return the new version.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the hub cannot be reached or answers with an error.
+    pub async fn publish(&self, values: &HashMap<String, String>) -> Result<u64, Error> {
+        let body = serde_json::to_string(values).map_err(|error| Error::from(error.to_string()))?;
+        let request = Request::new_with_init(
+            HUB_URL,
+            RequestInit::new()
+                .with_method(Method::Put)
+                .with_body(Some(JsValue::from(body))),
+        )
+        .map_err(|error| self.error(&error))?;
+        let mut response = self
+            .stub
+            .fetch_with_request(request)
+            .await
+            .map_err(|error| self.error(&error))?;
+        if response.status_code() != 200 {
+            let text = response.text().await.unwrap_or_default();
+            return Err(Error::from(format!(
+                "config hub `{}` refused the values: {text}",
+                self.name
+            )));
+        }
+        let bindings: HubBindings = response.json().await.map_err(|error| self.error(&error))?;
+        Ok(bindings.version)
+    }
+
+    /// Load the configuration into `shared` from the figment `figment`
+    /// builds over the hub's values, e.g. layering them over the
+    /// environment, then reload it every time the hub announces a change,
+    /// until the connection closes. Strings are read leniently, as by
+    /// [`SharedConfig::load_figment`].
+    ///
+    /// A failed extraction keeps the current configuration in place and
+    /// waits for the next change.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the hub cannot be reached, or the first extraction fails.
+    pub async fn follow<T: DeserializeOwned + 'static>(
+        &self,
+        shared: &SharedConfig<T>,
+        figment: impl Fn(&HubBindings) -> Figment,
+    ) -> Result<(), Error> {
+        let headers = Headers::new();
+        headers
+            .set("Upgrade", "websocket")
+            .map_err(|error| self.error(&error))?;
+        let request = Request::new_with_init(HUB_URL, RequestInit::new().with_headers(headers))
+            .map_err(|error| self.error(&error))?;
+        let socket = self
+            .stub
+            .fetch_with_request(request)
+            .await
+            .map_err(|error| self.error(&error))?
+            .websocket()
+            .ok_or_else(|| {
+                Error::from(format!("config hub `{}` refused the WebSocket", self.name))
+            })?;
+        // Listen before accepting, and load after, so no change is missed.
+        let mut events = socket.events().map_err(|error| self.error(&error))?;
+        socket.accept().map_err(|error| self.error(&error))?;
+        let mut version = self.refresh(shared, &figment).await?;
+        while let Some(event) = events.next().await {
+            let Ok(WebsocketEvent::Message(message)) = event else {
+                break;
+            };
+            match message.json::<HubNotice>() {
+                Ok(notice) if notice.version > version => {
+                    if let Ok(refreshed) = self.refresh(shared, &figment).await {
+                        version = refreshed;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Load the hub's values into `shared`, returning their version.
+    async fn refresh<T: DeserializeOwned + 'static>(
+        &self,
+        shared: &SharedConfig<T>,
+        figment: &impl Fn(&HubBindings) -> Figment,
+    ) -> Result<u64, Error> {
+        let bindings = self.load().await?;
+        shared.load_figment(&figment(&bindings))?;
+        Ok(bindings.version)
+    }
+
+    fn error(&self, error: &worker::Error) -> Error {
+        Error::from(format!("config hub `{}`: {error}", self.name))
+    }
+}
+
+/// The values held by a [`ConfigHub`] and their version, which starts at 0,
+/// before anything is published, and grows by one with every publish.
+///
+/// Every key resolves as a var and secret lookups always miss; keep secrets
+/// in worker secrets rather than in the hub.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HubBindings {
+    version: u64,
+    values: HashMap<String, String>,
+}
+
+impl HubBindings {
+    /// The version of the values.
+    #[must_use]
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+impl BindingSource for HubBindings {
+    fn var(&self, name: &str) -> Result<Option<String>, BindingError> {
+        Ok(self.values.get(name).cloned())
+    }
+
+    fn secret(&self, _name: &str) -> Result<Option<String>, BindingError> {
+        Ok(None)
+    }
+
+    fn names(&self) -> Option<Vec<String>> {
+        Some(self.values.keys().cloned().collect())
+    }
+}
+
+/// The message a hub sends its followers after a change.
+#[derive(Serialize, Deserialize)]
+struct HubNotice {
+    version: u64,
+}
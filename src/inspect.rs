@@ -0,0 +1,314 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+};
+
+use figment2::{
+    value::{Dict, Value},
+    Profile,
+};
+use serde::{Serialize, Serializer};
+
+use crate::{
+    describe_bounds, BindingCase, CloudflareWorkersBindings, LookupOrder, ResolutionMetrics,
+    REDACTED,
+};
+
+/// What a provider has been told and has read so far, with the values of
+/// secret fields, and of everything read as a secret, masked, and the values
+/// read left out unless asked for.
+#[derive(Serialize)]
+struct Description<'p> {
+    fields: Vec<FieldEntry<'p>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    undiscovered: Option<String>,
+    fallbacks: usize,
+    profile: &'p Profile,
+    path: Option<&'p str>,
+    focus: Option<&'p str>,
+    only: Option<&'p BTreeSet<String>>,
+    skipped: &'p BTreeSet<String>,
+    lookup_order: &'static str,
+    binding_cases: Vec<&'static str>,
+    aliases: &'p BTreeMap<String, Vec<String>>,
+    required: &'p BTreeSet<String>,
+    require_all: bool,
+    secrets: &'p BTreeSet<String>,
+    deferred: &'p BTreeSet<String>,
+    accumulated: &'p BTreeSet<String>,
+    defaults: Dict,
+    ranges: BTreeMap<&'p str, Vec<String>>,
+    #[cfg(feature = "regex")]
+    patterns: BTreeMap<&'p str, Vec<String>>,
+    #[cfg(feature = "encryption")]
+    decryption_key: Option<&'p str>,
+    reads: Vec<SourceEntry>,
+    metrics: ResolutionMetrics,
+}
+
+/// A field and the binding it is read from.
+#[derive(Debug, Serialize)]
+struct FieldEntry<'p> {
+    name: &'p str,
+    binding: &'p str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    accessor: Option<&'static str>,
+}
+
+/// What has been read from one source: each binding's value, or `None` if
+/// it is not bound, and how many bindings were read in one pass, if so.
+#[derive(Debug, Serialize)]
+struct SourceEntry {
+    prefetched: Option<usize>,
+    vars: BTreeMap<String, Option<ReadEntry>>,
+    secrets: BTreeMap<String, Option<ReadEntry>>,
+}
+
+/// A value read from a bound binding: its length, and the value itself, or
+/// [`REDACTED`], if values are shown.
+#[derive(Debug, Serialize)]
+struct ReadEntry {
+    length: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+}
+
+impl CloudflareWorkersBindings<'_> {
+    fn description(&self) -> Description<'_> {
+        let mut ranges: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for (field, bounds) in &self.ranges {
+            ranges
+                .entry(field)
+                .or_default()
+                .push(describe_bounds(bounds));
+        }
+        #[cfg(feature = "regex")]
+        let mut patterns: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        #[cfg(feature = "regex")]
+        for (field, regex) in &self.patterns {
+            let pattern = match regex {
+                Ok(regex) => regex.as_str().to_owned(),
+                Err(error) => error.to_string(),
+            };
+            patterns.entry(field).or_default().push(pattern);
+        }
+        Description {
+            fields: self
+                .fields
+                .iter()
+                .map(|field| FieldEntry {
+                    name: &field.name,
+                    binding: &field.binding,
+                    accessor: field.accessor.map(crate::BindingKind::accessor),
+                })
+                .collect(),
+            undiscovered: self.undiscovered.as_ref().map(ToString::to_string),
+            fallbacks: self.fallbacks.len(),
+            profile: &self.profile,
+            path: self.path.as_deref(),
+            focus: self.focus.as_deref(),
+            only: self.only.as_ref(),
+            skipped: &self.skipped,
+            lookup_order: match self.lookup_order {
+                LookupOrder::VarThenSecret => "var_then_secret",
+                LookupOrder::SecretThenVar => "secret_then_var",
+            },
+            binding_cases: self
+                .binding_cases
+                .iter()
+                .map(|case| match case {
+                    BindingCase::Exact => "exact",
+                    BindingCase::Upper => "upper",
+                    BindingCase::Lower => "lower",
+                })
+                .collect(),
+            aliases: &self.aliases,
+            required: &self.required,
+            require_all: self.require_all,
+            secrets: &self.secrets,
+            deferred: &self.deferred,
+            accumulated: &self.accumulated,
+            defaults: self
+                .defaults
+                .iter()
+                .map(|(field, value)| {
+                    let value = if self.secrets.contains(field) {
+                        Value::from(REDACTED)
+                    } else {
+                        value.clone()
+                    };
+                    (field.clone(), value)
+                })
+                .collect(),
+            ranges,
+            #[cfg(feature = "regex")]
+            patterns,
+            #[cfg(feature = "encryption")]
+            decryption_key: self.decryption_key.as_deref(),
+            reads: self.read_entries(),
+            metrics: self.metrics.get(),
+        }
+    }
+
+    /// What has been read from each source, with the values of the bindings
+    /// of secret fields, and of everything read as a secret, masked, if
+    /// values are shown at all.
+    fn read_entries(&self) -> Vec<SourceEntry> {
+        let secret_bindings: BTreeSet<String> = self
+            .fields
+            .iter()
+            .filter(|field| self.secrets.contains(field.name.as_ref()))
+            .flat_map(|field| self.binding_names(field))
+            .map(Cow::into_owned)
+            .collect();
+        self.reads
+            .borrow()
+            .iter()
+            .map(|reads| SourceEntry {
+                prefetched: reads
+                    .prefetched
+                    .get()
+                    .and_then(|values| values.as_ref().map(HashMap::len)),
+                vars: reads
+                    .vars
+                    .iter()
+                    .map(|(name, value)| {
+                        let entry = value.as_ref().map(|value| ReadEntry {
+                            length: value.len(),
+                            value: self.show_read_values.then(|| {
+                                if secret_bindings.contains(name) {
+                                    REDACTED.to_owned()
+                                } else {
+                                    value.clone()
+                                }
+                            }),
+                        });
+                        (name.clone(), entry)
+                    })
+                    .collect(),
+                secrets: reads
+                    .secrets
+                    .iter()
+                    .map(|(name, value)| {
+                        let entry = value.as_ref().map(|value| ReadEntry {
+                            length: value.len(),
+                            value: self.show_read_values.then(|| REDACTED.to_owned()),
+                        });
+                        (name.clone(), entry)
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+/// Lists the fields, the options the provider was built with, and what it
+/// has read so far: whether each binding is bound and the length of its
+/// value, and the value itself only with
+/// [`show_read_values`](CloudflareWorkersBindings::show_read_values). Even
+/// then, the values of secret fields, and of anything read as a secret, are
+/// shown as [`REDACTED`].
+impl fmt::Debug for CloudflareWorkersBindings<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = self.description();
+        let mut debug = formatter.debug_struct("CloudflareWorkersBindings");
+        debug
+            .field("fields", &description.fields)
+            .field("undiscovered", &description.undiscovered)
+            .field("fallbacks", &description.fallbacks)
+            .field("profile", description.profile)
+            .field("path", &description.path)
+            .field("focus", &description.focus)
+            .field("only", &description.only)
+            .field("skipped", description.skipped)
+            .field("lookup_order", &self.lookup_order)
+            .field("binding_cases", &self.binding_cases)
+            .field("aliases", description.aliases)
+            .field("required", description.required)
+            .field("require_all", &description.require_all)
+            .field("secrets", description.secrets)
+            .field("deferred", description.deferred)
+            .field("accumulated", description.accumulated)
+            .field("defaults", &description.defaults)
+            .field("ranges", &description.ranges);
+        #[cfg(feature = "regex")]
+        debug.field("patterns", &description.patterns);
+        #[cfg(feature = "encryption")]
+        debug.field("decryption_key", &description.decryption_key);
+        debug
+            .field("reads", &description.reads)
+            .field("metrics", &description.metrics)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Serialises what [`Debug`](fmt::Debug) shows, e.g. to log a provider as
+/// JSON, with secret values masked the same way.
+impl Serialize for CloudflareWorkersBindings<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.description().serialize(serializer)
+    }
+}
+
+#[cfg(all(test, feature = "test-util"))]
+mod tests {
+    use figment2::Provider;
+    use serde::Deserialize;
+
+    use crate::{CloudflareWorkersBindings, MockBindings};
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Config {
+        api_base_url: String,
+        api_key: String,
+        timeout: Option<String>,
+    }
+
+    /// A secret answering var lookups, as on a live `Env`.
+    fn bindings() -> MockBindings {
+        MockBindings::new()
+            .with_var("API_BASE_URL", "https://api.example.com/v1")
+            .with_var("API_KEY", "super-secret-key")
+    }
+
+    fn logged(
+        bindings: &MockBindings,
+        configure: impl FnOnce(CloudflareWorkersBindings<'_>) -> CloudflareWorkersBindings<'_>,
+    ) -> String {
+        let provider = configure(CloudflareWorkersBindings::from_struct::<Config>(bindings));
+        provider.data().unwrap();
+        format!("{provider:?}")
+    }
+
+    #[test]
+    fn read_values_are_left_out_by_default() {
+        let logged = logged(&bindings(), |provider| provider);
+        assert!(!logged.contains("super-secret-key"), "{logged}");
+        assert!(!logged.contains("https://api.example.com/v1"), "{logged}");
+        assert!(
+            logged.contains(r#""API_KEY": Some(ReadEntry { length: 16, value: None })"#),
+            "{logged}"
+        );
+        assert!(logged.contains(r#""TIMEOUT": None"#), "{logged}");
+    }
+
+    #[test]
+    fn shown_read_values_mask_secret_fields() {
+        let logged = logged(&bindings(), |provider| {
+            provider.secret("api_key").show_read_values()
+        });
+        assert!(!logged.contains("super-secret-key"), "{logged}");
+        assert!(
+            logged.contains(r#"value: Some("https://api.example.com/v1")"#),
+            "{logged}"
+        );
+        assert!(
+            logged.contains(
+                r#""API_KEY": Some(ReadEntry { length: 16, value: Some("[REDACTED]") })"#
+            ),
+            "{logged}"
+        );
+    }
+}
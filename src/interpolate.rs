@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use crate::{BindingError, BindingSource};
+
+/// A [`BindingSource`] expanding `${NAME}` references in the values of
+/// another, so values derived from other bindings need not be duplicated
+/// across environments:
+///
+/// ```toml
+/// [vars]
+/// API_HOST = "api.staging.example.com"
+/// API_URL = "https://${API_HOST}/v1"
+/// ```
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, Interpolated};
+///
+/// let config: Config = Figment::new()
+///     .merge(CloudflareWorkersBindings::from_struct::<Config>(&Interpolated::new(&env)))
+///     .extract_lossy()?;
+/// ```
+///
+/// References are looked up as vars of the same source, and expanded in
+/// turn, so they may chain; a reference back to a binding being expanded is
+/// an error naming the cycle, as is a reference to an unbound name. Write
+/// `$${` for a literal `${`. Secret values are expanded too. The Workers
+/// runtime answers var lookups for secrets as well, so a var referencing a
+/// secret holds the secret once expanded: declare the fields it backs
+/// [secret](crate::CloudflareWorkersBindings::secret).
+#[derive(Clone, Copy)]
+pub struct Interpolated<'a> {
+    source: &'a dyn BindingSource,
+}
+
+impl<'a> Interpolated<'a> {
+    /// Expand references in the bindings of `source`.
+    #[must_use]
+    pub fn new(source: &'a dyn BindingSource) -> Self {
+        Self { source }
+    }
+}
+
+impl std::fmt::Debug for Interpolated<'_> {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("Interpolated")
+            .finish_non_exhaustive()
+    }
+}
+
+impl BindingSource for Interpolated<'_> {
+    fn var(&self, name: &str) -> Result<Option<String>, BindingError> {
+        let lookup = |reference: &str| self.source.var(reference);
+        self.source
+            .var(name)?
+            .map(|value| expand(&value, &mut vec![name.to_owned()], &lookup))
+            .transpose()
+    }
+
+    fn secret(&self, name: &str) -> Result<Option<String>, BindingError> {
+        let lookup = |reference: &str| self.source.var(reference);
+        self.source
+            .secret(name)?
+            .map(|value| expand(&value, &mut vec![name.to_owned()], &lookup))
+            .transpose()
+    }
+
+    fn names(&self) -> Option<Vec<String>> {
+        self.source.names()
+    }
+
+    fn prefetch(&self) -> Option<HashMap<String, String>> {
+        let bindings = self.source.prefetch()?;
+        let lookup = |reference: &str| Ok(bindings.get(reference).cloned());
+        // If any value fails to expand, every lookup goes through the
+        // accessors instead, which report the error for the field it breaks.
+        bindings
+            .iter()
+            .map(|(name, value)| {
+                expand(value, &mut vec![name.clone()], &lookup).map(|value| (name.clone(), value))
+            })
+            .collect::<Result<_, _>>()
+            .ok()
+    }
+
+    fn fetch_ms(&self) -> f64 {
+        self.source.fetch_ms()
+    }
+}
+
+/// Expand the references in `value`, the value of the last binding in
+/// `chain`, resolving them with `lookup`.
+fn expand(
+    value: &str,
+    chain: &mut Vec<String>,
+    lookup: &dyn Fn(&str) -> Result<Option<String>, BindingError>,
+) -> Result<String, BindingError> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("$${") {
+            expanded.push_str("${");
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("${") {
+            let end = after.find('}').ok_or_else(|| {
+                BindingError::new(format!(
+                    "binding `{}` has an unterminated `${{` reference",
+                    chain[chain.len() - 1]
+                ))
+            })?;
+            let reference = &after[..end];
+            if chain.iter().any(|name| name == reference) {
+                return Err(BindingError::new(format!(
+                    "binding references form a cycle: {} -> {reference}",
+                    chain.join(" -> ")
+                )));
+            }
+            let referenced = lookup(reference)?.ok_or_else(|| {
+                BindingError::new(format!(
+                    "binding `{}` references `{reference}`, which is not bound",
+                    chain[chain.len() - 1]
+                ))
+            })?;
+            chain.push(reference.to_owned());
+            expanded.push_str(&expand(&referenced, chain, lookup)?);
+            chain.pop();
+            rest = &after[end + 1..];
+        } else {
+            expanded.push('$');
+            rest = &rest[1..];
+        }
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
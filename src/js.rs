@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::{BindingError, BindingSource};
+
+/// The bindings held by a plain JS env object, read through `js-sys` alone.
+///
+/// The `worker` feature reads a [`worker::Env`] of the `worker` release this
+/// crate is built against. Workers on another release can turn the feature
+/// off and enable `js` instead, reading their own `Env` as the object it
+/// wraps, without upgrading `worker` in lockstep; only the `wasm-bindgen`
+/// versions must agree:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, JsBindings};
+///
+/// let bindings = JsBindings::new(env.into());
+/// let config: Config = Figment::new()
+///     .merge(CloudflareWorkersBindings::from_struct::<Config>(&bindings))
+///     .extract()?;
+/// ```
+///
+/// Vars and secrets are the string-valued properties of the object and
+/// answer both lookups; a property of any other kind, such as a KV
+/// namespace, fails its lookup, and an absent one misses.
+#[derive(Clone, Debug)]
+pub struct JsBindings {
+    object: Object,
+}
+
+impl JsBindings {
+    /// The bindings held by `object`.
+    #[must_use]
+    pub fn new(object: Object) -> Self {
+        Self { object }
+    }
+
+    /// The bindings held by `value`, if it is an object.
+    #[must_use]
+    pub fn from_value(value: JsValue) -> Option<Self> {
+        value.dyn_into().ok().map(Self::new)
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>, BindingError> {
+        let value = Reflect::get(&self.object, &JsValue::from_str(name))
+            .map_err(|_| BindingError::new(format!("binding `{name}` cannot be read")))?;
+        if value.is_undefined() {
+            return Ok(None);
+        }
+        match value.as_string() {
+            Some(value) => Ok(Some(value)),
+            None => Err(BindingError::new(format!(
+                "binding `{name}` is not a string"
+            ))),
+        }
+    }
+}
+
+impl BindingSource for JsBindings {
+    fn var(&self, name: &str) -> Result<Option<String>, BindingError> {
+        self.get(name)
+    }
+
+    fn secret(&self, name: &str) -> Result<Option<String>, BindingError> {
+        self.get(name)
+    }
+
+    fn names(&self) -> Option<Vec<String>> {
+        Some(string_names(&self.object))
+    }
+
+    fn prefetch(&self) -> Option<HashMap<String, String>> {
+        Some(string_entries(&self.object))
+    }
+}
+
+/// The names of the string-valued properties of `object`: on an env
+/// object, its vars and secrets, since every other kind of binding is an
+/// object.
+pub(crate) fn string_names(object: &Object) -> Vec<String> {
+    Object::keys(object)
+        .iter()
+        .filter(|name| Reflect::get(object, name).is_ok_and(|value| value.is_string()))
+        .filter_map(|name| name.as_string())
+        .collect()
+}
+
+/// The string-valued properties of `object`, read in one reflection pass.
+pub(crate) fn string_entries(object: &Object) -> HashMap<String, String> {
+    Object::entries(object)
+        .iter()
+        .filter_map(|entry| {
+            let entry = entry.unchecked_into::<Array>();
+            Some((entry.get(0).as_string()?, entry.get(1).as_string()?))
+        })
+        .collect()
+}
@@ -0,0 +1,82 @@
+use figment2::{
+    value::{Dict, Map},
+    Error, Metadata, Profile, Provider,
+};
+
+use crate::BindingSource;
+
+/// A [figment2] provider emitting a whole configuration held as a JSON
+/// object in a single binding, such as an `APP_CONFIG` var, so only the
+/// secrets and the odd override need bindings of their own.
+///
+/// Merged before a [`CloudflareWorkersBindings`](crate::CloudflareWorkersBindings)
+/// provider, individual vars and secrets override the document field by
+/// field:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, JsonBinding};
+///
+/// let config: Config = Figment::new()
+///     .merge(JsonBinding::new(&env, "APP_CONFIG"))
+///     .merge(CloudflareWorkersBindings::from_struct::<Config>(&env))
+///     .extract_lossy()?;
+/// ```
+///
+/// The binding is read as a var, then as a secret, and must hold the
+/// document as a string: a secret, or a var written as a TOML string
+/// rather than an inline table, which the runtime binds as an object. An
+/// unbound document emits nothing.
+pub struct JsonBinding<'a> {
+    source: &'a dyn BindingSource,
+    binding: String,
+}
+
+impl<'a> JsonBinding<'a> {
+    /// Read the document bound as `binding` in `source`.
+    #[must_use]
+    pub fn new(source: &'a dyn BindingSource, binding: impl Into<String>) -> Self {
+        Self {
+            source,
+            binding: binding.into(),
+        }
+    }
+
+    /// The name of the binding holding the document.
+    #[must_use]
+    pub fn binding(&self) -> &str {
+        &self.binding
+    }
+}
+
+impl std::fmt::Debug for JsonBinding<'_> {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("JsonBinding")
+            .field("binding", &self.binding)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Provider for JsonBinding<'_> {
+    fn metadata(&self) -> Metadata {
+        Metadata::named(format!("`{}` JSON binding", self.binding))
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        let binding = &self.binding;
+        let document = match self.source.var(binding) {
+            Ok(Some(document)) => Some(document),
+            _ => self
+                .source
+                .secret(binding)
+                .map_err(|error| Error::from(format!("binding `{binding}`: {error}")))?,
+        };
+        let Some(document) = document else {
+            return Ok(Map::new());
+        };
+        let values: Dict = serde_json::from_str(&document).map_err(|error| {
+            Error::from(format!("binding `{binding}` is not a JSON object: {error}"))
+        })?;
+        Ok(Profile::Default.collect(values))
+    }
+}
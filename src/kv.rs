@@ -0,0 +1,402 @@
+use std::{cell::RefCell, collections::HashMap, time::Duration};
+
+use figment2::{Error, Figment};
+use futures_util::future::try_join_all;
+use serde::de::DeserializeOwned;
+use worker::{kv::KvStore, Date, Env, Request};
+
+use crate::{BindingError, BindingSource, CloudflareWorkersBindings};
+
+/// The key [`ConfigStore::put_versioned`] keeps the version of the
+/// configuration under, unless changed with [`ConfigStore::version_key`].
+pub const DEFAULT_VERSION_KEY: &str = "CONFIG_VERSION";
+
+/// A value read from KV and the time (in milliseconds since the epoch) at
+/// which it goes stale.
+type CachedValue = (Option<String>, u64);
+
+thread_local! {
+    /// Values read by every [`ConfigStore`] in the isolate, keyed by
+    /// namespace binding and key.
+    static CACHE: RefCell<HashMap<(String, String), CachedValue>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Configuration values stored under per-field keys in a Workers KV
+/// namespace.
+///
+/// KV reads are asynchronous, so a store is first
+/// [`load`](Self::load)ed into [`KvBindings`], a [`BindingSource`] that can
+/// then back (or fall back behind) a
+/// [`CloudflareWorkersBindings`](crate::CloudflareWorkersBindings) provider.
+/// Keys are the binding names of the fields, e.g. `DATABASE_URL`:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, ConfigStore};
+///
+/// let kv = ConfigStore::new(&env, "CONFIG")?
+///     .cache_ttl(Duration::from_secs(300))
+///     .max_age(Duration::from_secs(30))
+///     .load::<Config>()
+///     .await?;
+/// let config: Config = Figment::new()
+///     .merge(CloudflareWorkersBindings::from_sources::<Config>(&[&env, &kv]))
+///     .extract()?;
+/// ```
+///
+/// Frequently reloaded configuration can be cached at two levels:
+/// [`cache_ttl`](Self::cache_ttl) lets the KV edge cache answer reads, and
+/// [`max_age`](Self::max_age) keeps values in the isolate between requests.
+/// Either way, a change written to KV is seen within the two windows
+/// combined.
+///
+/// Multi-tenant workers can keep per-tenant overrides under keys prefixed
+/// with the tenant, e.g. `acme.example.com/DATABASE_URL`, and extract each
+/// tenant's configuration, its overrides layered over the environment, in
+/// one call with [`extract_tenant`](Self::extract_tenant), or
+/// [`extract_for_host`](Self::extract_for_host) for tenants named by
+/// hostname:
+///
+/// ```rust,ignore
+/// let config: Config = ConfigStore::new(&env, "TENANTS")?
+///     .max_age(Duration::from_secs(60))
+///     .extract_for_host(&env, &request)
+///     .await?;
+/// ```
+///
+/// Admin endpoints can write values back to the same keys with
+/// [`put`](Self::put), or, guarding against concurrent edits, with
+/// [`put_versioned`](Self::put_versioned):
+///
+/// ```rust,ignore
+/// let store = ConfigStore::new(&env, "CONFIG")?.version_key("CONFIG_VERSION");
+/// let version = store.version().await?;
+/// // ... render a form with `version` and receive the edit back ...
+/// store.put_versioned(version, &[("MAX_CONNECTIONS", "20")]).await?;
+/// ```
+#[derive(Debug)]
+pub struct ConfigStore {
+    binding: String,
+    store: KvStore,
+    cache_ttl: Option<Duration>,
+    max_age: Option<Duration>,
+    version_key: String,
+}
+
+impl ConfigStore {
+    /// Read configuration from the KV namespace bound as `binding`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `binding` is not a KV namespace binding.
+    pub fn new(env: &Env, binding: &str) -> Result<Self, Error> {
+        let store = env
+            .kv(binding)
+            .map_err(|error| Error::from(format!("KV namespace `{binding}`: {error}")))?;
+        Ok(Self {
+            binding: binding.to_owned(),
+            store,
+            cache_ttl: None,
+            max_age: None,
+            version_key: DEFAULT_VERSION_KEY.to_owned(),
+        })
+    }
+
+    /// Pass `ttl` as the `cacheTtl` of every KV read, so the edge location
+    /// may answer from its cache for that long. KV requires at least 60
+    /// seconds; shorter durations are rounded up.
+    #[must_use]
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl.max(Duration::from_mins(1)));
+        self
+    }
+
+    /// Keep values read from KV in the isolate for `age`, so requests served
+    /// within that window do not read KV at all.
+    #[must_use]
+    pub fn max_age(mut self, age: Duration) -> Self {
+        self.max_age = Some(age);
+        self
+    }
+
+    /// Store the version of the configuration, which
+    /// [`put_versioned`](Self::put_versioned) checks and increments, under
+    /// `key` instead of [`DEFAULT_VERSION_KEY`].
+    #[must_use]
+    pub fn version_key(mut self, key: impl Into<String>) -> Self {
+        self.version_key = key.into();
+        self
+    }
+
+    /// Read the keys for the fields of `T`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the fields of `T` cannot be discovered (see
+    /// [`from_struct`](crate::CloudflareWorkersBindings::from_struct)), or a
+    /// KV read fails.
+    pub async fn load<T: DeserializeOwned + 'static>(&self) -> Result<KvBindings, Error> {
+        let keys: Vec<&str> = crate::struct_fields::<T>()?
+            .iter()
+            .map(|field| field.binding.as_ref())
+            .collect();
+        self.load_keys(&keys).await
+    }
+
+    /// Read the given keys.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a KV read fails.
+    pub async fn load_keys(&self, keys: &[&str]) -> Result<KvBindings, Error> {
+        #[cfg(feature = "timing")]
+        let started = crate::clock::now_ms();
+        let values = try_join_all(keys.iter().map(|key| self.get(key))).await?;
+        Ok(KvBindings {
+            values: keys
+                .iter()
+                .zip(values)
+                .filter_map(|(key, value)| Some(((*key).to_owned(), value?)))
+                .collect(),
+            #[cfg(feature = "timing")]
+            fetch_ms: crate::clock::now_ms() - started,
+        })
+    }
+
+    /// Read the overrides of `tenant` for the fields of `T`, stored under the
+    /// keys of [`load`](Self::load) prefixed with `<tenant>/`. The bindings
+    /// are named without the prefix, so they can be layered over the
+    /// configuration every tenant shares.
+    ///
+    /// # Errors
+    ///
+    /// Fails as [`load`](Self::load) does.
+    pub async fn load_tenant<T: DeserializeOwned + 'static>(
+        &self,
+        tenant: &str,
+    ) -> Result<KvBindings, Error> {
+        let prefix = format!("{tenant}/");
+        let keys: Vec<String> = crate::struct_fields::<T>()?
+            .iter()
+            .map(|field| format!("{prefix}{}", field.binding))
+            .collect();
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+        let mut bindings = self.load_keys(&keys).await?;
+        bindings.values = bindings
+            .values
+            .into_iter()
+            .filter_map(|(key, value)| Some((key.strip_prefix(&prefix)?.to_owned(), value)))
+            .collect();
+        Ok(bindings)
+    }
+
+    /// Extract the configuration of `tenant`: its
+    /// [overrides](Self::load_tenant) layered over the bindings of `env`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the overrides cannot be read, or the layered bindings do not
+    /// form a valid `T`.
+    pub async fn extract_tenant<T: DeserializeOwned + 'static>(
+        &self,
+        env: &Env,
+        tenant: &str,
+    ) -> Result<T, Error> {
+        let overrides = self.load_tenant::<T>(tenant).await?;
+        Figment::new()
+            .merge(CloudflareWorkersBindings::from_sources::<T>(&[
+                &overrides, env,
+            ]))
+            .extract_lossy()
+    }
+
+    /// Extract the configuration of the tenant named by the hostname
+    /// `request` was sent to; see [`extract_tenant`](Self::extract_tenant).
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request URL has no hostname, or as
+    /// [`extract_tenant`](Self::extract_tenant) does.
+    pub async fn extract_for_host<T: DeserializeOwned + 'static>(
+        &self,
+        env: &Env,
+        request: &Request,
+    ) -> Result<T, Error> {
+        let url = request
+            .url()
+            .map_err(|error| Error::from(format!("request URL: {error}")))?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| Error::from(format!("request URL `{url}` has no hostname")))?;
+        self.extract_tenant(env, host).await
+    }
+
+    /// Write `value` under `key`, and drop any value of `key` cached in the
+    /// isolate, so this isolate reads it back at once. Other isolates and
+    /// edge locations see it once their caches expire.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the KV write fails.
+    pub async fn put(&self, key: &str, value: &str) -> Result<(), Error> {
+        self.store
+            .put(key, value)
+            .map_err(|error| Error::from(format!("KV key `{key}`: {error}")))?
+            .execute()
+            .await
+            .map_err(|error| Error::from(format!("KV key `{key}`: {error}")))?;
+        CACHE.with(|cache| {
+            cache
+                .borrow_mut()
+                .remove(&(self.binding.clone(), key.to_owned()))
+        });
+        Ok(())
+    }
+
+    /// Write every key and value of `values`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a KV write fails; the writes before it are kept.
+    pub async fn put_all(&self, values: &[(&str, &str)]) -> Result<(), Error> {
+        for (key, value) in values {
+            self.put(key, value).await?;
+        }
+        Ok(())
+    }
+
+    /// The current version of the configuration, read from the
+    /// [version key](Self::version_key) bypassing every cache; 0 if it was
+    /// never written.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the KV read fails or the version is not a number.
+    pub async fn version(&self) -> Result<u64, Error> {
+        let key = &self.version_key;
+        let version = self
+            .store
+            .get(key)
+            .text()
+            .await
+            .map_err(|error| Error::from(format!("KV key `{key}`: {error}")))?;
+        version.map_or(Ok(0), |version| {
+            version.parse().map_err(|_| {
+                Error::from(format!(
+                    "KV key `{key}` holds `{version}`, not a configuration version"
+                ))
+            })
+        })
+    }
+
+    /// Write `values` if the configuration is still at version `expected`,
+    /// then increment the version, returning the new one. An admin endpoint
+    /// reads the [`version`](Self::version) along with the values it shows,
+    /// and writes the edit back with it, so an edit made meanwhile is not
+    /// silently overwritten.
+    ///
+    /// KV has no transactions, so the check is optimistic: it catches edits
+    /// made since `expected` was read, but two writers checking within the
+    /// same moment (or across locations within KV's propagation delay) can
+    /// both succeed.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the version is not `expected`, writing nothing, or if a KV
+    /// read or write fails.
+    pub async fn put_versioned(
+        &self,
+        expected: u64,
+        values: &[(&str, &str)],
+    ) -> Result<u64, Error> {
+        let current = self.version().await?;
+        if current != expected {
+            return Err(Error::from(format!(
+                "configuration version conflict: expected {expected}, found {current}"
+            )));
+        }
+        self.put_all(values).await?;
+        let next = current + 1;
+        self.put(&self.version_key, &next.to_string()).await?;
+        Ok(next)
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>, Error> {
+        let cache_key = (self.binding.clone(), key.to_owned());
+        let now = Date::now().as_millis();
+        if self.max_age.is_some() {
+            let cached = CACHE.with(|cache| cache.borrow().get(&cache_key).cloned());
+            if let Some((value, stale_at)) = cached {
+                if now < stale_at {
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(
+                        namespace = self.binding,
+                        key,
+                        hit = value.is_some(),
+                        "KV lookup (cached in isolate)",
+                    );
+                    return Ok(value);
+                }
+            }
+        }
+
+        let mut get = self.store.get(key);
+        if let Some(ttl) = self.cache_ttl {
+            get = get.cache_ttl(ttl.as_secs());
+        }
+        #[cfg(feature = "tracing")]
+        let started = crate::clock::now_ms();
+        let value = get
+            .text()
+            .await
+            .map_err(|error| Error::from(format!("KV key `{key}`: {error}")))?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            namespace = self.binding,
+            key,
+            hit = value.is_some(),
+            elapsed_ms = crate::clock::now_ms() - started,
+            "KV lookup",
+        );
+
+        if let Some(age) = self.max_age {
+            let stale_at = now.saturating_add(u64::try_from(age.as_millis()).unwrap_or(u64::MAX));
+            CACHE.with(|cache| {
+                cache
+                    .borrow_mut()
+                    .insert(cache_key, (value.clone(), stale_at))
+            });
+        }
+        Ok(value)
+    }
+}
+
+/// Values loaded from a [`ConfigStore`].
+///
+/// Every key resolves as a var and secret lookups always miss, since KV
+/// values are not secrets.
+#[derive(Clone, Debug, Default)]
+pub struct KvBindings {
+    values: HashMap<String, String>,
+    #[cfg(feature = "timing")]
+    fetch_ms: f64,
+}
+
+impl BindingSource for KvBindings {
+    fn var(&self, name: &str) -> Result<Option<String>, BindingError> {
+        Ok(self.values.get(name).cloned())
+    }
+
+    fn secret(&self, _name: &str) -> Result<Option<String>, BindingError> {
+        Ok(None)
+    }
+
+    fn names(&self) -> Option<Vec<String>> {
+        Some(self.values.keys().cloned().collect())
+    }
+
+    #[cfg(feature = "timing")]
+    fn fetch_ms(&self) -> f64 {
+        self.fetch_ms
+    }
+}
@@ -0,0 +1,141 @@
+use std::{
+    fmt,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use figment2::Error;
+use serde::de::DeserializeOwned;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{extract_config, BindingSource, CachedConfig};
+
+/// A tower [`Layer`] inserting the configuration into the extensions of
+/// every request, so services downstream pull it out as an `Arc<T>`, e.g.
+/// with axum's `Extension<Arc<T>>`, instead of extracting it themselves.
+///
+/// Build the layer in the `fetch` handler, from the cached configuration
+/// so the isolate extracts it only once:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CachedConfig, ConfigLayer};
+///
+/// static CONFIG: CachedConfig<Config> = CachedConfig::new();
+///
+/// #[event(fetch)]
+/// async fn fetch(request: HttpRequest, env: Env, _context: Context) -> Result<axum::http::Response<Body>> {
+///     let layer = ConfigLayer::from_cache(&CONFIG, &env).map_err(|error| Error::RustError(error.to_string()))?;
+///     Ok(router().layer(layer).call(request).await?)
+/// }
+///
+/// async fn handler(Extension(config): Extension<Arc<Config>>) -> String {
+///     config.greeting.clone()
+/// }
+/// ```
+///
+/// Extraction happens when the layer is built, so a failure surfaces there
+/// rather than as an error of the wrapped service.
+pub struct ConfigLayer<T> {
+    config: Arc<T>,
+}
+
+impl<T> ConfigLayer<T> {
+    /// A layer inserting `config`.
+    #[must_use]
+    pub fn new(config: Arc<T>) -> Self {
+        Self { config }
+    }
+}
+
+impl<T: DeserializeOwned + 'static> ConfigLayer<T> {
+    /// A layer inserting the configuration extracted from the bindings of
+    /// `source` with [`extract_config`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if the configuration cannot be extracted.
+    pub fn extract(source: &dyn BindingSource) -> Result<Self, Error> {
+        extract_config(source).map(|config| Self::new(Arc::new(config)))
+    }
+
+    /// A layer inserting the configuration cached in `cache`, extracted from
+    /// the bindings of `source` if it is not cached yet.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the configuration is not cached and cannot be extracted.
+    pub fn from_cache(cache: &CachedConfig<T>, source: &dyn BindingSource) -> Result<Self, Error> {
+        cache.get(source).map(Self::new)
+    }
+}
+
+impl<T> Clone for ConfigLayer<T> {
+    fn clone(&self) -> Self {
+        Self::new(Arc::clone(&self.config))
+    }
+}
+
+impl<T> fmt::Debug for ConfigLayer<T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The configuration may hold secrets.
+        formatter
+            .debug_struct("ConfigLayer")
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, T> Layer<S> for ConfigLayer<T> {
+    type Service = ConfigService<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConfigService {
+            inner,
+            config: Arc::clone(&self.config),
+        }
+    }
+}
+
+/// The service of a [`ConfigLayer`], inserting the configuration into each
+/// request before passing it on to `S`.
+pub struct ConfigService<S, T> {
+    inner: S,
+    config: Arc<T>,
+}
+
+impl<S: Clone, T> Clone for ConfigService<S, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            config: Arc::clone(&self.config),
+        }
+    }
+}
+
+impl<S: fmt::Debug, T> fmt::Debug for ConfigService<S, T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("ConfigService")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, T, B> Service<http::Request<B>> for ConfigService<S, T>
+where
+    S: Service<http::Request<B>>,
+    T: Send + Sync + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, context: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(context)
+    }
+
+    fn call(&mut self, mut request: http::Request<B>) -> Self::Future {
+        request.extensions_mut().insert(Arc::clone(&self.config));
+        self.inner.call(request)
+    }
+}
@@ -0,0 +1,159 @@
+use figment2::{Error, Figment};
+use serde::de::DeserializeOwned;
+
+use crate::{CloudflareWorkersBindings, FigmentExt};
+
+/// A [`CloudflareWorkersBindings`] provider that is not merged until a
+/// value is extracted, created by
+/// [`lazy`](CloudflareWorkersBindings::lazy), so that extracting one field
+/// or section looks up the bindings under it alone:
+///
+/// ```rust,ignore
+/// let config = CloudflareWorkersBindings::from_struct::<Config>(&env).lazy(Figment::new());
+/// // Reads `DATABASE_URL` and nothing else.
+/// let database_url: String = config.extract_inner("database_url")?;
+/// ```
+///
+/// Merging a provider into a figment resolves every field it reads at
+/// once, as figment asks providers for all their data up front. Each
+/// extraction here instead merges the provider
+/// [`focus`](CloudflareWorkersBindings::focus)ed on the key extracted over
+/// the figment given, so a handler that needs a small slice of a large
+/// shared configuration struct pays for that slice only. Extractions do not
+/// share their lookups: extracting the same key twice looks its bindings up
+/// twice.
+#[derive(Clone)]
+pub struct Lazy<'a> {
+    figment: Figment,
+    provider: CloudflareWorkersBindings<'a>,
+}
+
+impl<'a> Lazy<'a> {
+    pub(crate) fn new(figment: Figment, provider: CloudflareWorkersBindings<'a>) -> Self {
+        Self { figment, provider }
+    }
+
+    /// The figment with the provider merged in, looking up only the
+    /// bindings under the dotted key `path`, or all of them for `None`.
+    fn figment(&self, path: Option<&str>) -> Figment {
+        let provider = match path {
+            Some(path) => self.provider.clone().focus(path),
+            None => self.provider.clone(),
+        };
+        self.figment.clone().merge_bindings(&provider)
+    }
+
+    /// Extract the value at the dotted key `path`, looking up only the
+    /// bindings of the fields under it.
+    ///
+    /// # Errors
+    ///
+    /// Fails as [`Figment::extract_inner`] does, including if resolving
+    /// those fields fails.
+    pub fn extract_inner<T: DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
+        self.figment(Some(path)).extract_inner(path)
+    }
+
+    /// Like [`extract_inner`](Self::extract_inner), but converting strings
+    /// as [`Figment::extract_inner_lossy`] does.
+    ///
+    /// # Errors
+    ///
+    /// Fails as [`Figment::extract_inner_lossy`] does, including if
+    /// resolving those fields fails.
+    pub fn extract_inner_lossy<T: DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
+        self.figment(Some(path)).extract_inner_lossy(path)
+    }
+
+    /// Extract the whole configuration, looking up every binding.
+    ///
+    /// # Errors
+    ///
+    /// Fails as [`Figment::extract`] does, including if resolving fails.
+    pub fn extract<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        self.figment(None).extract()
+    }
+}
+
+#[cfg(all(test, feature = "test-util"))]
+mod tests {
+    use std::cell::RefCell;
+
+    use figment2::{providers::Serialized, Figment};
+    use serde::Deserialize;
+
+    use crate::{BindingError, BindingSource, CloudflareWorkersBindings, MockBindings};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Database {
+        url: String,
+        pool_size: u16,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[allow(dead_code)]
+    struct Config {
+        api_base_url: String,
+        api_key: String,
+        max_retries: u8,
+    }
+
+    /// Records the names it is asked for.
+    struct Counting {
+        bindings: MockBindings,
+        read: RefCell<Vec<String>>,
+    }
+
+    impl BindingSource for Counting {
+        fn var(&self, name: &str) -> Result<Option<String>, BindingError> {
+            self.read.borrow_mut().push(name.to_owned());
+            self.bindings.var(name)
+        }
+
+        fn secret(&self, name: &str) -> Result<Option<String>, BindingError> {
+            self.read.borrow_mut().push(name.to_owned());
+            self.bindings.secret(name)
+        }
+    }
+
+    fn counting() -> Counting {
+        Counting {
+            bindings: MockBindings::new()
+                .with_var("API_BASE_URL", "https://api.example.com/v1")
+                .with_secret("API_KEY", "super-secret-key")
+                .with_var("MAX_RETRIES", "3")
+                .with_var("URL", "postgres://localhost/app"),
+            read: RefCell::new(Vec::new()),
+        }
+    }
+
+    #[test]
+    fn only_the_extracted_field_is_looked_up() {
+        let source = counting();
+        let config = CloudflareWorkersBindings::from_struct::<Config>(&source).lazy(Figment::new());
+        assert!(source.read.borrow().is_empty());
+        let retries: u8 = config.extract_inner_lossy("max_retries").unwrap();
+        assert_eq!(retries, 3);
+        let read = source.read.take();
+        assert!(
+            !read.is_empty() && read.iter().all(|name| name == "MAX_RETRIES"),
+            "{read:?}"
+        );
+    }
+
+    #[test]
+    fn a_section_is_extracted_over_the_figment() {
+        let source = counting();
+        let config = CloudflareWorkersBindings::from_struct::<Database>(&source)
+            .at("database")
+            .lazy(Figment::from(Serialized::default("database.pool_size", 5)));
+        let database: Database = config.extract_inner("database").unwrap();
+        assert_eq!(
+            database,
+            Database {
+                url: "postgres://localhost/app".to_owned(),
+                pool_size: 5,
+            }
+        );
+    }
+}
@@ -7,9 +7,10 @@
 //! # Usage
 //!
 //! Construct a [`CloudflareWorkersBindings`] provider via
-//! [`from_struct`](CloudflareWorkersBindings::from_struct), passing the target
-//! configuration type as a type parameter. The provider uppercases field names
-//! to derive the Cloudflare binding names, and reads each one from [`worker::Env`]:
+//! [`from_struct`](CloudflareWorkersBindings::from_struct), passing the
+//! target configuration type as a type parameter. The provider uppercases
+//! field names to derive the Cloudflare binding names, and reads each one
+//! from [`worker::Env`]:
 //!
 //! ```rust,ignore
 //! use figment2::Figment;
@@ -29,14 +30,37 @@
 //! ```
 //!
 //! The above looks up `DATABASE_URL` and `MAX_CONNECTIONS` in the worker
-//! environment automatically.
+//! environment automatically. With [`FigmentExt`] in scope, the
+//! single-provider case is a one-liner:
+//!
+//! ```rust,ignore
+//! use figment2_cloudflare_workers::FigmentExt;
+//!
+//! let config: Config = Figment::from_cloudflare::<Config>(&env).extract()?;
+//! ```
+//!
+//! The provider reads bindings through the [`BindingSource`] trait, which is
+//! implemented for [`worker::Env`], `HashMap<String, String>` and
+//! [`ProcessEnv`], among others, so configuration logic can also be tested
+//! on the host. Since bindings are fixed for the lifetime of an isolate, a
+//! `static` [`CachedConfig`] can extract once and share the result with
+//! every later request.
 //!
 //! # Vars vs. secrets
 //!
 //! Cloudflare Workers distinguish between plain-text **variables** and
 //! **secrets**, but a given binding name can only be one or the other. The
 //! provider therefore tries [`worker::Env::var`] first and falls back to
-//! [`worker::Env::secret`] — no manual annotation is needed.
+//! [`worker::Env::secret`] — no manual annotation is needed. Teams that want
+//! secrets to always win over stray vars of the same name can flip this with
+//! [`lookup_order`](CloudflareWorkersBindings::lookup_order):
+//!
+//! ```rust,ignore
+//! use figment2_cloudflare_workers::{CloudflareWorkersBindings, LookupOrder};
+//!
+//! let provider = CloudflareWorkersBindings::from_struct::<Config>(&env)
+//!     .lookup_order(LookupOrder::SecretThenVar);
+//! ```
 //!
 //! At the struct level, the recommended convention is to use
 //! [`secrecy::SecretString`] for fields backed by secrets. This prevents
@@ -52,39 +76,704 @@
 //!     api_key: SecretString,          // Cloudflare secret
 //! }
 //! ```
+//!
+//! [`secrecy::SecretString`]: https://docs.rs/secrecy/latest/secrecy/type.SecretString.html
+//!
+//! # Cargo features
+//!
+//! Bundle size is a hard limit on Workers, so everything beyond reading
+//! vars and secrets is opt-in, and each feature pulls in only the
+//! dependencies it needs. Each item below documents itself.
+//!
+//! | Feature | Adds |
+//! |---|---|
+//! | `worker` (default) | [`worker::Env`] as a binding source |
+//! | `diagnostics` (default) | `diff`, `Redacted` and diagnostics reports |
+//! | `admin` | `AdminEndpoint`, serving and reloading a `CachedConfig` |
+//! | `analytics-engine` | `FailureAnalytics` data points for failed loads |
+//! | `audit` | `AuditEvent` records of each load |
+//! | `axum` | the `Config` extractor |
+//! | `console-debug` | `console_debug` reports for `wrangler tail` |
+//! | `d1` | `D1ConfigStore`, values kept in a D1 table |
+//! | `derive` | `#[derive(CloudflareConfig)]` and `#[derive(FieldNames)]` |
+//! | `dotenv` | `DotenvFile`, a `.env` file as a binding source (native) |
+//! | `durable-object` | `ConfigHub` and `HubClient`, pushed changes |
+//! | `encryption` | `enc:v1:` values, decrypted with `decrypt_with` |
+//! | `fingerprint` | snapshot fingerprints |
+//! | `flags` | `FeatureFlags`, boolean and variant flags in KV |
+//! | `garde` | `extract_validated_garde` |
+//! | `js` | `JsBindings`, a plain JS env object as a binding source |
+//! | `json-binding` | `JsonBinding`, a configuration held in one binding |
+//! | `kv` | `ConfigStore`, values kept in Workers KV |
+//! | `log` | `log` records of how each field resolved |
+//! | `overrides` | `HeaderOverrides` and `QueryOverrides` |
+//! | `proptest` | `strategies` for property tests (native) |
+//! | `queue` | `ChangeNotifier`, changes published to a Queue |
+//! | `regex` | `matches`, checking values against regular expressions |
+//! | `remote` | `RemoteDocument`, documents fetched from an origin |
+//! | `rollout` | `Rollout` staged values and `Variant` experiments |
+//! | `rotation` | `SecretRotator`, pushing secrets to a worker (native) |
+//! | `secrecy` | `secret_bytes` decoders |
+//! | `semver` | `versions` parsers |
+//! | `signatures` | `Signed` documents |
+//! | `stack` | `CloudflareStack`, bindings layered with KV and R2 |
+//! | `startup` | `StartupConfig`, extracted in the `start` event |
+//! | `test-util` | `MockBindings` and `assert_config_matches` |
+//! | `timing` | `LoadTimings` of lookups, fetches and extraction |
+//! | `tower` | `ConfigLayer` |
+//! | `trace` | `trace_json` resolution traces |
+//! | `tracing` | a span per resolution and an event per lookup |
+//! | `uuid` | `uuids` parsers |
+//! | `validator` | `extract_validated` |
+//! | `watch` | `KvWatcher`, reloading when a KV version key changes |
+//! | `wrangler` | `SecretsFile`, `StructGenerator` and other local tooling |
+//!
+//! The smallest build therefore depends on `worker`, `figment2` (without its
+//! default features) and `serde` alone:
+//!
+//! ```toml
+//! figment2-cloudflare-workers = { version = "0.1", default-features = false, features = ["worker"] }
+//! ```
+
+use std::{
+    any::{type_name, TypeId},
+    borrow::Cow,
+    cell::{Cell, OnceCell, RefCell},
+    collections::{BTreeMap, BTreeSet, HashMap},
+    convert::Infallible,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+    rc::Rc,
+};
 
 use figment2::{
     value::{Dict, Map, Value},
-    Error, Metadata, Profile, Provider,
+    Error, Figment, Metadata, Profile, Provider,
 };
 use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
+// `rotation` is host-only, but its `serde_json` dependency is not.
+#[cfg(all(feature = "rotation", target_arch = "wasm32"))]
+use serde_json as _;
+
+mod accumulate;
+#[cfg(feature = "admin")]
+mod admin;
+#[cfg(feature = "analytics-engine")]
+mod analytics;
+#[cfg(feature = "audit")]
+mod audit;
+mod cache;
+mod canary;
+mod cell;
+mod clock;
+mod config;
+#[cfg(feature = "d1")]
+mod d1;
+mod defer;
+mod describe;
+mod deserializer;
+#[cfg(feature = "diagnostics")]
+mod diff;
+#[cfg(all(feature = "dotenv", not(target_arch = "wasm32")))]
+mod dotenv;
+#[cfg(feature = "encryption")]
+mod encryption;
+mod ext;
+#[cfg(feature = "axum")]
+mod extractor;
+#[cfg(feature = "flags")]
+mod flags;
+#[cfg(feature = "wrangler")]
+mod generate;
+#[cfg(feature = "test-util")]
+mod golden;
+#[cfg(feature = "durable-object")]
+mod hub;
+mod inspect;
+mod interpolate;
+#[cfg(feature = "js")]
+mod js;
+#[cfg(feature = "json-binding")]
+mod json;
+#[cfg(feature = "kv")]
+mod kv;
+#[cfg(feature = "tower")]
+mod layer;
+mod lazy;
+mod metrics;
+mod migrate;
+#[cfg(feature = "test-util")]
+mod mock;
+#[cfg(feature = "queue")]
+mod notify;
+#[cfg(feature = "overrides")]
+mod overrides;
+#[cfg(feature = "worker")]
+mod pages;
+#[cfg(feature = "diagnostics")]
+mod redact;
+#[cfg(feature = "remote")]
+mod remote;
+#[cfg(any(
+    feature = "diagnostics",
+    feature = "fingerprint",
+    feature = "test-util"
+))]
+mod render;
+#[cfg(feature = "diagnostics")]
+mod report;
+#[cfg(feature = "rollout")]
+mod rollout;
+#[cfg(all(feature = "rotation", not(target_arch = "wasm32")))]
+mod rotate;
+#[cfg(feature = "worker")]
+mod router;
+#[cfg(feature = "secrecy")]
+pub mod secret_bytes;
+mod share;
+mod shared;
+#[cfg(feature = "signatures")]
+mod signed;
+mod snapshot;
+mod source;
+#[cfg(feature = "stack")]
+mod stack;
+#[cfg(feature = "startup")]
+mod startup;
+#[cfg(all(feature = "proptest", not(target_arch = "wasm32")))]
+pub mod strategies;
+mod tagged;
+#[cfg(feature = "worker")]
+mod templated;
+#[cfg(feature = "timing")]
+mod timing;
+#[cfg(any(feature = "admin", feature = "overrides"))]
+mod token;
+#[cfg(feature = "trace")]
+mod trace;
+#[cfg(feature = "uuid")]
+pub mod uuids;
+#[cfg(any(feature = "garde", feature = "validator"))]
+mod validate;
+#[cfg(feature = "rollout")]
+mod variant;
+#[cfg(feature = "semver")]
+pub mod versions;
+#[cfg(feature = "watch")]
+mod watch;
+#[cfg(feature = "wrangler")]
+mod wrangler;
+
+pub use accumulate::Accumulated;
+#[cfg(feature = "admin")]
+pub use admin::AdminEndpoint;
+#[cfg(feature = "analytics-engine")]
+pub use analytics::FailureAnalytics;
+#[cfg(feature = "audit")]
+pub use audit::{Audit, AuditEvent, AuditKind, AUDIT_SCHEMA, AUDIT_VERSION};
+pub use cache::CachedConfig;
+pub use canary::{Canary, CanaryOutcome};
+pub use cell::{ConfigCell, RetryPolicy};
+pub use config::{CloudflareConfig, ConfigStructs, FieldBinding, FieldNames};
+#[doc(hidden)]
+pub use config::{DeclaredFieldNames, DiscoveredFieldNames, FieldsOf};
+#[cfg(feature = "d1")]
+pub use d1::{D1Bindings, D1ConfigStore, DEFAULT_D1_TABLE};
+pub use defer::Deferred;
+pub use describe::{describe, BindingSpec};
+pub use deserializer::{from_env, BindingsDeserializer};
+#[cfg(feature = "diagnostics")]
+pub use diff::{diff, Change, ConfigDiff};
+#[cfg(all(feature = "dotenv", not(target_arch = "wasm32")))]
+pub use dotenv::DotenvFile;
+#[cfg(feature = "encryption")]
+pub use encryption::{encrypt_value, ENCRYPTED_PREFIX};
+pub use ext::{extract_config, FigmentExt};
+#[cfg(feature = "axum")]
+pub use extractor::Config;
+#[cfg(feature = "derive")]
+pub use figment2_cloudflare_workers_derive::{CloudflareConfig, FieldNames};
+#[cfg(feature = "flags")]
+pub use flags::{FeatureFlags, FLAGS_KEY, FLAG_PREFIX};
+#[cfg(feature = "wrangler")]
+pub use generate::{StructGenerator, WranglerStub};
+#[cfg(feature = "test-util")]
+pub use golden::assert_config_matches;
+#[cfg(feature = "durable-object")]
+pub use hub::{ConfigHub, HubBindings, HubClient};
+pub use interpolate::Interpolated;
+#[cfg(feature = "js")]
+pub use js::JsBindings;
+#[cfg(feature = "json-binding")]
+pub use json::JsonBinding;
+#[cfg(feature = "kv")]
+pub use kv::{ConfigStore, KvBindings, DEFAULT_VERSION_KEY};
+#[cfg(feature = "tower")]
+pub use layer::{ConfigLayer, ConfigService};
+pub use lazy::Lazy;
+pub use metrics::ResolutionMetrics;
+pub use migrate::{Migrated, Migrations, SCHEMA_VERSION_KEY};
+#[cfg(feature = "test-util")]
+pub use mock::MockBindings;
+#[cfg(feature = "queue")]
+pub use notify::{ChangeNotifier, ConfigChange};
+#[cfg(feature = "overrides")]
+pub use overrides::{
+    HeaderOverrides, QueryOverrides, OVERRIDE_HEADER, OVERRIDE_TOKEN_HEADER, QUERY_OVERRIDE_PREFIX,
+};
+#[cfg(feature = "worker")]
+pub use pages::{PagesEnv, PAGES_PLATFORM_VARS};
+#[cfg(feature = "diagnostics")]
+pub use redact::Redacted;
+#[cfg(feature = "remote")]
+pub use remote::{FetchedDocument, RemoteDocument};
+#[cfg(all(feature = "diagnostics", feature = "worker"))]
+pub use report::diagnostics_response;
+#[cfg(feature = "diagnostics")]
+pub use report::{Diagnostics, FieldDiagnostics};
+#[cfg(feature = "rollout")]
+pub use rollout::Rollout;
+#[cfg(all(feature = "rotation", not(target_arch = "wasm32")))]
+pub use rotate::{SecretRotator, CLOUDFLARE_API};
+#[cfg(feature = "worker")]
+pub use router::{cached_config_router, config_router};
+pub use share::SharedProvider;
+pub use shared::SharedConfig;
+#[cfg(feature = "signatures")]
+pub use signed::{Signed, VerifyingKey};
+pub use snapshot::Snapshot;
+pub use source::{BindingError, BindingSource, ProcessEnv};
+#[cfg(feature = "stack")]
+pub use stack::CloudflareStack;
+#[cfg(feature = "startup")]
+pub use startup::{global_env, StartupConfig};
+use tagged::TaggedValue;
+#[cfg(feature = "worker")]
+pub use templated::RequestTemplated;
+#[cfg(feature = "timing")]
+pub use timing::LoadTimings;
+#[cfg(feature = "trace")]
+use trace::{Candidate, FieldTrace, ResolutionTrace, Winner};
+#[cfg(feature = "validator")]
+pub use validate::extract_validated;
+#[cfg(feature = "garde")]
+pub use validate::extract_validated_garde;
+#[cfg(feature = "rollout")]
+pub use variant::Variant;
+#[cfg(feature = "watch")]
+pub use watch::KvWatcher;
+#[cfg(feature = "wrangler")]
+pub use wrangler::{check_wrangler_toml, SecretsFile, WranglerDefaults, WranglerToml};
+
+/// The placeholder that replaces masked and secret values.
+pub const REDACTED: &str = "[REDACTED]";
 
 /// A [figment2] provider that reads values from a Cloudflare Worker
 /// environment.
 ///
-/// Field names are discovered from the target struct's [`Deserialize`]
-/// implementation and uppercased to derive Cloudflare binding names
-/// (e.g. `database_url` → `DATABASE_URL`). For each binding,
-/// [`worker::Env::var`] is tried first; if that fails,
-/// [`worker::Env::secret`] is used as a fallback.
+/// Field names are discovered from the target struct's
+/// [`Deserialize`](serde::Deserialize) implementation and uppercased to
+/// derive Cloudflare binding names (e.g. `database_url` → `DATABASE_URL`).
+/// For each binding, [`worker::Env::var`] is tried first; if that fails,
+/// [`worker::Env::secret`] is used as a fallback. The order can be changed
+/// with [`lookup_order`](Self::lookup_order).
+///
+/// Bindings are read through the [`BindingSource`] trait, so any source —
+/// not just [`worker::Env`] — can back the provider.
 ///
-/// Missing bindings are silently skipped, allowing other providers in the
-/// [figment2] stack to supply defaults.
+/// Missing bindings, and bindings that cannot be read as strings, are
+/// silently skipped, allowing other providers in the [figment2] stack, or
+/// [`default`](Self::default), to supply defaults.
+///
+/// Figment's magic values can be read from bindings too. A binding for an
+/// `Either` field is parsed as figment's `Env` provider parses values, so
+/// `10` matches a `Tagged<usize>` on the left as it would in a TOML file,
+/// while `unlimited` falls through to a `String` on the right. A `Tagged`
+/// value's tag leads to this provider's metadata or, merged with
+/// [`merge_tagged`](FigmentExt::merge_tagged), to its binding's, and a
+/// `RelativePathBuf` read from a binding has no file to be relative to, so
+/// its path is used as written.
+///
+/// Each binding is read from each source at most once per provider (and a
+/// [`worker::Env`] is read in a single reflection pass), so
+/// figment calling [`Provider::data`] repeatedly during extraction, or taking
+/// a [`snapshot`](Self::snapshot) alongside, does not cross the JS boundary
+/// again.
+///
+/// Figment implements [`Provider`] for references, so one configured
+/// provider can be merged into several figments as `&provider`; one behind
+/// an `Rc`, or a [`Snapshot`] behind an `Arc`, is merged by value through a
+/// [`SharedProvider`]. A clone starts with a copy of what the original has
+/// read so far, though reads made after cloning are not shared, so
+/// per-profile variants cloned after a first merge are cheap:
+///
+/// ```rust,ignore
+/// let provider = CloudflareWorkersBindings::from_struct::<Config>(&env);
+/// let production = Figment::new().merge(&provider);
+/// let staging = Figment::new()
+///     .merge(provider.clone().profile("staging"))
+///     .select("staging");
+/// ```
+#[derive(Clone)]
 pub struct CloudflareWorkersBindings<'a> {
-    env: &'a worker::Env,
-    fields: Vec<String>,
+    source: Source<'a>,
+    fallbacks: Vec<&'a dyn BindingSource>,
+    fields: Cow<'static, [Field]>,
+    /// Why the fields of the target type could not be discovered, if so.
+    undiscovered: Option<Error>,
+    defaults: Dict,
+    required: BTreeSet<String>,
+    require_all: bool,
+    only: Option<BTreeSet<String>>,
+    skipped: BTreeSet<String>,
+    focus: Option<String>,
+    deferred: BTreeSet<String>,
+    accumulated: BTreeSet<String>,
     profile: Profile,
+    path: Option<String>,
+    lookup_order: LookupOrder,
+    binding_cases: Vec<BindingCase>,
+    aliases: BTreeMap<String, Vec<String>>,
+    secrets: BTreeSet<String>,
+    /// Whether `Debug` and `Serialize` show the values read, not only their
+    /// lengths.
+    show_read_values: bool,
+    /// The bounds numeric fields must lie within.
+    ranges: Vec<(String, Bounds)>,
+    /// The patterns fields must match, each compiled or why not.
+    #[cfg(feature = "regex")]
+    patterns: Vec<(String, Result<regex_lite::Regex, Error>)>,
+    #[cfg(feature = "encryption")]
+    decryption_key: Option<String>,
+    reads: RefCell<Vec<SourceReads>>,
+    metrics: Cell<ResolutionMetrics>,
+    #[cfg(feature = "timing")]
+    timings: Cell<LoadTimings>,
+    #[cfg(feature = "console-debug")]
+    console_debug: Option<String>,
+    #[cfg(feature = "console-debug")]
+    console_sink: fn(&str),
+    #[cfg(feature = "audit")]
+    audit: Option<Audit>,
+    #[cfg(feature = "analytics-engine")]
+    analytics: Option<FailureAnalytics>,
+    /// The required bindings the last resolution found missing.
+    #[cfg(any(feature = "analytics-engine", feature = "audit"))]
+    missing: RefCell<Vec<String>>,
+}
+
+/// The order in which [`worker::Env`] accessors are consulted for each
+/// binding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LookupOrder {
+    /// Try [`worker::Env::var`] first, then fall back to
+    /// [`worker::Env::secret`].
+    #[default]
+    VarThenSecret,
+    /// Try [`worker::Env::secret`] first, then fall back to
+    /// [`worker::Env::var`], so secrets always win over vars of the same name.
+    SecretThenVar,
+}
+
+/// A casing of a field's binding name, tried by a provider given
+/// [`binding_cases`](CloudflareWorkersBindings::binding_cases).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BindingCase {
+    /// The field's name as written, e.g. `api_key`, or the binding name
+    /// declared for it.
+    Exact,
+    /// The binding name uppercased, e.g. `API_KEY`.
+    Upper,
+    /// The binding name lowercased, e.g. `api_key`.
+    Lower,
+}
+
+/// The accessor a binding was resolved through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum BindingKind {
+    Var,
+    Secret,
+}
+
+impl BindingKind {
+    /// The name of the accessor, for diagnostics.
+    fn accessor(self) -> &'static str {
+        match self {
+            Self::Var => "var",
+            Self::Secret => "secret",
+        }
+    }
+}
+
+/// The bounds of a [`range`](CloudflareWorkersBindings::range).
+type Bounds = (Bound<f64>, Bound<f64>);
+
+/// The binding source a provider reads from.
+#[derive(Clone)]
+enum Source<'a> {
+    Borrowed(&'a dyn BindingSource),
+    Owned(Rc<dyn BindingSource>),
+}
+
+/// A source without any bindings.
+struct NoBindings;
+
+impl BindingSource for NoBindings {
+    fn var(&self, _name: &str) -> Result<Option<String>, BindingError> {
+        Ok(None)
+    }
+
+    fn secret(&self, _name: &str) -> Result<Option<String>, BindingError> {
+        Ok(None)
+    }
+}
+
+/// A field to resolve and the binding it is read from.
+#[derive(Clone, Debug)]
+struct Field {
+    name: Cow<'static, str>,
+    binding: Cow<'static, str>,
+    /// The only accessor to consult, if the field declares one.
+    accessor: Option<BindingKind>,
+    /// Whether the field is a figment `Either`, whose value is parsed as
+    /// figment's `Env` provider parses values, so that `10` can match an
+    /// integer on its left rather than only a string on its right.
+    parse: bool,
+}
+
+/// How a field's value is merged into a figment: with the provider's own
+/// data, or by its deferred or accumulated view.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MergeMode {
+    Merged,
+    Deferred,
+    Accumulated,
+}
+
+/// What a provider has read from one of its sources.
+#[derive(Clone, Default)]
+struct SourceReads {
+    prefetched: OnceCell<Option<HashMap<String, String>>>,
+    vars: HashMap<String, Option<String>>,
+    secrets: HashMap<String, Option<String>>,
+}
+
+/// A binding found in one of a provider's sources.
+struct Found {
+    value: String,
+    kind: BindingKind,
+    /// The index of the source: the primary source, then the fallbacks.
+    source: usize,
+}
+
+/// A field resolved from its binding.
+#[derive(Clone)]
+pub(crate) struct Resolution {
+    pub(crate) field: Cow<'static, str>,
+    pub(crate) value: String,
+    pub(crate) secret: bool,
+    /// The binding the value was found under.
+    binding: String,
+    kind: BindingKind,
+    source: usize,
 }
 
 impl<'a> CloudflareWorkersBindings<'a> {
     /// Create a provider that reads all fields declared in `T` from the
-    /// Cloudflare Worker environment.
+    /// Cloudflare Worker environment, or any other [`BindingSource`].
+    ///
+    /// The fields of `T` are discovered once per type (and thread) and
+    /// cached by its `TypeId`, so constructing a provider on every request
+    /// is cheap. That is why `T` must be `'static`: a configuration type
+    /// borrowing from elsewhere must own its data instead, e.g. through
+    /// `String` rather than `Cow<'a, str>`.
+    ///
+    /// Discovery relies on the `deserialize_struct` call a
+    /// `#[derive(Deserialize)]` struct makes. Types deserialised from a map
+    /// instead, such as structs with `#[serde(flatten)]` fields, do not
+    /// reveal their fields, and neither do types deserialised from any
+    /// self-describing value, such as `#[serde(untagged)]` enums. Resolving
+    /// the provider fails for them; list their fields through [`FieldNames`]
+    /// and use [`from_struct!`](crate::from_struct!) or
+    /// [`from_field_names`](Self::from_field_names) instead, or name them
+    /// with [`field_names`](Self::field_names).
     #[must_use]
-    pub fn from_struct<T: DeserializeOwned>(env: &'a worker::Env) -> Self {
+    pub fn from_struct<T: DeserializeOwned + 'static>(source: &'a dyn BindingSource) -> Self {
+        Self::discovered::<T>(Source::Borrowed(source))
+    }
+
+    /// Create a provider that reads the fields of every struct in the tuple
+    /// `S`, so configuration shared across workers, such as a library's, and
+    /// a worker's own can be resolved by one provider with the same options:
+    ///
+    /// ```rust,ignore
+    /// let figment = Figment::new()
+    ///     .merge(CloudflareWorkersBindings::from_structs::<(CommonConfig, AppConfig)>(&env));
+    /// let common: CommonConfig = figment.extract_lossy()?;
+    /// let app: AppConfig = figment.extract_lossy()?;
+    /// ```
+    ///
+    /// A field the structs share is read once. Each struct is then extracted
+    /// from the same figment, ignoring the fields of the others, so none of
+    /// them may deny unknown fields.
+    #[must_use]
+    pub fn from_structs<S: ConfigStructs + 'static>(source: &'a dyn BindingSource) -> Self {
+        Self::with_discovered(
+            Source::Borrowed(source),
+            try_cached_fields::<Union<S>, _>(|| {
+                S::field_names().map(|names| uppercased_fields(&names))
+            }),
+        )
+    }
+
+    /// Like [`from_struct`](Self::from_struct), but taking the field names
+    /// from the [`FieldNames`] impl of `T` rather than discovering them by a
+    /// dummy deserialisation, so that types with hand-written `Deserialize`
+    /// impls are supported too.
+    #[must_use]
+    pub fn from_field_names<T: FieldNames + 'static>(source: &'a dyn BindingSource) -> Self {
+        Self::with_fields(
+            Source::Borrowed(source),
+            Cow::Borrowed(cached_fields::<Named<T>>(|| {
+                uppercased_fields(T::field_names())
+            })),
+        )
+    }
+
+    /// Create a provider that reads the fields of `T` from the bindings it
+    /// declares, typically with `#[derive(CloudflareConfig)]`, instead of
+    /// discovering them at runtime. Fields declared secret are treated as if
+    /// passed to [`secret`](Self::secret).
+    #[must_use]
+    pub fn from_config<T: CloudflareConfig + 'static>(source: &'a dyn BindingSource) -> Self {
+        let mut provider = Self::with_fields(
+            Source::Borrowed(source),
+            Cow::Borrowed(cached_fields::<Declared<T>>(|| {
+                T::bindings()
+                    .iter()
+                    .map(|binding| Field {
+                        name: Cow::Borrowed(binding.field()),
+                        binding: Cow::Borrowed(binding.binding()),
+                        accessor: if binding.is_secret() {
+                            Some(BindingKind::Secret)
+                        } else if binding.is_var() {
+                            Some(BindingKind::Var)
+                        } else {
+                            None
+                        },
+                        parse: false,
+                    })
+                    .collect()
+            })),
+        );
+        provider.secrets.extend(
+            T::bindings()
+                .iter()
+                .filter(|binding| binding.is_secret())
+                .map(|binding| binding.field().to_owned()),
+        );
+        provider
+    }
+
+    /// Create a provider that resolves each field of `T` against several
+    /// sources in order, taking the value from the first source that binds
+    /// it, e.g. the [`worker::Env`] followed by a mock overlay in a preview
+    /// environment. The layered lookup happens inside one provider, so
+    /// errors and metadata name a single source.
+    ///
+    /// This is [`from_struct`](Self::from_struct) with the remaining sources
+    /// added as [`fallback`](Self::fallback)s.
+    ///
+    /// ```rust,ignore
+    /// let overlay = MockBindings::new().with_var("API_BASE_URL", "https://staging.example.com");
+    /// let provider = CloudflareWorkersBindings::from_sources::<Config>(&[&overlay, &env]);
+    /// ```
+    #[must_use]
+    pub fn from_sources<T: DeserializeOwned + 'static>(sources: &[&'a dyn BindingSource]) -> Self {
+        let Some((primary, fallbacks)) = sources.split_first() else {
+            return Self::from_struct::<T>(&NoBindings);
+        };
+        let mut provider = Self::from_struct::<T>(*primary);
+        provider.fallbacks.extend_from_slice(fallbacks);
+        provider
+    }
+
+    /// Create a provider that reads every var and secret the source can
+    /// enumerate (see [`BindingSource::names`]), without a target struct.
+    ///
+    /// Each binding is emitted under its lowercased name (e.g. `API_KEY` as
+    /// `api_key`), so the result can be extracted into a
+    /// `HashMap<String, String>`, or into a partially known configuration
+    /// type that ignores unknown fields. On a [`worker::Env`], bindings of
+    /// other kinds (KV namespaces, Durable Objects, …) are not listed; vars
+    /// and secrets cannot be told apart there, so both are emitted.
+    ///
+    /// ```rust,ignore
+    /// let bindings: HashMap<String, String> =
+    ///     Figment::from(CloudflareWorkersBindings::all(&env)).extract()?;
+    /// ```
+    #[must_use]
+    pub fn all(source: &'a dyn BindingSource) -> Self {
+        Self::with_fields(Source::Borrowed(source), Cow::Owned(all_fields(source)))
+    }
+
+    /// A provider for the discovered fields of `T`.
+    fn discovered<T: DeserializeOwned + 'static>(source: Source<'a>) -> Self {
+        Self::with_discovered(source, struct_fields::<T>())
+    }
+
+    /// A provider for discovered `fields`, failing to resolve if they could
+    /// not be discovered.
+    fn with_discovered(source: Source<'a>, fields: Result<&'static [Field], Error>) -> Self {
+        match fields {
+            Ok(fields) => Self::with_fields(source, Cow::Borrowed(fields)),
+            Err(error) => Self {
+                undiscovered: Some(error),
+                ..Self::with_fields(source, Cow::Borrowed(&[]))
+            },
+        }
+    }
+
+    fn with_fields(source: Source<'a>, fields: Cow<'static, [Field]>) -> Self {
         Self {
-            env,
-            fields: field_names::<T>(),
+            source,
+            fallbacks: Vec::new(),
+            fields,
+            undiscovered: None,
+            defaults: Dict::new(),
+            required: BTreeSet::new(),
+            require_all: false,
+            only: None,
+            skipped: BTreeSet::new(),
+            focus: None,
+            deferred: BTreeSet::new(),
+            accumulated: BTreeSet::new(),
+            path: None,
             profile: Profile::Default,
+            lookup_order: LookupOrder::default(),
+            binding_cases: Vec::new(),
+            aliases: BTreeMap::new(),
+            secrets: BTreeSet::new(),
+            show_read_values: false,
+            #[cfg(feature = "encryption")]
+            decryption_key: None,
+            ranges: Vec::new(),
+            #[cfg(feature = "regex")]
+            patterns: Vec::new(),
+            reads: RefCell::default(),
+            metrics: Cell::default(),
+            #[cfg(feature = "timing")]
+            timings: Cell::default(),
+            #[cfg(feature = "console-debug")]
+            console_debug: None,
+            #[cfg(feature = "console-debug")]
+            console_sink: write_to_console,
+            #[cfg(feature = "audit")]
+            audit: None,
+            #[cfg(feature = "analytics-engine")]
+            analytics: None,
+            #[cfg(any(feature = "analytics-engine", feature = "audit"))]
+            missing: RefCell::default(),
         }
     }
 
@@ -94,6 +783,1370 @@ impl<'a> CloudflareWorkersBindings<'a> {
         self.profile = profile.into();
         self
     }
+
+    /// Apply `configure` only if the provider emits into `profile`, so that
+    /// bindings are required, or looked up at all, only where they are
+    /// meant to be bound:
+    ///
+    /// ```rust,ignore
+    /// let provider = CloudflareWorkersBindings::from_struct::<Config>(&env)
+    ///     .profile(environment_name)
+    ///     .when_profile("prod", |provider| provider.require("sentry_dsn"))
+    ///     .unless_profile("prod", |provider| provider.skip(&["sentry_dsn"]));
+    /// ```
+    ///
+    /// The profile is the one set with [`profile`](Self::profile) so far, so
+    /// set it first. Profile names are compared case-insensitively.
+    #[must_use]
+    pub fn when_profile(
+        self,
+        profile: impl Into<Profile>,
+        configure: impl FnOnce(Self) -> Self,
+    ) -> Self {
+        if self.profile == profile.into() {
+            configure(self)
+        } else {
+            self
+        }
+    }
+
+    /// Apply `configure` only if the provider emits into a profile other
+    /// than `profile`; see [`when_profile`](Self::when_profile).
+    #[must_use]
+    pub fn unless_profile(
+        self,
+        profile: impl Into<Profile>,
+        configure: impl FnOnce(Self) -> Self,
+    ) -> Self {
+        if self.profile == profile.into() {
+            self
+        } else {
+            configure(self)
+        }
+    }
+
+    /// Emit all values under the dotted key `path` (e.g. `database` or
+    /// `services.database`) instead of at the top level, so the provider can
+    /// populate one section of a larger configuration whose other sections
+    /// come from other providers.
+    ///
+    /// Field discovery and binding names are unaffected, so `T` in
+    /// [`from_struct`](Self::from_struct) should be the type of the section:
+    ///
+    /// ```rust,ignore
+    /// let config: AppConfig = Figment::from(Toml::file("app.toml"))
+    ///     .merge(CloudflareWorkersBindings::from_struct::<DatabaseConfig>(&env).at("database"))
+    ///     .extract()?;
+    /// ```
+    #[must_use]
+    pub fn at(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Set the order in which vars and secrets are consulted.
+    #[must_use]
+    pub fn lookup_order(mut self, lookup_order: LookupOrder) -> Self {
+        self.lookup_order = lookup_order;
+        self
+    }
+
+    /// Look each binding up under each of `cases` in order, rather than
+    /// under its binding name alone, so bindings deployed with inconsistent
+    /// casing are still found:
+    ///
+    /// ```rust,ignore
+    /// use figment2_cloudflare_workers::BindingCase;
+    ///
+    /// let provider = CloudflareWorkersBindings::from_struct::<Config>(&env)
+    ///     .binding_cases(&[BindingCase::Upper, BindingCase::Exact, BindingCase::Lower]);
+    /// ```
+    ///
+    /// Each name is tried in every source before the next one. A field
+    /// found under a name other than its binding name, or one of its
+    /// [aliases](Self::alias), is logged as a
+    /// warning with the `log` feature, and listed in `trace_json` with the
+    /// `trace` feature, so the misnamed binding can be fixed.
+    #[must_use]
+    pub fn binding_cases(mut self, cases: &[BindingCase]) -> Self {
+        self.binding_cases = cases.to_vec();
+        self
+    }
+
+    /// Read `field` from the first of `bindings` that is bound, instead of
+    /// from its binding name, so deployments naming it differently are all
+    /// served by one configuration type:
+    ///
+    /// ```rust,ignore
+    /// let provider = CloudflareWorkersBindings::from_struct::<Config>(&env)
+    ///     .alias("api_key", &["API_KEY", "SERVICE_API_KEY"]);
+    /// ```
+    ///
+    /// The binding name is not tried unless it is listed. Calling `alias`
+    /// again for the same field replaces its list.
+    #[must_use]
+    pub fn alias(mut self, field: impl Into<String>, bindings: &[&str]) -> Self {
+        self.aliases.insert(
+            field.into(),
+            bindings
+                .iter()
+                .map(|binding| (*binding).to_owned())
+                .collect(),
+        );
+        self
+    }
+
+    /// The bindings `field` is read from, in order: its
+    /// [aliases](Self::alias), or else its binding name.
+    fn declared_bindings<'f>(&'f self, field: &'f Field) -> Vec<&'f str> {
+        match self.aliases.get(field.name.as_ref()) {
+            Some(aliases) => aliases.iter().map(String::as_str).collect(),
+            None => vec![&*field.binding],
+        }
+    }
+
+    /// The names to look `field` up under, in order: each of its declared
+    /// bindings under each of the [`binding_cases`](Self::binding_cases).
+    fn binding_names<'f>(&'f self, field: &'f Field) -> Vec<Cow<'f, str>> {
+        let declared = self.declared_bindings(field);
+        if self.binding_cases.is_empty() {
+            return declared.into_iter().map(Cow::Borrowed).collect();
+        }
+        let mut names: Vec<Cow<'f, str>> = Vec::new();
+        for binding in declared {
+            for case in &self.binding_cases {
+                let name = match case {
+                    // A binding derived from the field's name is the name as
+                    // written; a declared one is as declared.
+                    BindingCase::Exact
+                        if binding == field.binding && binding == field.name.to_uppercase() =>
+                    {
+                        Cow::Borrowed(&*field.name)
+                    }
+                    BindingCase::Exact => Cow::Borrowed(binding),
+                    BindingCase::Upper => Cow::Owned(binding.to_uppercase()),
+                    BindingCase::Lower => Cow::Owned(binding.to_lowercase()),
+                };
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+
+    /// Look `field` up under each of its [names](Self::binding_names),
+    /// returning the name it was found under along with the binding.
+    fn lookup_field<'f>(
+        &'f self,
+        field: &'f Field,
+    ) -> Result<Option<(Found, Cow<'f, str>)>, Error> {
+        for name in self.binding_names(field) {
+            let found = match field.accessor {
+                None => self.lookup(&name),
+                Some(kind) => self.lookup_as(&name, kind)?,
+            };
+            if let Some(found) = found {
+                return Ok(Some((found, name)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Emit `value` for `field` when its binding is missing, instead of
+    /// requiring a separate `Serialized::defaults` provider.
+    ///
+    /// ```rust,ignore
+    /// let provider = CloudflareWorkersBindings::from_struct::<Config>(&env)
+    ///     .default("max_retries", 3)
+    ///     .default("log_level", "info");
+    /// ```
+    #[must_use]
+    pub fn default(mut self, field: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.defaults.insert(field.into(), value.into());
+        self
+    }
+
+    /// Fail if `field` has no binding, even if its type is an `Option`, so
+    /// that fields optional in the type can be mandatory in production.
+    ///
+    /// A [`default`](Self::default) does not satisfy the requirement, but a
+    /// [`fallback`](Self::fallback) source does.
+    ///
+    /// ```rust,ignore
+    /// let provider = CloudflareWorkersBindings::from_struct::<Config>(&env)
+    ///     .require("api_key");
+    /// ```
+    #[must_use]
+    pub fn require(mut self, field: impl Into<String>) -> Self {
+        self.required.insert(field.into());
+        self
+    }
+
+    /// Fail if any field has no binding; see [`require`](Self::require).
+    #[must_use]
+    pub fn require_all(mut self) -> Self {
+        self.require_all = true;
+        self
+    }
+
+    /// Read `fields` (each from its uppercased name) in place of the fields
+    /// discovered from the target type.
+    ///
+    /// This is the escape hatch for types whose fields cannot be discovered,
+    /// such as `#[serde(untagged)]` enums, for which resolving the provider
+    /// otherwise fails:
+    ///
+    /// ```rust,ignore
+    /// #[derive(Deserialize)]
+    /// #[serde(untagged)]
+    /// enum Storage {
+    ///     Bucket { bucket_name: String },
+    ///     Database { database_url: String },
+    /// }
+    ///
+    /// let provider = CloudflareWorkersBindings::from_struct::<Storage>(&env)
+    ///     .field_names(&["bucket_name", "database_url"]);
+    /// ```
+    #[must_use]
+    pub fn field_names(mut self, fields: &[&str]) -> Self {
+        self.fields = Cow::Owned(
+            fields
+                .iter()
+                .map(|name| Field {
+                    name: Cow::Owned((*name).to_owned()),
+                    binding: Cow::Owned(name.to_uppercase()),
+                    accessor: None,
+                    parse: false,
+                })
+                .collect(),
+        );
+        self.undiscovered = None;
+        self
+    }
+
+    /// Resolve only `fields`, skipping the lookups for every other field, as
+    /// an allow-list fixed up front. To look up no more than the fields
+    /// actually extracted, use [`lazy`](Self::lazy) instead.
+    ///
+    /// ```rust,ignore
+    /// let provider = CloudflareWorkersBindings::from_struct::<Config>(&env)
+    ///     .only(&["database_url", "pool_size"]);
+    /// ```
+    ///
+    /// Calling `only` again adds to the selection. Fields left out are not
+    /// checked by [`require`](Self::require), though their
+    /// [defaults](Self::default) are still emitted.
+    ///
+    /// [`Figment::extract_inner`]: figment2::Figment::extract_inner
+    #[must_use]
+    pub fn only(mut self, fields: &[&str]) -> Self {
+        self.only
+            .get_or_insert_with(BTreeSet::new)
+            .extend(fields.iter().map(|field| (*field).to_owned()));
+        self
+    }
+
+    /// Never look up `fields`, as if they were left out of
+    /// [`only`](Self::only), such as a field only bound in some profiles;
+    /// see [`when_profile`](Self::when_profile).
+    ///
+    /// Skipped fields are not checked by [`require`](Self::require), though
+    /// their [defaults](Self::default) are still emitted.
+    #[must_use]
+    pub fn skip(mut self, fields: &[&str]) -> Self {
+        self.skipped
+            .extend(fields.iter().map(|field| (*field).to_owned()));
+        self
+    }
+
+    /// Resolve only the fields under the dotted key `path` of the figment,
+    /// taking the [`at`](Self::at) path into account, so a subsystem can
+    /// extract its own section without the lookups for every other one:
+    ///
+    /// ```rust,ignore
+    /// let database: DatabaseConfig = Figment::new()
+    ///     .merge(
+    ///         CloudflareWorkersBindings::from_struct::<DatabaseConfig>(&env)
+    ///             .at("database")
+    ///             .focus("database"),
+    ///     )
+    ///     .merge(
+    ///         CloudflareWorkersBindings::from_struct::<CacheConfig>(&env)
+    ///             .at("cache")
+    ///             .focus("database"),
+    ///     )
+    ///     .extract_inner("database")?;
+    /// ```
+    ///
+    /// Above, the cache provider resolves nothing. A path into the section,
+    /// such as `database.url` there, resolves that field alone, like
+    /// [`only`](Self::only), which it combines with; fields left out are
+    /// not checked by [`require`](Self::require) either.
+    #[must_use]
+    pub fn focus(mut self, path: impl Into<String>) -> Self {
+        self.focus = Some(path.into());
+        self
+    }
+
+    /// Defer merging the provider over `figment` until a value is
+    /// extracted, so that each extraction looks up the bindings under the
+    /// key extracted alone; see [`Lazy`].
+    #[must_use]
+    pub fn lazy(self, figment: Figment) -> Lazy<'a> {
+        Lazy::new(figment, self)
+    }
+
+    /// Whether `field` is selected by [`only`](Self::only),
+    /// [`skip`](Self::skip) and [`focus`](Self::focus).
+    fn selects(&self, field: &str) -> bool {
+        let focused = self.focus.as_deref().is_none_or(|focus| {
+            // The field is on the focused path if one of the two paths is a
+            // prefix of the other.
+            let path = self
+                .path
+                .iter()
+                .flat_map(|path| path.split('.'))
+                .chain(std::iter::once(field));
+            focus
+                .split('.')
+                .filter(|segment| !segment.is_empty())
+                .zip(path)
+                .all(|(focused, segment)| focused == segment)
+        });
+        focused
+            && !self.skipped.contains(field)
+            && self.only.as_ref().is_none_or(|only| only.contains(field))
+    }
+
+    /// Give `fields` lower precedence than the providers already in the
+    /// figment, while the other fields keep overriding them.
+    ///
+    /// Deferred fields are left out of this provider's own data and emitted
+    /// by its [`deferred`](Self::deferred) view instead, which should be
+    /// joined rather than merged; [`FigmentExt::merge_bindings`] does both.
+    /// This lets secrets come from Cloudflare while tunables such as a log
+    /// level defer to a configuration file:
+    ///
+    /// ```rust,ignore
+    /// use figment2_cloudflare_workers::FigmentExt;
+    ///
+    /// let config: Config = Figment::from(Toml::file("config.toml"))
+    ///     .merge_bindings(
+    ///         &CloudflareWorkersBindings::from_struct::<Config>(&env).defer(&["log_level"]),
+    ///     )
+    ///     .extract()?;
+    /// ```
+    #[must_use]
+    pub fn defer(mut self, fields: &[&str]) -> Self {
+        self.deferred
+            .extend(fields.iter().map(|field| (*field).to_owned()));
+        self
+    }
+
+    /// A provider emitting only the fields passed to [`defer`](Self::defer).
+    #[must_use]
+    pub fn deferred(&self) -> Deferred<'_, 'a> {
+        Deferred::new(self)
+    }
+
+    /// Append the lists of `fields` to those of the providers already in
+    /// the figment, rather than replacing them, so that a list such as
+    /// `allowed_origins` can gather entries from a file, vars and KV alike.
+    ///
+    /// An accumulated field's binding is read as a comma-separated list,
+    /// with surrounding whitespace trimmed and empty items dropped, and is
+    /// emitted by this provider's [`accumulated`](Self::accumulated) view
+    /// instead of its own data; the view should be admerged, which
+    /// [`FigmentExt::merge_bindings`] does:
+    ///
+    /// ```rust,ignore
+    /// let config: Config = Figment::from(Json::file("config.json"))
+    ///     .merge_bindings(
+    ///         &CloudflareWorkersBindings::from_struct::<Config>(&env)
+    ///             .accumulate(&["allowed_origins"]),
+    ///     )
+    ///     .merge_bindings(
+    ///         &CloudflareWorkersBindings::from_struct::<Config>(&kv)
+    ///             .accumulate(&["allowed_origins"]),
+    ///     )
+    ///     .extract_lossy()?;
+    /// ```
+    ///
+    /// A field that is also [deferred](Self::defer) is deferred instead.
+    #[must_use]
+    pub fn accumulate(mut self, fields: &[&str]) -> Self {
+        self.accumulated
+            .extend(fields.iter().map(|field| (*field).to_owned()));
+        self
+    }
+
+    /// A provider emitting only the fields passed to
+    /// [`accumulate`](Self::accumulate).
+    #[must_use]
+    pub fn accumulated(&self) -> Accumulated<'_, 'a> {
+        Accumulated::new(self)
+    }
+
+    /// How `field` is merged into a figment.
+    fn merge_mode_of(&self, field: &str) -> MergeMode {
+        if self.deferred.contains(field) {
+            MergeMode::Deferred
+        } else if self.accumulated.contains(field) {
+            MergeMode::Accumulated
+        } else {
+            MergeMode::Merged
+        }
+    }
+
+    /// Read fields that have no binding in the primary source from
+    /// `fallback` instead. Fallbacks are consulted in the order they are
+    /// added.
+    #[must_use]
+    pub fn fallback(mut self, fallback: &'a dyn BindingSource) -> Self {
+        self.fallbacks.push(fallback);
+        self
+    }
+
+    /// Read fields that have no binding from the process environment, via
+    /// [`ProcessEnv`].
+    ///
+    /// This lets the same configuration code run in `wrangler dev`, in
+    /// native unit tests and in production: on `wasm32-unknown-unknown`
+    /// there is no process environment and the fallback never matches, while
+    /// natively compiled code (or a WASI shim providing an environment) picks
+    /// up variables such as `DATABASE_URL` from the shell.
+    #[must_use]
+    pub fn fallback_to_process_env(self) -> Self {
+        self.fallback(&ProcessEnv)
+    }
+
+    /// Treat `field` as secret wherever resolved values are masked, such as
+    /// in a [`Snapshot`].
+    ///
+    /// Values read through the secret accessor are treated as secret too,
+    /// but only sources that look bindings up one at a time, such as
+    /// `MockBindings`, tell which accessor answered. A [`worker::Env`]
+    /// reads every binding in one pass, and the Workers runtime answers var
+    /// lookups for secrets anyway, so under the default
+    /// [`lookup_order`](Self::lookup_order) its secrets are read as vars and
+    /// stay unmasked unless declared here.
+    #[must_use]
+    pub fn secret(mut self, field: impl Into<String>) -> Self {
+        self.secrets.insert(field.into());
+        self
+    }
+
+    /// Show the values read from vars when the provider is logged through
+    /// its `Debug` or `Serialize` implementation, rather than only whether
+    /// each binding is bound and the length of its value.
+    ///
+    /// The lookups of a live [`worker::Env`] cannot tell secrets from vars,
+    /// so only opt in when every secret-backed field is declared with
+    /// [`secret`](Self::secret); their values stay masked.
+    #[must_use]
+    pub fn show_read_values(mut self) -> Self {
+        self.show_read_values = true;
+        self
+    }
+
+    /// Fail resolution if the value bound for `field` is not a number
+    /// within `range`, so an operator's typo such as `5000` connections is
+    /// caught at extraction rather than by the origin:
+    ///
+    /// ```rust,ignore
+    /// let provider = CloudflareWorkersBindings::from_struct::<Config>(&env)
+    ///     .range("max_connections", 1..=500)
+    ///     .range("sample_rate", 0.0..=1.0);
+    /// ```
+    ///
+    /// Every field out of range is reported, in one error listing each with
+    /// its binding and value (masked for secret fields). An unbound field is
+    /// not checked.
+    #[must_use]
+    pub fn range<N: Into<f64> + Copy>(
+        mut self,
+        field: impl Into<String>,
+        range: impl RangeBounds<N>,
+    ) -> Self {
+        let bound = |bound: Bound<&N>| bound.map(|&value| value.into());
+        self.ranges.push((
+            field.into(),
+            (bound(range.start_bound()), bound(range.end_bound())),
+        ));
+        self
+    }
+
+    /// Fail, listing every violation, if any of `resolutions` lies outside
+    /// its field's [ranges](Self::range).
+    fn check_ranges(&self, resolutions: &[Resolution]) -> Result<(), Error> {
+        let mut violations = Vec::new();
+        for (field, bounds) in &self.ranges {
+            let Some(resolution) = resolutions
+                .iter()
+                .find(|resolution| resolution.field == field.as_str())
+            else {
+                continue;
+            };
+            let value = resolution.value.trim();
+            if value
+                .parse::<f64>()
+                .is_ok_and(|number| bounds.contains(&number))
+            {
+                continue;
+            }
+            let binding = &resolution.binding;
+            let value = if resolution.secret {
+                "a secret value".to_owned()
+            } else {
+                format!("`{value}`")
+            };
+            violations.push(format!(
+                "binding `{binding}` for field `{field}` is {value}, but must be a number {}",
+                describe_bounds(bounds)
+            ));
+        }
+        // Errors chained later are displayed first.
+        let mut violations = violations.into_iter().rev().map(Error::from);
+        match violations.next() {
+            None => Ok(()),
+            Some(first) => Err(violations.fold(first, Error::chain)),
+        }
+    }
+
+    /// Fail resolution if the value bound for `field` does not match the
+    /// regular expression `pattern`, so malformed values are caught at
+    /// extraction with an error naming the binding:
+    ///
+    /// ```rust,ignore
+    /// let provider = CloudflareWorkersBindings::from_struct::<Config>(&env)
+    ///     .matches("api_base_url", r"^https://")
+    ///     .matches("region", r"^[a-z]{2}-[a-z]+$");
+    /// ```
+    ///
+    /// The value is matched after decryption, and not shown in the error. A
+    /// field may be given several patterns, all of which must match; an
+    /// unbound field is not checked. An invalid pattern fails resolution
+    /// too. Patterns use `regex-lite` syntax, which lacks Unicode classes
+    /// such as `\p{L}` but keeps the bundle small.
+    #[cfg(feature = "regex")]
+    #[must_use]
+    pub fn matches(mut self, field: impl Into<String>, pattern: &str) -> Self {
+        let field = field.into();
+        let regex = regex_lite::Regex::new(pattern).map_err(|error| {
+            Error::from(format!(
+                "invalid pattern `{pattern}` for field `{field}`: {error}"
+            ))
+        });
+        self.patterns.push((field, regex));
+        self
+    }
+
+    /// Fail if the `value` bound as `binding` for `field` does not match one
+    /// of the field's [patterns](Self::matches).
+    #[cfg(feature = "regex")]
+    fn check_patterns(&self, field: &str, binding: &str, value: &str) -> Result<(), Error> {
+        for (_, regex) in self.patterns.iter().filter(|(name, _)| name == field) {
+            let regex = regex.as_ref().map_err(Clone::clone)?;
+            if !regex.is_match(value) {
+                return Err(Error::from(format!(
+                    "binding `{binding}` for field `{field}` does not match `{}`",
+                    regex.as_str()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Decrypt values carrying the `enc:v1:` prefix using the AES-256-GCM key
+    /// held by the `key_binding` secret as standard base64. Values without
+    /// the prefix are emitted unchanged; see [`encrypt_value`] for producing
+    /// encrypted values.
+    ///
+    /// The key is read through the secret accessor of the primary source
+    /// only, so that no fallback, such as a KV namespace anyone with write
+    /// access can fill, and no var of the same name can supply it.
+    /// Resolving fails if it is not bound there. Each value decrypts only
+    /// under the binding it was encrypted for.
+    #[cfg(feature = "encryption")]
+    #[must_use]
+    pub fn decrypt_with(mut self, key_binding: impl Into<String>) -> Self {
+        self.decryption_key = Some(key_binding.into());
+        self
+    }
+
+    /// Resolve every binding now and capture the result as an owned
+    /// [`Snapshot`], which can be serialised (with secrets masked) and
+    /// replayed later as a provider.
+    ///
+    /// # Errors
+    ///
+    /// Fails if resolution fails, e.g. because an encrypted value cannot be
+    /// decrypted.
+    pub fn snapshot(&self) -> Result<Snapshot, Error> {
+        Ok(self.snapshot_of(self.resolve()?))
+    }
+
+    fn snapshot_of(&self, resolutions: Vec<Resolution>) -> Snapshot {
+        let secrets = resolutions
+            .iter()
+            .filter(|resolution| resolution.secret)
+            .map(|resolution| resolution.field.to_string())
+            .collect();
+        let values = self.values(resolutions);
+        Snapshot::new(self.profile.clone(), self.path.clone(), values, secrets)
+    }
+
+    /// Resolve every binding now and compute a stable fingerprint of the
+    /// result; see [`Snapshot::fingerprint`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if resolution fails.
+    #[cfg(feature = "fingerprint")]
+    pub fn fingerprint(&self, salt: &[u8]) -> Result<String, Error> {
+        self.snapshot().map(|snapshot| snapshot.fingerprint(salt))
+    }
+
+    /// Report how each field resolves: its binding, the source and accessor
+    /// it was found through, and its value, masked for secret fields. Unlike
+    /// [`snapshot`](Self::snapshot), the report is complete even when
+    /// resolution fails, and records the error instead; see
+    /// `diagnostics_response` for serving it from a worker.
+    #[cfg(feature = "diagnostics")]
+    #[must_use]
+    pub fn diagnostics(&self) -> Diagnostics {
+        let error = self.resolve().err().map(|error| error.to_string());
+        let fields = self
+            .fields
+            .iter()
+            .filter(|field| self.selects(&field.name))
+            .map(|field| {
+                let found = self
+                    .lookup_field(field)
+                    .ok()
+                    .flatten()
+                    .map(|(found, _)| found);
+                FieldDiagnostics::new(
+                    field.name.to_string(),
+                    field.binding.to_string(),
+                    found.map(|found| {
+                        let secret = found.kind == BindingKind::Secret
+                            || self.secrets.contains(field.name.as_ref());
+                        (
+                            source_label(found.source),
+                            found.kind.accessor(),
+                            if secret {
+                                REDACTED.to_owned()
+                            } else {
+                                found.value
+                            },
+                        )
+                    }),
+                )
+            })
+            .collect();
+        Diagnostics::new(fields, error)
+    }
+
+    /// A JSON trace of the full resolution, for attaching to bug reports and
+    /// for tooling that visualises the provider's layers: for each field,
+    /// every source and accessor tried, in order up to the first hit, and
+    /// the layer that won. Layers are listed highest precedence first.
+    ///
+    /// ```json
+    /// {
+    ///   "profile": "default",
+    ///   "path": null,
+    ///   "lookup_order": "var_then_secret",
+    ///   "layers": ["primary", "fallback 1", "default"],
+    ///   "fields": [
+    ///     {
+    ///       "field": "api_key",
+    ///       "binding": "API_KEY",
+    ///       "required": true,
+    ///       "deferred": false,
+    ///       "candidates": [
+    ///         { "source": "primary", "accessor": "var", "hit": false },
+    ///         { "source": "primary", "accessor": "secret", "hit": false },
+    ///         { "source": "fallback 1", "accessor": "var", "hit": true }
+    ///       ],
+    ///       "winner": { "source": "fallback 1", "accessor": "var" }
+    ///     }
+    ///   ],
+    ///   "error": null
+    /// }
+    /// ```
+    ///
+    /// The trace names bindings, never their values, so it is safe to share.
+    #[cfg(feature = "trace")]
+    #[must_use]
+    pub fn trace_json(&self) -> String {
+        let error = self.resolve().err().map(|error| error.to_string());
+        let (order, lookup_order) = match self.lookup_order {
+            LookupOrder::VarThenSecret => {
+                ([BindingKind::Var, BindingKind::Secret], "var_then_secret")
+            }
+            LookupOrder::SecretThenVar => {
+                ([BindingKind::Secret, BindingKind::Var], "secret_then_var")
+            }
+        };
+        let mut layers: Vec<String> = (0..self.sources().count()).map(source_label).collect();
+        layers.push("default".to_owned());
+        let fields = self
+            .fields
+            .iter()
+            .filter(|field| self.selects(&field.name))
+            .map(|field| {
+                let kinds = field
+                    .accessor
+                    .as_ref()
+                    .map_or(&order[..], std::slice::from_ref);
+                let mut candidates = Vec::new();
+                let mut winner = None;
+                'names: for name in self.binding_names(field) {
+                    for (index, source) in self.sources().enumerate() {
+                        for &kind in kinds {
+                            let hit = self
+                                .read(index, source, kind, &name, field.accessor.is_none())
+                                .is_some();
+                            candidates.push(Candidate {
+                                binding: (name != field.binding).then(|| name.to_string()),
+                                source: source_label(index),
+                                accessor: kind.accessor(),
+                                hit,
+                            });
+                            if hit {
+                                winner = Some(Winner {
+                                    source: source_label(index),
+                                    accessor: Some(kind.accessor()),
+                                });
+                                break 'names;
+                            }
+                        }
+                    }
+                }
+                let winner = winner.or_else(|| {
+                    self.defaults
+                        .contains_key(field.name.as_ref())
+                        .then(|| Winner {
+                            source: "default".to_owned(),
+                            accessor: None,
+                        })
+                });
+                FieldTrace {
+                    field: field.name.to_string(),
+                    binding: field.binding.to_string(),
+                    required: self.require_all || self.required.contains(field.name.as_ref()),
+                    deferred: self.deferred.contains(field.name.as_ref()),
+                    candidates,
+                    winner,
+                }
+            })
+            .collect();
+        let trace = ResolutionTrace {
+            profile: self.profile.as_str().as_str().to_lowercase(),
+            path: self.path.clone(),
+            lookup_order,
+            layers,
+            fields,
+            error,
+        };
+        // Plain structs with string keys always serialise.
+        serde_json::to_string_pretty(&trace).unwrap_or_default()
+    }
+
+    /// The counters of what this provider's resolutions have found so far.
+    ///
+    /// Merge the provider by reference (`figment.merge(&provider)`) to read
+    /// them after extraction.
+    #[must_use]
+    pub fn metrics(&self) -> ResolutionMetrics {
+        self.metrics.get()
+    }
+
+    /// Report an [`AuditEvent`] to the sink of `audit`, by default the
+    /// console, whenever the provider is merged into a figment:
+    /// `config.loaded` with the number of fields resolved and, if requested,
+    /// a fingerprint, or `config.failed` with the missing required bindings.
+    #[cfg(feature = "audit")]
+    #[must_use]
+    pub fn audit(mut self, audit: Audit) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Write a [`FailureAnalytics`] data point whenever the provider fails to
+    /// resolve as it is merged into a figment, e.g. for missing required
+    /// bindings. Failing to write the data point does not fail the merge.
+    #[cfg(feature = "analytics-engine")]
+    #[must_use]
+    pub fn analytics(mut self, analytics: FailureAnalytics) -> Self {
+        self.analytics = Some(analytics);
+        self
+    }
+
+    /// Print the [`diagnostics`](Self::diagnostics) table to the console
+    /// whenever the provider is merged into a figment while the var `binding`
+    /// is set, e.g. to `1`, so [`wrangler tail`] shows what it resolved.
+    ///
+    /// ```rust,ignore
+    /// let provider = CloudflareWorkersBindings::from_struct::<Config>(&env).console_debug("CONFIG_DEBUG");
+    /// ```
+    ///
+    /// Values are masked as in the report. The var is read from the primary
+    /// source alone; an empty value, `0`, `false`, `no` and `off` leave
+    /// printing disabled.
+    ///
+    /// [`wrangler tail`]: https://developers.cloudflare.com/workers/observability/logs/real-time-logs/
+    #[cfg(feature = "console-debug")]
+    #[must_use]
+    pub fn console_debug(mut self, binding: impl Into<String>) -> Self {
+        self.console_debug = Some(binding.into());
+        self
+    }
+
+    /// Write the [`console_debug`](Self::console_debug) reports to `sink`
+    /// rather than to the console, or to standard error off wasm32.
+    #[cfg(feature = "console-debug")]
+    #[must_use]
+    pub fn console_sink(mut self, sink: fn(&str)) -> Self {
+        self.console_sink = sink;
+        self
+    }
+
+    /// Print the diagnostics table if the debug var is set.
+    #[cfg(feature = "console-debug")]
+    fn print_console_debug(&self) {
+        let Some(binding) = &self.console_debug else {
+            return;
+        };
+        let primary = self.sources().next().expect("a primary source");
+        let enabled = primary.var(binding).ok().flatten().is_some_and(|value| {
+            let value = value.trim().to_ascii_lowercase();
+            !["", "0", "false", "no", "off"].contains(&value.as_str())
+        });
+        if !enabled {
+            return;
+        }
+        let table = self.diagnostics().to_string();
+        let report = format!("{binding} is set; resolved bindings:\n{}", table.trim_end());
+        (self.console_sink)(&report);
+    }
+
+    /// The time this provider has spent loading configuration so far.
+    ///
+    /// Merge the provider by reference to read them after extraction, and
+    /// wrap the extraction in [`time_extraction`](Self::time_extraction) to
+    /// measure it too.
+    #[cfg(feature = "timing")]
+    #[must_use]
+    pub fn timings(&self) -> LoadTimings {
+        let fetches_ms = self.sources().map(BindingSource::fetch_ms).sum();
+        self.timings.get().with_fetches(fetches_ms)
+    }
+
+    /// Run `extract`, typically merging this provider into a figment by
+    /// reference and extracting from it, and record the time it took beyond
+    /// looking bindings up as [`LoadTimings::extraction_ms`].
+    #[cfg(feature = "timing")]
+    pub fn time_extraction<R>(&self, extract: impl FnOnce() -> R) -> R {
+        let started = clock::now_ms();
+        let lookups_ms = self.timings.get().lookups_ms();
+        let extracted = extract();
+        let mut timings = self.timings.get();
+        let elapsed_ms = clock::now_ms() - started - (timings.lookups_ms() - lookups_ms);
+        timings.record_extraction(elapsed_ms.max(0.0));
+        self.timings.set(timings);
+        extracted
+    }
+
+    /// Add the time since `started` to the lookup timings.
+    #[cfg(feature = "timing")]
+    fn time_lookup(&self, started: f64) {
+        let mut timings = self.timings.get();
+        timings.record_lookup(clock::now_ms() - started);
+        self.timings.set(timings);
+    }
+
+    fn count(&self, record: impl FnOnce(&mut ResolutionMetrics)) {
+        let mut metrics = self.metrics.get();
+        record(&mut metrics);
+        self.metrics.set(metrics);
+    }
+
+    pub(crate) fn resolve(&self) -> Result<Vec<Resolution>, Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("resolve_bindings", fields = self.fields.len()).entered();
+        if let Some(error) = &self.undiscovered {
+            return Err(error.clone());
+        }
+        #[cfg(feature = "encryption")]
+        let mut decryption_key = None;
+
+        let mut resolutions = Vec::new();
+        let mut missing = Vec::new();
+        for field in self.fields.iter() {
+            if !self.selects(&field.name) {
+                continue;
+            }
+            let Some((
+                Found {
+                    value,
+                    kind,
+                    source,
+                },
+                name,
+            )) = self.lookup_field(field)?
+            else {
+                self.count(ResolutionMetrics::record_miss);
+                let required = self.require_all || self.required.contains(field.name.as_ref());
+                #[cfg(feature = "log")]
+                log::debug!(
+                    "binding `{}` for field `{}` is not bound{}",
+                    field.binding,
+                    field.name,
+                    if required { " but required" } else { "" },
+                );
+                if required {
+                    missing.push(field.binding.to_string());
+                }
+                continue;
+            };
+            self.count(|metrics| metrics.record_hit(kind == BindingKind::Secret));
+            #[cfg(feature = "log")]
+            if !self.declared_bindings(field).contains(&&*name) {
+                log::warn!(
+                    "binding `{}` for field `{}` is not bound; found `{name}` instead",
+                    field.binding,
+                    field.name,
+                );
+            }
+            #[cfg(feature = "log")]
+            log_resolution(field, &name, kind, source);
+            #[cfg(feature = "encryption")]
+            let value = match &self.decryption_key {
+                Some(key_binding) if encryption::is_encrypted(&value) => {
+                    if decryption_key.is_none() {
+                        // Only a secret of the primary source may hold the
+                        // key, never a writable fallback or a plain var.
+                        let encoded = self
+                            .primary()
+                            .secret(key_binding)
+                            .map_err(|error| {
+                                Error::from(format!(
+                                    "decryption key binding `{key_binding}`: {error}"
+                                ))
+                            })?
+                            .ok_or_else(|| {
+                                Error::from(format!(
+                                    "decryption key binding `{key_binding}` is not a secret of the primary source"
+                                ))
+                            })?;
+                        decryption_key =
+                            Some(encryption::DecryptionKey::decode(key_binding, &encoded)?);
+                    }
+                    decryption_key
+                        .as_ref()
+                        .expect("key decoded above")
+                        .decrypt(&field.binding, value)?
+                }
+                _ => value,
+            };
+            #[cfg(feature = "regex")]
+            self.check_patterns(&field.name, &name, &value)?;
+
+            resolutions.push(Resolution {
+                secret: kind == BindingKind::Secret || self.secrets.contains(field.name.as_ref()),
+                field: field.name.clone(),
+                value,
+                binding: name.into_owned(),
+                kind,
+                source,
+            });
+        }
+
+        self.check_missing(missing)?;
+        self.check_ranges(&resolutions)?;
+
+        #[cfg(feature = "log")]
+        log::debug!(
+            "resolved {} of {} bindings",
+            resolutions.len(),
+            self.fields.len()
+        );
+        Ok(resolutions)
+    }
+
+    /// Fail if any of the `missing` bindings, or any required name that is
+    /// not a field at all, is required.
+    fn check_missing(&self, mut missing: Vec<String>) -> Result<(), Error> {
+        // Required names that are not fields of the struct can never resolve.
+        missing.extend(
+            self.required
+                .iter()
+                .filter(|name| !self.fields.iter().any(|field| field.name == name.as_str()))
+                .map(|name| name.to_uppercase()),
+        );
+        #[cfg(any(feature = "analytics-engine", feature = "audit"))]
+        self.missing.replace(missing.clone());
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let missing = missing
+            .iter()
+            .map(|binding| format!("`{binding}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        #[cfg(feature = "log")]
+        log::warn!("required bindings are missing: {missing}");
+        Err(Error::from(format!(
+            "required bindings are missing: {missing}"
+        )))
+    }
+
+    /// The values to emit: the resolved fields, plus defaults for fields
+    /// that did not resolve.
+    fn values(&self, resolutions: Vec<Resolution>) -> Dict {
+        let mut values: Dict = resolutions
+            .into_iter()
+            .map(|resolution| self.value_of(resolution))
+            .collect();
+        for (field, default) in &self.defaults {
+            values
+                .entry(field.clone())
+                .or_insert_with(|| default.clone());
+        }
+        values
+    }
+
+    /// The key and value `resolution` is emitted as.
+    fn value_of(&self, resolution: Resolution) -> (String, Value) {
+        let parse = self
+            .fields
+            .iter()
+            .any(|field| field.parse && field.name == resolution.field);
+        let value = if self.accumulated.contains(resolution.field.as_ref()) {
+            resolution
+                .value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(Value::from)
+                .collect::<Vec<_>>()
+                .into()
+        } else if parse {
+            let Ok(value) = resolution.value.parse();
+            value
+        } else {
+            Value::from(resolution.value)
+        };
+        (resolution.field.into_owned(), value)
+    }
+
+    /// Resolve the bindings as [`Provider::data`] does, reporting the
+    /// outcome to the audit, analytics and console, if set up.
+    fn resolve_reported(&self) -> Result<Vec<Resolution>, Error> {
+        let resolutions = self.resolve();
+        #[cfg(feature = "audit")]
+        self.report_audit(&resolutions);
+        #[cfg(feature = "analytics-engine")]
+        self.report_analytics(&resolutions);
+        #[cfg(feature = "console-debug")]
+        self.print_console_debug();
+        resolutions
+    }
+
+    /// Resolve and emit each value on its own, with metadata naming the
+    /// binding, or default, it came from.
+    pub(crate) fn emit_tagged(&self) -> Result<Vec<TaggedValue>, Error> {
+        let resolutions = self.resolve_reported()?;
+        let mut tagged = Vec::new();
+        for (field, default) in &self.defaults {
+            if resolutions
+                .iter()
+                .all(|resolution| resolution.field != field.as_str())
+            {
+                let dict = Dict::from([(field.clone(), default.clone())]);
+                tagged.push(TaggedValue::new(
+                    format!("default for `{field}`"),
+                    self.merge_mode_of(field),
+                    self.profile.collect(nest(self.path.as_deref(), dict)),
+                ));
+            }
+        }
+        for resolution in resolutions {
+            let mut source = format!("{} `{}`", resolution.kind.accessor(), resolution.binding);
+            if resolution.source > 0 {
+                source = format!("{source} ({})", source_label(resolution.source));
+            }
+            let mode = self.merge_mode_of(&resolution.field);
+            let dict = Dict::from([self.value_of(resolution)]);
+            tagged.push(TaggedValue::new(
+                source,
+                mode,
+                self.profile.collect(nest(self.path.as_deref(), dict)),
+            ));
+        }
+        Ok(tagged)
+    }
+
+    /// Resolve and emit the fields merged as `mode`.
+    pub(crate) fn emit(&self, mode: MergeMode) -> Result<Map<Profile, Dict>, Error> {
+        Ok(self.collect(self.resolve()?, mode))
+    }
+
+    /// Emit the fields of `resolutions` merged as `mode`.
+    fn collect(&self, resolutions: Vec<Resolution>, mode: MergeMode) -> Map<Profile, Dict> {
+        let mut dict = self.values(resolutions);
+        dict.retain(|field, _| self.merge_mode_of(field) == mode);
+        self.profile.collect(nest(self.path.as_deref(), dict))
+    }
+
+    /// Report the outcome of a resolution to the [`Audit`], if any.
+    #[cfg(feature = "audit")]
+    fn report_audit(&self, resolutions: &Result<Vec<Resolution>, Error>) {
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        let event = match resolutions {
+            Ok(resolutions) => {
+                #[cfg(feature = "fingerprint")]
+                let fingerprint = audit
+                    .salt()
+                    .map(|salt| self.snapshot_of(resolutions.clone()).fingerprint(salt));
+                #[cfg(not(feature = "fingerprint"))]
+                let fingerprint = None;
+                audit.event(resolutions.len(), Vec::new(), fingerprint)
+            }
+            Err(error) => audit
+                .event(0, self.missing.borrow().clone(), None)
+                .with_error(error),
+        };
+        audit.emit(&event);
+    }
+
+    /// Record a failed resolution to the [`FailureAnalytics`], if any.
+    #[cfg(feature = "analytics-engine")]
+    fn report_analytics(&self, resolutions: &Result<Vec<Resolution>, Error>) {
+        if let (Some(analytics), Err(error)) = (&self.analytics, resolutions) {
+            let _ = analytics.record(&self.missing.borrow(), error);
+        }
+    }
+
+    fn lookup(&self, binding: &str) -> Option<Found> {
+        self.sources().enumerate().find_map(|(index, source)| {
+            let (value, kind) = self.lookup_in(index, source, binding)?;
+            Some(Found {
+                value,
+                kind,
+                source: index,
+            })
+        })
+    }
+
+    /// The source the provider was created with.
+    fn primary(&self) -> &dyn BindingSource {
+        match &self.source {
+            Source::Borrowed(source) => *source,
+            Source::Owned(source) => &**source,
+        }
+    }
+
+    /// The primary source, then the fallbacks in order.
+    fn sources(&self) -> impl Iterator<Item = &dyn BindingSource> {
+        std::iter::once(self.primary()).chain(self.fallbacks.iter().copied())
+    }
+
+    /// Look `binding` up through the `kind` accessor alone, failing if it is
+    /// missing there but bound through the other one. Prefetched bindings
+    /// cannot tell the accessors apart, so each is looked up on its own.
+    fn lookup_as(&self, binding: &str, kind: BindingKind) -> Result<Option<Found>, Error> {
+        let find = |kind| {
+            self.sources().enumerate().find_map(|(index, source)| {
+                Some(Found {
+                    value: self.read(index, source, kind, binding, false)?,
+                    kind,
+                    source: index,
+                })
+            })
+        };
+        if let Some(found) = find(kind) {
+            return Ok(Some(found));
+        }
+        let (declared, other) = match kind {
+            BindingKind::Var => ("a var", BindingKind::Secret),
+            BindingKind::Secret => ("a secret", BindingKind::Var),
+        };
+        match find(other) {
+            Some(_) => Err(Error::from(format!(
+                "binding `{binding}` is declared {declared} but is not bound as one"
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    fn lookup_in(
+        &self,
+        index: usize,
+        source: &dyn BindingSource,
+        binding: &str,
+    ) -> Option<(String, BindingKind)> {
+        let var = || {
+            Some((
+                self.read(index, source, BindingKind::Var, binding, true)?,
+                BindingKind::Var,
+            ))
+        };
+        let secret = || {
+            Some((
+                self.read(index, source, BindingKind::Secret, binding, true)?,
+                BindingKind::Secret,
+            ))
+        };
+        match self.lookup_order {
+            LookupOrder::VarThenSecret => var().or_else(secret),
+            LookupOrder::SecretThenVar => secret().or_else(var),
+        }
+    }
+
+    /// Read `binding` through the `kind` accessor of the source at `index`
+    /// (the primary source, then the fallbacks in order), remembering the
+    /// result for later resolutions. With `prefetch`, sources that can
+    /// [prefetch](BindingSource::prefetch) their bindings are read in one
+    /// pass instead, which answers both accessors alike; lookups that must
+    /// tell the accessors apart go without it.
+    fn read(
+        &self,
+        index: usize,
+        source: &dyn BindingSource,
+        kind: BindingKind,
+        binding: &str,
+        prefetch: bool,
+    ) -> Option<String> {
+        let mut reads = self.reads.borrow_mut();
+        if reads.len() <= index {
+            reads.resize_with(index + 1, SourceReads::default);
+        }
+        let reads = &mut reads[index];
+        if prefetch {
+            let prefetched = reads.prefetched.get_or_init(|| {
+                #[cfg(any(feature = "timing", feature = "tracing"))]
+                let started = clock::now_ms();
+                let prefetched = source.prefetch();
+                #[cfg(feature = "timing")]
+                self.time_lookup(started);
+                #[cfg(feature = "tracing")]
+                if let Some(bindings) = &prefetched {
+                    tracing::debug!(
+                        source = index,
+                        bindings = bindings.len(),
+                        elapsed_ms = clock::now_ms() - started,
+                        "prefetched bindings",
+                    );
+                }
+                prefetched
+            });
+            if let Some(bindings) = prefetched {
+                let value = bindings.get(binding).cloned();
+                #[cfg(feature = "tracing")]
+                tracing::trace!(
+                    binding,
+                    source = index,
+                    hit = value.is_some(),
+                    "binding lookup (prefetched)",
+                );
+                return value;
+            }
+        }
+
+        let read = match kind {
+            BindingKind::Var => &mut reads.vars,
+            BindingKind::Secret => &mut reads.secrets,
+        };
+        if let Some(value) = read.get(binding) {
+            return value.clone();
+        }
+        #[cfg(any(feature = "timing", feature = "tracing"))]
+        let started = clock::now_ms();
+        let value = match kind {
+            BindingKind::Var => source.var(binding),
+            BindingKind::Secret => source.secret(binding),
+        };
+        #[cfg(feature = "timing")]
+        self.time_lookup(started);
+        #[cfg(feature = "tracing")]
+        match &value {
+            Ok(value) => tracing::debug!(
+                binding,
+                source = index,
+                accessor = kind.accessor(),
+                hit = value.is_some(),
+                elapsed_ms = clock::now_ms() - started,
+                "binding lookup",
+            ),
+            Err(error) => tracing::warn!(
+                binding,
+                source = index,
+                accessor = kind.accessor(),
+                %error,
+                "binding lookup failed",
+            ),
+        }
+        if value.is_err() {
+            self.count(ResolutionMetrics::record_parse_failure);
+        }
+        let value = value.ok().flatten();
+        read.insert(binding.to_owned(), value.clone());
+        value
+    }
+}
+
+impl CloudflareWorkersBindings<'static> {
+    /// Like [`from_struct`](Self::from_struct), but taking ownership of the
+    /// source, so the provider is `'static` and can be kept in a
+    /// `thread_local!`, moved into a future spawned with
+    /// `wasm_bindgen_futures::spawn_local`, or held across await points.
+    ///
+    /// A [`worker::Env`] is a cheap handle to the underlying JS object, so
+    /// passing `env.clone()` does not copy any bindings.
+    ///
+    /// ```rust,ignore
+    /// let provider = CloudflareWorkersBindings::from_struct_owned::<Config>(env.clone());
+    /// ```
+    #[must_use]
+    pub fn from_struct_owned<T: DeserializeOwned + 'static>(
+        source: impl BindingSource + 'static,
+    ) -> Self {
+        Self::discovered::<T>(Source::Owned(Rc::new(source)))
+    }
+
+    /// Like [`from_struct_owned`](Self::from_struct_owned), reading the
+    /// bindings held by a plain JS env object, such as one handed over by a
+    /// custom runtime, an embedded workerd or a `wasm-bindgen-test` test,
+    /// rather than a [`worker::Env`]:
+    ///
+    /// ```rust,ignore
+    /// let env = js_sys::Object::new();
+    /// js_sys::Reflect::set(&env, &"API_KEY".into(), &"test-key".into())?;
+    /// let provider = CloudflareWorkersBindings::from_js_object::<Config>(&env);
+    /// ```
+    ///
+    /// The object is read as [`JsBindings`] reads it; the provider holds a
+    /// handle to it, so later changes to its properties are seen by lookups
+    /// that have not been made yet.
+    #[cfg(feature = "js")]
+    #[must_use]
+    pub fn from_js_object<T: DeserializeOwned + 'static>(object: &js_sys::Object) -> Self {
+        Self::from_struct_owned::<T>(JsBindings::new(object.clone()))
+    }
+
+    /// Like [`all`](Self::all), but taking ownership of the source.
+    #[must_use]
+    pub fn all_owned(source: impl BindingSource + 'static) -> Self {
+        let fields = all_fields(&source);
+        Self::with_fields(Source::Owned(Rc::new(source)), Cow::Owned(fields))
+    }
 }
 
 impl Provider for CloudflareWorkersBindings<'_> {
@@ -102,40 +2155,189 @@ impl Provider for CloudflareWorkersBindings<'_> {
     }
 
     fn data(&self) -> Result<Map<Profile, Dict>, Error> {
-        let dict = self
-            .fields
-            .iter()
-            .filter_map(|field| {
-                let binding = field.to_uppercase();
-                let value = self
-                    .env
-                    .var(&binding)
-                    .map(|var| var.to_string())
-                    .ok()
-                    .or_else(|| {
-                        self.env
-                            .secret(&binding)
-                            .map(|secret| secret.to_string())
-                            .ok()
-                    })?;
-                Some((field.clone(), Value::from(value)))
-            })
-            .collect::<Dict>();
+        self.resolve_reported()
+            .map(|resolutions| self.collect(resolutions, MergeMode::Merged))
+    }
+}
+
+/// How diagnostics name the source at `index`: `primary`, then `fallback 1`
+/// and so on.
+fn source_label(index: usize) -> String {
+    if index == 0 {
+        "primary".to_owned()
+    } else {
+        format!("fallback {index}")
+    }
+}
+
+/// Log how `field` resolved: through which accessor of which source.
+///
+/// A field declared a var is never resolved from a secret, failing instead,
+/// and no other field can be told to be misprovisioned: the Workers runtime
+/// answers var lookups for secrets, and other sources may hold a secret
+/// only as a secret.
+#[cfg(feature = "log")]
+fn log_resolution(field: &Field, binding: &str, kind: BindingKind, source: usize) {
+    log::debug!(
+        "resolved field `{}` from the {} `{binding}` ({} source)",
+        field.name,
+        kind.accessor(),
+        source_label(source),
+    );
+}
 
-        Ok(self.profile.collect(dict))
+/// `bounds` in words, e.g. `at least 1 and at most 500`.
+fn describe_bounds((start, end): &Bounds) -> String {
+    let start = match start {
+        Bound::Included(start) => Some(format!("at least {start}")),
+        Bound::Excluded(start) => Some(format!("greater than {start}")),
+        Bound::Unbounded => None,
+    };
+    let end = match end {
+        Bound::Included(end) => Some(format!("at most {end}")),
+        Bound::Excluded(end) => Some(format!("less than {end}")),
+        Bound::Unbounded => None,
+    };
+    match (start, end) {
+        (Some(start), Some(end)) => format!("{start} and {end}"),
+        (Some(bound), None) | (None, Some(bound)) => bound,
+        (None, None) => "of any value".to_owned(),
     }
 }
 
+/// Write `line` to the console, or to standard error off wasm32.
+#[cfg(any(feature = "audit", feature = "console-debug"))]
+pub(crate) fn write_to_console(line: &str) {
+    #[cfg(target_arch = "wasm32")]
+    worker::console_log!("{line}");
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("{line}");
+}
+
+/// Nest `dict` under the dotted key `path`, if any.
+pub(crate) fn nest(path: Option<&str>, dict: Dict) -> Dict {
+    let Some(path) = path else {
+        return dict;
+    };
+    match figment2::util::nest(path, Value::from(dict)) {
+        Value::Dict(_, nested) => nested,
+        // `path` is empty or starts with an array index.
+        value => value.into_dict().unwrap_or_default(),
+    }
+}
+
+/// The fields of `T`, each read from its uppercased name.
+///
+/// Fields are built once per type and thread (and leaked, as there is one
+/// short list per configuration type), since providers are typically
+/// constructed on every request.
+///
+/// # Errors
+///
+/// Fails if the fields of `T` cannot be discovered.
+fn struct_fields<T: DeserializeOwned + 'static>() -> Result<&'static [Field], Error> {
+    try_cached_fields::<T, _>(|| {
+        let names = discover_field_names::<T>()?;
+        let mut fields = uppercased_fields(names);
+        for (field, name) in fields.iter_mut().zip(names) {
+            let ty = describe::field_type::<T>(name);
+            field.parse = ty.trim_start_matches("option<").starts_with("either");
+        }
+        Ok(fields)
+    })
+}
+
+/// `names` as fields, each read from its uppercased name.
+fn uppercased_fields(names: &[&'static str]) -> Vec<Field> {
+    names
+        .iter()
+        .map(|name| Field {
+            name: Cow::Borrowed(*name),
+            binding: Cow::Owned(name.to_uppercase()),
+            accessor: None,
+            parse: false,
+        })
+        .collect()
+}
+
+/// Keys the field cache for types whose field names are listed through
+/// [`FieldNames`] rather than discovered.
+struct Named<T: ?Sized>(PhantomData<T>);
+
+/// Keys the field cache for the union of the fields of the structs in a
+/// [`ConfigStructs`] tuple.
+struct Union<S>(PhantomData<S>);
+
+/// Keys the field cache for types whose bindings are declared through
+/// [`CloudflareConfig`] rather than discovered.
+struct Declared<T>(PhantomData<T>);
+
+/// The fields of `T`, built by `build` on first use in each thread.
+fn cached_fields<T: 'static>(build: impl FnOnce() -> Vec<Field>) -> &'static [Field] {
+    match try_cached_fields::<T, Infallible>(|| Ok(build())) {
+        Ok(fields) => fields,
+    }
+}
+
+/// Like [`cached_fields`], but `build` may fail, in which case nothing is
+/// cached.
+fn try_cached_fields<T: 'static, E>(
+    build: impl FnOnce() -> Result<Vec<Field>, E>,
+) -> Result<&'static [Field], E> {
+    thread_local! {
+        static FIELDS: RefCell<HashMap<TypeId, &'static [Field]>> =
+            RefCell::new(HashMap::new());
+    }
+
+    if let Some(fields) = FIELDS.with(|cache| cache.borrow().get(&TypeId::of::<T>()).copied()) {
+        return Ok(fields);
+    }
+    let fields: &'static [Field] = build()?.leak();
+    FIELDS.with(|cache| cache.borrow_mut().insert(TypeId::of::<T>(), fields));
+    Ok(fields)
+}
+
+/// Every binding `source` can enumerate, each emitted under its lowercased
+/// name.
+fn all_fields(source: &dyn BindingSource) -> Vec<Field> {
+    let mut names = source.names().unwrap_or_default();
+    names.sort_unstable();
+    names.dedup();
+    names
+        .into_iter()
+        .map(|binding| Field {
+            name: Cow::Owned(binding.to_lowercase()),
+            binding: Cow::Owned(binding),
+            accessor: None,
+            parse: false,
+        })
+        .collect()
+}
+
 /// Discover the field names of a `#[derive(Deserialize)]` struct by running
 /// a dummy deserialisation that captures the `fields` slice passed to
 /// [`Deserializer::deserialize_struct`].
-fn field_names<T: DeserializeOwned>() -> Vec<String> {
-    struct Extractor(Vec<String>);
+///
+/// # Errors
+///
+/// Fails if `T` is deserialised from a map or from any self-describing
+/// value, neither of which reveals the fields it expects.
+pub(crate) fn discover_field_names<T: DeserializeOwned>() -> Result<&'static [&'static str], Error>
+{
+    /// What the dummy deserialisation was asked for.
+    enum Shape {
+        Struct(&'static [&'static str]),
+        Map,
+        Any,
+    }
+
+    struct Extractor(Shape);
 
     impl<'de> Deserializer<'de> for &mut Extractor {
         type Error = de::value::Error;
 
         fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+            self.0 = Shape::Any;
             Err(de::Error::custom("field extraction only"))
         }
 
@@ -145,18 +2347,40 @@ fn field_names<T: DeserializeOwned>() -> Vec<String> {
             fields: &'static [&'static str],
             _visitor: V,
         ) -> Result<V::Value, Self::Error> {
-            self.0 = fields.iter().map(|field| (*field).to_owned()).collect();
+            self.0 = Shape::Struct(fields);
+            Err(de::Error::custom("field extraction only"))
+        }
+
+        fn deserialize_map<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+            self.0 = Shape::Map;
             Err(de::Error::custom("field extraction only"))
         }
 
         serde::forward_to_deserialize_any! {
             bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
             bytes byte_buf option unit unit_struct newtype_struct seq tuple
-            tuple_struct map enum identifier ignored_any
+            tuple_struct enum identifier ignored_any
         }
     }
 
-    let mut extractor = Extractor(Vec::new());
+    let mut extractor = Extractor(Shape::Any);
     let _ = T::deserialize(&mut extractor);
-    extractor.0
+    match extractor.0 {
+        Shape::Struct(fields) => Ok(fields),
+        Shape::Map => Err(Error::from(format!(
+            "cannot discover the fields of `{}`: it is deserialised from a map, as \
+             structs with `#[serde(flatten)]` fields are; list its fields through \
+             `FieldNames` and use `from_field_names` instead",
+            type_name::<T>()
+        ))),
+        Shape::Any => Err(Error::from(format!(
+            "cannot discover the fields of `{}`: it is not deserialised as a struct, \
+             e.g. as `#[serde(untagged)]` enums are not; name its fields with \
+             `field_names`",
+            type_name::<T>()
+        ))),
+    }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,77 @@
+use serde::Serialize;
+
+/// Counters of what a provider's resolutions found, from
+/// [`CloudflareWorkersBindings::metrics`](crate::CloudflareWorkersBindings::metrics).
+///
+/// Counters accumulate over every resolution of the provider and its
+/// snapshots, fingerprints and diagnostics; merging a provider into a figment
+/// resolves it once. Export them to a metrics pipeline to alert when, say,
+/// secret hits suddenly spike because vars went missing:
+///
+/// ```rust,ignore
+/// let provider = CloudflareWorkersBindings::from_struct::<Config>(&env);
+/// let config: Config = Figment::new().merge(&provider).extract()?;
+/// let metrics = provider.metrics();
+/// console_log!("{}", serde_json::to_string(&metrics)?);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ResolutionMetrics {
+    lookups: u64,
+    var_hits: u64,
+    secret_hits: u64,
+    misses: u64,
+    parse_failures: u64,
+}
+
+impl ResolutionMetrics {
+    /// The number of fields looked up.
+    #[must_use]
+    pub fn lookups(&self) -> u64 {
+        self.lookups
+    }
+
+    /// The number of lookups answered by a var.
+    #[must_use]
+    pub fn var_hits(&self) -> u64 {
+        self.var_hits
+    }
+
+    /// The number of lookups answered by a secret.
+    #[must_use]
+    pub fn secret_hits(&self) -> u64 {
+        self.secret_hits
+    }
+
+    /// The number of lookups that found nothing bound.
+    #[must_use]
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// The number of bindings that exist but could not be read as strings,
+    /// e.g. a KV namespace looked up as a var. Each is skipped as if it were
+    /// not bound, and counted once, as the provider reads each binding at
+    /// most once.
+    #[must_use]
+    pub fn parse_failures(&self) -> u64 {
+        self.parse_failures
+    }
+
+    pub(crate) fn record_hit(&mut self, secret: bool) {
+        self.lookups += 1;
+        if secret {
+            self.secret_hits += 1;
+        } else {
+            self.var_hits += 1;
+        }
+    }
+
+    pub(crate) fn record_miss(&mut self) {
+        self.lookups += 1;
+        self.misses += 1;
+    }
+
+    pub(crate) fn record_parse_failure(&mut self) {
+        self.parse_failures += 1;
+    }
+}
@@ -0,0 +1,176 @@
+use std::{collections::BTreeMap, fmt};
+
+use figment2::{
+    value::{Dict, Map, Num, Value},
+    Error, Metadata, Profile, Provider,
+};
+
+/// The key a configuration document keeps its schema version under.
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+type Step = Box<dyn Fn(&mut Dict) -> Result<(), String>>;
+
+/// The migrations upgrading configuration documents, such as ones stored
+/// in KV or R2 or fetched from a remote origin, from older schema versions
+/// to the one the worker's configuration struct expects.
+///
+/// Each step upgrades a document by one version, so a document is brought
+/// to the current version by running every step from its own onwards:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::Migrations;
+///
+/// let migrations = Migrations::new(2)
+///     // Version 1 renamed `retries`.
+///     .step(0, |document| {
+///         if let Some(retries) = document.remove("retries") {
+///             document.insert("max_retries".into(), retries);
+///         }
+///         Ok::<_, String>(())
+///     })
+///     // Version 2 made `timeout` milliseconds rather than seconds.
+///     .step(1, |document| { /* ... */ Ok::<_, String>(()) });
+/// let figment = Figment::new().merge(migrations.apply(Json::string(&document)));
+/// ```
+///
+/// A document's version is read from [`SCHEMA_VERSION_KEY`], as a number or
+/// a numeric string; documents without one predate versioning and are at
+/// version 0. Migrated documents have the key set to the current version.
+pub struct Migrations {
+    current: u64,
+    steps: BTreeMap<u64, Step>,
+}
+
+impl Migrations {
+    /// Migrate documents to the schema version `current`.
+    #[must_use]
+    pub fn new(current: u64) -> Self {
+        Self {
+            current,
+            steps: BTreeMap::new(),
+        }
+    }
+
+    /// Upgrade documents at version `from` to version `from + 1` with
+    /// `migrate`, replacing any step registered for `from` before. A step
+    /// fails by returning any displayable error, e.g. a `String`.
+    #[must_use]
+    pub fn step<E: fmt::Display>(
+        mut self,
+        from: u64,
+        migrate: impl Fn(&mut Dict) -> Result<(), E> + 'static,
+    ) -> Self {
+        self.steps.insert(
+            from,
+            Box::new(move |document| migrate(document).map_err(|error| error.to_string())),
+        );
+        self
+    }
+
+    /// The schema version documents are migrated to.
+    #[must_use]
+    pub fn current(&self) -> u64 {
+        self.current
+    }
+
+    /// Migrate `document` to the current version in place.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the document's version is not a number or is newer than the
+    /// current one, if a step between the two is missing, or if a step
+    /// fails; `document` may then be partially migrated.
+    pub fn migrate(&self, document: &mut Dict) -> Result<(), Error> {
+        let mut version = schema_version(document)?;
+        if version > self.current {
+            return Err(Error::from(format!(
+                "configuration schema version {version} is newer than the supported version {}",
+                self.current
+            )));
+        }
+        while version < self.current {
+            let step = self.steps.get(&version).ok_or_else(|| {
+                Error::from(format!(
+                    "no migration from configuration schema version {version}"
+                ))
+            })?;
+            step(document).map_err(|error| {
+                Error::from(format!(
+                    "migrating configuration schema version {version}: {error}"
+                ))
+            })?;
+            version += 1;
+        }
+        document.insert(
+            SCHEMA_VERSION_KEY.to_owned(),
+            Value::from(Num::U64(self.current)),
+        );
+        Ok(())
+    }
+
+    /// Wrap `provider`, so its documents are migrated before they are
+    /// merged.
+    #[must_use]
+    pub fn apply<P: Provider>(self, provider: P) -> Migrated<P> {
+        Migrated {
+            provider,
+            migrations: self,
+        }
+    }
+}
+
+impl fmt::Debug for Migrations {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("Migrations")
+            .field("current", &self.current)
+            .field("steps", &self.steps.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// The version `document` declares under [`SCHEMA_VERSION_KEY`].
+fn schema_version(document: &Dict) -> Result<u64, Error> {
+    let Some(value) = document.get(SCHEMA_VERSION_KEY) else {
+        return Ok(0);
+    };
+    let version = match value {
+        Value::Num(_, num) => num.to_u128_lossy().and_then(|num| u64::try_from(num).ok()),
+        Value::String(_, string) => string.trim().parse().ok(),
+        _ => None,
+    };
+    version.ok_or_else(|| {
+        Error::from(format!(
+            "`{SCHEMA_VERSION_KEY}` must be a non-negative integer"
+        ))
+    })
+}
+
+/// A [figment2] provider whose documents are upgraded by [`Migrations`]
+/// before they are merged; see [`Migrations::apply`].
+///
+/// The dictionary of every profile of the wrapped provider is migrated on
+/// its own.
+#[derive(Debug)]
+pub struct Migrated<P> {
+    provider: P,
+    migrations: Migrations,
+}
+
+impl<P: Provider> Provider for Migrated<P> {
+    fn metadata(&self) -> Metadata {
+        self.provider.metadata()
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        let mut data = self.provider.data()?;
+        for document in data.values_mut() {
+            self.migrations.migrate(document)?;
+        }
+        Ok(data)
+    }
+
+    fn profile(&self) -> Option<Profile> {
+        self.provider.profile()
+    }
+}
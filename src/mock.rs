@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use crate::{BindingError, BindingSource};
+
+/// An in-memory [`BindingSource`] for testing configuration logic in plain
+/// `cargo test`, without a wasm runtime or a real [`worker::Env`].
+///
+/// Vars and secrets are kept apart, as in Cloudflare's model, so var/secret
+/// fallback and [`LookupOrder`](crate::LookupOrder) paths can be exercised.
+/// Bindings registered with [`with_error`](Self::with_error) fail every
+/// lookup, simulating the JS error raised when a binding of another kind
+/// (e.g. a KV namespace) is read as a string.
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, MockBindings};
+///
+/// let bindings = MockBindings::new()
+///     .with_var("DATABASE_URL", "postgres://localhost")
+///     .with_secret("API_KEY", "test-key")
+///     .with_error("CACHE", "Binding cannot be cast to the type String from KvNamespace");
+///
+/// let config: Config = Figment::new()
+///     .merge(CloudflareWorkersBindings::from_struct::<Config>(&bindings))
+///     .extract()?;
+/// ```
+///
+/// For end-to-end tests against workerd, the repository's
+/// `tests/harness.mjs` starts a worker under Miniflare with a given set of
+/// bindings and asserts on the JSON its routes return; it is
+/// self-contained and can be copied into other projects.
+#[derive(Clone, Debug, Default)]
+pub struct MockBindings {
+    vars: HashMap<String, String>,
+    secrets: HashMap<String, String>,
+    errors: HashMap<String, String>,
+}
+
+impl MockBindings {
+    /// Create an empty set of bindings.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind the plain-text var `name` to `value`.
+    #[must_use]
+    pub fn with_var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.vars.insert(name.into(), value.into());
+        self
+    }
+
+    /// Bind the secret `name` to `value`.
+    #[must_use]
+    pub fn with_secret(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.secrets.insert(name.into(), value.into());
+        self
+    }
+
+    /// Bind every secret in a `wrangler secret bulk` file.
+    #[cfg(feature = "wrangler")]
+    #[must_use]
+    pub fn with_secrets_file(mut self, file: &crate::SecretsFile) -> Self {
+        self.secrets.extend(
+            file.iter()
+                .map(|(name, value)| (name.to_owned(), value.to_owned())),
+        );
+        self
+    }
+
+    /// Make every lookup of `name` fail with `message`.
+    #[must_use]
+    pub fn with_error(mut self, name: impl Into<String>, message: impl Into<String>) -> Self {
+        self.errors.insert(name.into(), message.into());
+        self
+    }
+
+    fn get(
+        &self,
+        bindings: &HashMap<String, String>,
+        name: &str,
+    ) -> Result<Option<String>, BindingError> {
+        match self.errors.get(name) {
+            Some(message) => Err(BindingError::new(message.clone())),
+            None => Ok(bindings.get(name).cloned()),
+        }
+    }
+}
+
+impl BindingSource for MockBindings {
+    fn var(&self, name: &str) -> Result<Option<String>, BindingError> {
+        self.get(&self.vars, name)
+    }
+
+    fn secret(&self, name: &str) -> Result<Option<String>, BindingError> {
+        self.get(&self.secrets, name)
+    }
+
+    fn names(&self) -> Option<Vec<String>> {
+        let names = self.vars.keys().chain(self.secrets.keys());
+        Some(
+            names
+                .filter(|name| !self.errors.contains_key(*name))
+                .cloned()
+                .collect(),
+        )
+    }
+}
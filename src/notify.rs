@@ -0,0 +1,120 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use figment2::Error;
+use serde::{Deserialize, Serialize};
+use worker::Queue;
+
+use crate::Snapshot;
+
+thread_local! {
+    /// The last fingerprint every [`ChangeNotifier`] in the isolate saw,
+    /// keyed by source.
+    static FINGERPRINTS: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+}
+
+/// Publishes a [`ConfigChange`] to a bound Queue when the
+/// [fingerprint](Snapshot::fingerprint) of a reloaded configuration differs
+/// from the last one seen, so other systems, such as cache invalidators or
+/// sibling workers, learn about the change without polling.
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{ChangeNotifier, CloudflareWorkersBindings, ConfigStore};
+///
+/// let kv = ConfigStore::new(&env, "CONFIG")?.max_age(Duration::from_secs(30)).load::<Config>().await?;
+/// let snapshot = CloudflareWorkersBindings::from_sources::<Config>(&[&env, &kv]).snapshot()?;
+/// ChangeNotifier::new(env.queue("CONFIG_CHANGES")?, "checkout-api", salt.as_bytes())
+///     .notify_if_changed(&snapshot)
+///     .await?;
+/// ```
+///
+/// Fingerprints are remembered per isolate, so the first check in each
+/// isolate only records a baseline: a change is published by the isolates
+/// that see it happen, e.g. as a [`ConfigStore`](crate::ConfigStore) value
+/// goes stale and is read anew, and consumers should expect it more than
+/// once.
+#[derive(Debug)]
+pub struct ChangeNotifier {
+    queue: Queue,
+    source: String,
+    salt: Vec<u8>,
+}
+
+impl ChangeNotifier {
+    /// Publish changes to `queue` under `source`, fingerprinting with
+    /// `salt`; see [`Snapshot::fingerprint`].
+    #[must_use]
+    pub fn new(queue: Queue, source: impl Into<String>, salt: &[u8]) -> Self {
+        Self {
+            queue,
+            source: source.into(),
+            salt: salt.to_vec(),
+        }
+    }
+
+    /// Fingerprint `snapshot` and, if it differs from the last fingerprint
+    /// seen under this source in the isolate, publish a [`ConfigChange`].
+    ///
+    /// Returns whether a change was published.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the message cannot be sent; the fingerprint is then not
+    /// recorded, so the next check tries again.
+    pub async fn notify_if_changed(&self, snapshot: &Snapshot) -> Result<bool, Error> {
+        let fingerprint = snapshot.fingerprint(&self.salt);
+        let previous =
+            FINGERPRINTS.with(|fingerprints| fingerprints.borrow().get(&self.source).cloned());
+        if previous.as_ref() == Some(&fingerprint) {
+            return Ok(false);
+        }
+        let changed = previous.is_some();
+        if let Some(previous) = previous {
+            let change = ConfigChange {
+                source: self.source.clone(),
+                previous,
+                fingerprint: fingerprint.clone(),
+            };
+            self.queue.send(change).await.map_err(|error| {
+                Error::from(format!(
+                    "publishing the change of `{}`: {error}",
+                    self.source
+                ))
+            })?;
+        }
+        FINGERPRINTS.with(|fingerprints| {
+            fingerprints
+                .borrow_mut()
+                .insert(self.source.clone(), fingerprint)
+        });
+        Ok(changed)
+    }
+}
+
+/// The message a [`ChangeNotifier`] publishes, serialised as e.g.
+/// `{"source":"checkout-api","previous":"1f0c…","fingerprint":"9b7e…"}`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigChange {
+    source: String,
+    previous: String,
+    fingerprint: String,
+}
+
+impl ConfigChange {
+    /// The source the change was published under.
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The fingerprint of the configuration before the change.
+    #[must_use]
+    pub fn previous(&self) -> &str {
+        &self.previous
+    }
+
+    /// The fingerprint of the configuration after the change.
+    #[must_use]
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+}
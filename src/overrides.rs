@@ -0,0 +1,395 @@
+use figment2::{
+    value::{Dict, Map, Value},
+    Error, Metadata, Profile, Provider,
+};
+use worker::{Request, Url};
+
+use crate::{token::tokens_match, BindingSource, Snapshot};
+
+/// The request header [`HeaderOverrides`] reads a JSON object of overrides
+/// from.
+pub const OVERRIDE_HEADER: &str = "X-Config-Override";
+
+/// The request header carrying the shared secret that authorises
+/// [`HeaderOverrides`].
+pub const OVERRIDE_TOKEN_HEADER: &str = "X-Config-Override-Token";
+
+/// The prefix of the query parameters [`QueryOverrides`] reads:
+/// `?cfg.log_level=debug` overrides `log_level`.
+pub const QUERY_OVERRIDE_PREFIX: &str = "cfg.";
+
+/// Configuration overrides carried by a single request, for debugging a
+/// preview deployment without redeploying it: a JSON object in the
+/// [`OVERRIDE_HEADER`] header, merged on top of the extracted configuration
+/// for that request only.
+///
+/// Overrides are an explicit opt-in: they are only read when a secret is
+/// bound under the binding named to [`from_request`](Self::from_request),
+/// and only applied when the request presents that secret in the
+/// [`OVERRIDE_TOKEN_HEADER`] header. Leave the secret unbound, or empty, in
+/// production and the header is ignored there:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{FigmentExt, HeaderOverrides};
+///
+/// let overrides = HeaderOverrides::from_request(&request, &env, "CONFIG_OVERRIDE_TOKEN")?;
+/// let config: Config = Figment::from_cloudflare::<Config>(&env)
+///     .merge(overrides)
+///     .extract_lossy()?;
+/// ```
+///
+/// ```sh
+/// curl -H 'X-Config-Override: {"log_level": "debug"}' \
+///      -H "X-Config-Override-Token: $TOKEN" https://preview.example.workers.dev/
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct HeaderOverrides {
+    values: Dict,
+}
+
+impl HeaderOverrides {
+    /// Read the overrides `request` carries, authorised by the secret bound
+    /// as `token_binding` in `source`. They are empty if the request carries
+    /// none, or if the secret is not bound or is empty.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request carries overrides with a missing or wrong token,
+    /// or if they are not a JSON object.
+    pub fn from_request(
+        request: &Request,
+        source: &dyn BindingSource,
+        token_binding: &str,
+    ) -> Result<Self, Error> {
+        let header = |name| request.headers().get(name).ok().flatten();
+        let Some(overrides) = header(OVERRIDE_HEADER) else {
+            return Ok(Self::default());
+        };
+        let Some(expected) = source
+            .secret(token_binding)
+            .ok()
+            .flatten()
+            .filter(|expected| !expected.is_empty())
+        else {
+            return Ok(Self::default());
+        };
+        if !header(OVERRIDE_TOKEN_HEADER).is_some_and(|token| tokens_match(&token, &expected)) {
+            return Err(Error::from(format!(
+                "`{OVERRIDE_HEADER}` rejected: `{OVERRIDE_TOKEN_HEADER}` is missing or wrong"
+            )));
+        }
+        let values: Dict = serde_json::from_str(&overrides).map_err(|error| {
+            Error::from(format!("`{OVERRIDE_HEADER}` is not a JSON object: {error}"))
+        })?;
+        #[cfg(feature = "log")]
+        log::warn!(
+            "applying request configuration overrides of {}",
+            values
+                .keys()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        Ok(Self { values })
+    }
+
+    /// Whether the request carried no overrides to apply.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The top-level keys overridden, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+}
+
+impl Provider for HeaderOverrides {
+    fn metadata(&self) -> Metadata {
+        Metadata::named(format!("`{OVERRIDE_HEADER}` request header"))
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        Ok(Profile::Global.collect(self.values.clone()))
+    }
+}
+
+/// Configuration overrides read from the query parameters of a single
+/// request to a preview deployment, e.g. `?cfg.log_level=debug`, merged on
+/// top of the extracted configuration for that request only.
+///
+/// Only requests to a preview hostname are considered, and only fields
+/// explicitly allowed, and not secret in the [`Snapshot`] of the
+/// configuration, can be overridden, so a link cannot swap credentials or
+/// reconfigure production:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, QueryOverrides};
+///
+/// let snapshot = CloudflareWorkersBindings::from_struct::<Config>(&env)
+///     .secret("api_key")
+///     .snapshot()?;
+/// let overrides = QueryOverrides::from_request(
+///     &request,
+///     &snapshot,
+///     &["log_level", "sample_rate"],
+///     &["*.my-worker.example.workers.dev"],
+/// )?;
+/// let config: Config = Figment::from(snapshot).merge(overrides).extract_lossy()?;
+/// ```
+///
+/// A preview hostname is matched exactly, or, written as `*.example.com`,
+/// by any subdomain. Parameters without the [`QUERY_OVERRIDE_PREFIX`] are
+/// left alone; values are strings, converted to the field's type by lossy
+/// extraction. Dotted names override nested values: `?cfg.db.host=localhost`
+/// sets `host` in the `db` table, and is allowed if either `db.host` or
+/// `db` is.
+#[derive(Clone, Debug, Default)]
+pub struct QueryOverrides {
+    values: Dict,
+}
+
+impl QueryOverrides {
+    /// Read the overrides in the query of `request` if it was sent to one
+    /// of `preview_hosts`, allowing the fields `allowed` that are not secret
+    /// in `snapshot`. They are empty for requests to any other host.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request URL cannot be parsed, or, for a preview
+    /// hostname, if a parameter overrides a field that is not allowed or is
+    /// secret.
+    pub fn from_request(
+        request: &Request,
+        snapshot: &Snapshot,
+        allowed: &[&str],
+        preview_hosts: &[&str],
+    ) -> Result<Self, Error> {
+        let url = request
+            .url()
+            .map_err(|error| Error::from(format!("request URL: {error}")))?;
+        Self::from_url(&url, snapshot, allowed, preview_hosts)
+    }
+
+    fn from_url(
+        url: &Url,
+        snapshot: &Snapshot,
+        allowed: &[&str],
+        preview_hosts: &[&str],
+    ) -> Result<Self, Error> {
+        let host = url.host_str().unwrap_or_default();
+        if !preview_hosts
+            .iter()
+            .any(|pattern| host_matches(host, pattern))
+        {
+            return Ok(Self::default());
+        }
+        let mut values = Dict::new();
+        for (name, value) in url.query_pairs() {
+            let Some(field) = name.strip_prefix(QUERY_OVERRIDE_PREFIX) else {
+                continue;
+            };
+            let top = field.split('.').next().unwrap_or(field);
+            if snapshot.is_secret(top) {
+                return Err(Error::from(format!(
+                    "`{QUERY_OVERRIDE_PREFIX}{field}` rejected: `{top}` is secret"
+                )));
+            }
+            if !allowed.contains(&field) && !allowed.contains(&top) {
+                return Err(Error::from(format!(
+                    "`{QUERY_OVERRIDE_PREFIX}{field}` rejected: `{field}` may not be overridden"
+                )));
+            }
+            insert_at(&mut values, field, Value::from(value.into_owned()));
+        }
+        #[cfg(feature = "log")]
+        if !values.is_empty() {
+            log::warn!(
+                "applying query configuration overrides of {}",
+                values
+                    .keys()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        Ok(Self { values })
+    }
+
+    /// Whether the request carried no overrides to apply.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The top-level fields overridden, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+}
+
+impl Provider for QueryOverrides {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("request query overrides")
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        Ok(Profile::Global.collect(self.values.clone()))
+    }
+}
+
+/// Insert `value` into `dict` at the dotted `path`, creating the tables on
+/// the way, or replacing any value that is not one.
+fn insert_at(dict: &mut Dict, path: &str, value: Value) {
+    let Some((key, rest)) = path.split_once('.') else {
+        dict.insert(path.to_owned(), value);
+        return;
+    };
+    let entry = dict
+        .entry(key.to_owned())
+        .or_insert_with(|| Value::from(Dict::new()));
+    if !matches!(entry, Value::Dict(..)) {
+        *entry = Value::from(Dict::new());
+    }
+    if let Value::Dict(_, nested) = entry {
+        insert_at(nested, rest, value);
+    }
+}
+
+/// Whether `host` is `pattern`, or, for a pattern `*.example.com`, a
+/// subdomain of `example.com`.
+fn host_matches(host: &str, pattern: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host
+            .strip_suffix(domain)
+            .is_some_and(|subdomain| subdomain.len() > 1 && subdomain.ends_with('.')),
+        None => host.eq_ignore_ascii_case(pattern),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use figment2::{providers::Serialized, Figment};
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Config {
+        log_level: String,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Database {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Nested {
+        log_level: String,
+        db: Database,
+    }
+
+    fn snapshot(secrets: &[&str]) -> Snapshot {
+        Snapshot::new(
+            Profile::Default,
+            None,
+            Dict::new(),
+            secrets.iter().map(|&secret| secret.to_owned()).collect(),
+        )
+    }
+
+    fn query(url: &str, allowed: &[&str]) -> Result<QueryOverrides, Error> {
+        QueryOverrides::from_url(
+            &Url::parse(url).unwrap(),
+            &snapshot(&["db_password"]),
+            allowed,
+            &["*.preview.example.com"],
+        )
+    }
+
+    #[test]
+    fn header_overrides_win_in_the_selected_profile() {
+        let overrides = HeaderOverrides {
+            values: Dict::from([("log_level".to_owned(), Value::from("debug"))]),
+        };
+        let config: Config = Figment::from(Serialized::default("log_level", "warn"))
+            .merge(Serialized::from(
+                Dict::from([("log_level".to_owned(), Value::from("info"))]),
+                "production",
+            ))
+            .merge(overrides)
+            .select("production")
+            .extract()
+            .unwrap();
+        assert_eq!(config.log_level, "debug");
+    }
+
+    #[test]
+    fn dotted_query_overrides_set_nested_fields_in_the_selected_profile() {
+        let overrides = query(
+            "https://pr-7.preview.example.com/?cfg.db.host=localhost&cfg.db.port=5433&cfg.log_level=debug",
+            &["db", "log_level"],
+        )
+        .unwrap();
+        assert_eq!(overrides.keys().collect::<Vec<_>>(), ["db", "log_level"]);
+        let base = Dict::from([
+            ("log_level".to_owned(), Value::from("info")),
+            (
+                "db".to_owned(),
+                Value::from(Dict::from([
+                    ("host".to_owned(), Value::from("db.internal")),
+                    ("port".to_owned(), Value::from(5432)),
+                ])),
+            ),
+        ]);
+        let config: Nested = Figment::from(Serialized::from(base, "production"))
+            .merge(overrides)
+            .select("production")
+            .extract_lossy()
+            .unwrap();
+        assert_eq!(
+            config,
+            Nested {
+                log_level: "debug".to_owned(),
+                db: Database {
+                    host: "localhost".to_owned(),
+                    port: 5433,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn query_overrides_check_the_top_level_field() {
+        assert!(query(
+            "https://pr-7.preview.example.com/?cfg.db.host=x",
+            &["db.host"]
+        )
+        .is_ok_and(|overrides| !overrides.is_empty()));
+        assert_eq!(
+            query(
+                "https://pr-7.preview.example.com/?cfg.db.host=x",
+                &["log_level"]
+            )
+            .unwrap_err()
+            .to_string(),
+            "`cfg.db.host` rejected: `db.host` may not be overridden"
+        );
+        assert_eq!(
+            query(
+                "https://pr-7.preview.example.com/?cfg.db_password.x=y",
+                &["db_password"]
+            )
+            .unwrap_err()
+            .to_string(),
+            "`cfg.db_password.x` rejected: `db_password` is secret"
+        );
+        assert!(
+            query("https://example.com/?cfg.log_level=debug", &["log_level"])
+                .is_ok_and(|overrides| overrides.is_empty())
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use figment2::Error;
+use worker::{
+    wasm_bindgen::{JsCast, JsValue},
+    Env,
+};
+
+use crate::{BindingError, BindingSource};
+
+/// The vars Cloudflare Pages sets on every deployment, rather than the
+/// project: whether the code runs on Pages, and the branch, commit and URL
+/// of the deployment.
+pub const PAGES_PLATFORM_VARS: &[&str] = &[
+    "CF_PAGES",
+    "CF_PAGES_BRANCH",
+    "CF_PAGES_COMMIT_SHA",
+    "CF_PAGES_URL",
+];
+
+/// The bindings of a Cloudflare Pages project, as handed to a Pages
+/// Function in `context.env` or to an advanced-mode `_worker.js`.
+///
+/// Pages exposes the bindings as a plain object rather than through a
+/// `#[event(fetch)]` handler, so a Function calling into Rust passes
+/// `context.env` across as a [`JsValue`]:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, PagesEnv};
+///
+/// #[wasm_bindgen]
+/// pub fn handle(env: JsValue) -> Result<String, JsError> {
+///     let env = PagesEnv::from_js(env)?;
+///     let config: Config = Figment::new()
+///         .merge(CloudflareWorkersBindings::from_struct::<Config>(&env))
+///         .extract()?;
+///     // ...
+/// }
+/// ```
+///
+/// Bindings are read as from an [`Env`], except that the
+/// [`PAGES_PLATFORM_VARS`] are left out of the names it lists, so that
+/// [`all`](crate::CloudflareWorkersBindings::all) returns the project's own
+/// vars and secrets; they can still be read as fields, and
+/// [`branch`](Self::branch) reads the branch deployed. The `ASSETS` binding,
+/// like every binding that is not a var or secret, is never read.
+#[derive(Clone, Debug)]
+pub struct PagesEnv {
+    env: Env,
+}
+
+impl PagesEnv {
+    /// The bindings in `env`, the `context.env` of a Pages Function.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `env` is not an object.
+    pub fn from_js(env: JsValue) -> Result<Self, Error> {
+        if env.is_object() {
+            Ok(Self {
+                env: env.unchecked_into(),
+            })
+        } else {
+            Err(Error::from(
+                "the bindings of a Pages project must be an object".to_owned(),
+            ))
+        }
+    }
+
+    /// The bindings, as the [`Env`] of a worker.
+    #[must_use]
+    pub fn env(&self) -> &Env {
+        &self.env
+    }
+
+    /// The branch deployed, from `CF_PAGES_BRANCH`, if set.
+    #[must_use]
+    pub fn branch(&self) -> Option<String> {
+        BindingSource::var(&self.env, "CF_PAGES_BRANCH")
+            .ok()
+            .flatten()
+    }
+}
+
+impl From<Env> for PagesEnv {
+    fn from(env: Env) -> Self {
+        Self { env }
+    }
+}
+
+impl BindingSource for PagesEnv {
+    fn var(&self, name: &str) -> Result<Option<String>, BindingError> {
+        BindingSource::var(&self.env, name)
+    }
+
+    fn secret(&self, name: &str) -> Result<Option<String>, BindingError> {
+        BindingSource::secret(&self.env, name)
+    }
+
+    fn names(&self) -> Option<Vec<String>> {
+        let mut names = self.env.names()?;
+        names.retain(|name| !PAGES_PLATFORM_VARS.contains(&name.as_str()));
+        Some(names)
+    }
+
+    fn prefetch(&self) -> Option<HashMap<String, String>> {
+        self.env.prefetch()
+    }
+}
@@ -0,0 +1,116 @@
+use std::borrow::Cow;
+
+use figment2::value::Value;
+use serde::{ser, Serialize, Serializer};
+
+use crate::REDACTED;
+
+/// A [`Serialize`] wrapper that masks selected fields of a configuration
+/// value.
+///
+/// The wrapped value is serialised as usual, except that every masked path
+/// is replaced by [`REDACTED`]. Paths are dotted (e.g. `database.password`)
+/// and refer to serialised field names. Paths that do not exist, or whose
+/// value is `None`, are left untouched.
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::Redacted;
+///
+/// let body = Redacted::new(&config).mask("api_key");
+/// Response::from_json(&body)
+/// ```
+pub struct Redacted<'a, T: ?Sized> {
+    value: &'a T,
+    paths: Vec<Cow<'a, str>>,
+    /// Whether `paths` are the values left in place, every other one being
+    /// masked.
+    revealing: bool,
+}
+
+impl<'a, T: Serialize + ?Sized> Redacted<'a, T> {
+    /// Wrap `value` without masking anything yet.
+    #[must_use]
+    pub fn new(value: &'a T) -> Self {
+        Self {
+            value,
+            paths: Vec::new(),
+            revealing: false,
+        }
+    }
+
+    /// Wrap `value`, masking every value except those at the dotted
+    /// `paths`.
+    #[cfg(feature = "admin")]
+    pub(crate) fn revealing<P: Into<Cow<'a, str>>>(
+        value: &'a T,
+        paths: impl IntoIterator<Item = P>,
+    ) -> Self {
+        Self {
+            value,
+            paths: paths.into_iter().map(Into::into).collect(),
+            revealing: true,
+        }
+    }
+
+    /// Mask the value at the dotted `path`.
+    #[must_use]
+    pub fn mask(mut self, path: impl Into<Cow<'a, str>>) -> Self {
+        self.paths.push(path.into());
+        self
+    }
+
+    /// Mask the values at every dotted path in `paths`.
+    #[must_use]
+    pub fn mask_all<P: Into<Cow<'a, str>>>(mut self, paths: impl IntoIterator<Item = P>) -> Self {
+        self.paths.extend(paths.into_iter().map(Into::into));
+        self
+    }
+}
+
+impl<T: Serialize + ?Sized> Serialize for Redacted<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut value = Value::serialize(self.value).map_err(ser::Error::custom)?;
+        if self.revealing {
+            mask_unrevealed(&mut value, "", &self.paths);
+        } else {
+            for path in &self.paths {
+                mask(&mut value, path.split('.'));
+            }
+        }
+        value.serialize(serializer)
+    }
+}
+
+fn mask<'p>(value: &mut Value, mut path: impl Iterator<Item = &'p str>) {
+    match (path.next(), value) {
+        (None, Value::Empty(..)) => {}
+        (None, value) => *value = Value::from(REDACTED),
+        (Some(key), Value::Dict(_, dict)) => {
+            if let Some(value) = dict.get_mut(key) {
+                mask(value, path);
+            }
+        }
+        (Some(_), _) => {}
+    }
+}
+
+/// Mask every value under `value`, found at the dotted `prefix`, except
+/// those at or under one of the `revealed` paths.
+fn mask_unrevealed(value: &mut Value, prefix: &str, revealed: &[Cow<'_, str>]) {
+    match value {
+        Value::Empty(..) => {}
+        Value::Dict(_, dict) => {
+            for (key, value) in dict.iter_mut() {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                if !revealed.iter().any(|revealed| *revealed == path) {
+                    mask_unrevealed(value, &path, revealed);
+                }
+            }
+        }
+        value => *value = Value::from(REDACTED),
+    }
+}
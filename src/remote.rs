@@ -0,0 +1,219 @@
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+
+use figment2::Error;
+use worker::{
+    wasm_bindgen::JsCast, wasm_bindgen_futures::JsFuture, worker_sys, Env, Fetch, Headers, Request,
+    RequestInit, Response,
+};
+
+thread_local! {
+    /// The last document every [`RemoteDocument`] in the isolate fetched,
+    /// keyed by service binding and URL.
+    static DOCUMENTS: RefCell<HashMap<String, Cached>> = RefCell::new(HashMap::new());
+}
+
+/// A fetched document and the validators it was served with.
+#[derive(Clone)]
+struct Cached {
+    document: Rc<str>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+/// A configuration document fetched from a remote origin or through a
+/// service binding, and revalidated with conditional requests.
+///
+/// The isolate keeps the last document fetched from each URL along with its
+/// `ETag` and `Last-Modified` headers, and sends them back as
+/// `If-None-Match` and `If-Modified-Since`, so a refresh of a document that
+/// has not changed is answered with an empty `304 Not Modified`. Whether
+/// the document changed is reported, so callers can keep what they parsed
+/// from it before:
+///
+/// ```rust,ignore
+/// use figment2::providers::Json;
+/// use figment2_cloudflare_workers::RemoteDocument;
+///
+/// let fetched = RemoteDocument::service(&env, "CONFIG_SERVICE", "https://config/checkout.json")?
+///     .fetch()
+///     .await?;
+/// if fetched.modified() {
+///     let config: Config = Figment::new().merge(Json::string(fetched.document())).extract()?;
+///     CONFIG.replace(config);
+/// }
+/// ```
+///
+/// The document can also be wrapped in `Signed`, or migrated with
+/// [`Migrations`](crate::Migrations), before it is merged.
+pub struct RemoteDocument {
+    url: String,
+    service: Option<(String, worker_sys::Fetcher)>,
+}
+
+impl RemoteDocument {
+    /// Fetch the document at `url` from the Internet.
+    #[must_use]
+    pub fn origin(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            service: None,
+        }
+    }
+
+    /// Fetch the document at `url` from the worker bound as the service
+    /// binding `binding`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `binding` is not a service binding.
+    pub fn service(env: &Env, binding: &str, url: impl Into<String>) -> Result<Self, Error> {
+        let fetcher = env
+            .service(binding)
+            .map_err(|error| Error::from(format!("service binding `{binding}`: {error}")))?;
+        Ok(Self {
+            url: url.into(),
+            service: Some((binding.to_owned(), fetcher.into_rpc())),
+        })
+    }
+
+    /// Fetch the document, revalidating the one fetched last in the isolate
+    /// if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails or is answered with a status other than a
+    /// success or, to a conditional request, `304 Not Modified`.
+    pub async fn fetch(&self) -> Result<FetchedDocument, Error> {
+        let key = self.cache_key();
+        let cached = DOCUMENTS.with(|documents| documents.borrow().get(&key).cloned());
+
+        let headers = Headers::new();
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                headers
+                    .set("If-None-Match", etag)
+                    .map_err(|error| self.error(&error))?;
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                headers
+                    .set("If-Modified-Since", last_modified)
+                    .map_err(|error| self.error(&error))?;
+            }
+        }
+        let mut init = RequestInit::new();
+        init.with_headers(headers);
+        let request =
+            Request::new_with_init(&self.url, &init).map_err(|error| self.error(&error))?;
+        let mut response = self.send(request).await?;
+
+        match (response.status_code(), cached) {
+            (304, Some(cached)) => Ok(FetchedDocument {
+                document: cached.document,
+                modified: false,
+            }),
+            (200..=299, cached) => {
+                let document: Rc<str> = response
+                    .text()
+                    .await
+                    .map_err(|error| self.error(&error))?
+                    .into();
+                let header = |name| response.headers().get(name).ok().flatten();
+                let (etag, last_modified) = (header("ETag"), header("Last-Modified"));
+                let modified = cached.is_none_or(|cached| cached.document != document);
+                DOCUMENTS.with(|documents| {
+                    let mut documents = documents.borrow_mut();
+                    if etag.is_some() || last_modified.is_some() {
+                        documents.insert(
+                            key,
+                            Cached {
+                                document: Rc::clone(&document),
+                                etag,
+                                last_modified,
+                            },
+                        );
+                    } else {
+                        // Without validators there is nothing to revalidate.
+                        documents.remove(&key);
+                    }
+                });
+                Ok(FetchedDocument { document, modified })
+            }
+            (status, _) => Err(Error::from(format!(
+                "configuration document `{}` answered with status {status}",
+                self.url
+            ))),
+        }
+    }
+
+    /// Forget the document fetched last, so the next fetch is unconditional.
+    pub fn invalidate(&self) {
+        let key = self.cache_key();
+        DOCUMENTS.with(|documents| documents.borrow_mut().remove(&key));
+    }
+
+    async fn send(&self, request: Request) -> Result<Response, Error> {
+        match &self.service {
+            None => Fetch::Request(request)
+                .send()
+                .await
+                .map_err(|error| self.error(&error)),
+            Some((_, fetcher)) => {
+                let promise = fetcher
+                    .fetch(request.inner())
+                    .map_err(|error| self.error(&format!("{error:?}")))?;
+                let response = JsFuture::from(promise)
+                    .await
+                    .map_err(|error| self.error(&format!("{error:?}")))?;
+                Ok(Response::from(
+                    response.unchecked_into::<worker::web_sys::Response>(),
+                ))
+            }
+        }
+    }
+
+    fn cache_key(&self) -> String {
+        match &self.service {
+            Some((binding, _)) => format!("{binding} {}", self.url),
+            None => self.url.clone(),
+        }
+    }
+
+    fn error(&self, error: &dyn fmt::Display) -> Error {
+        Error::from(format!("configuration document `{}`: {error}", self.url))
+    }
+}
+
+impl fmt::Debug for RemoteDocument {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("RemoteDocument")
+            .field("url", &self.url)
+            .field(
+                "service",
+                &self.service.as_ref().map(|(binding, _)| binding),
+            )
+            .finish()
+    }
+}
+
+/// A document fetched by a [`RemoteDocument`].
+#[derive(Clone, Debug)]
+pub struct FetchedDocument {
+    document: Rc<str>,
+    modified: bool,
+}
+
+impl FetchedDocument {
+    /// The document.
+    #[must_use]
+    pub fn document(&self) -> &str {
+        &self.document
+    }
+
+    /// Whether the document differs from the one fetched last in the
+    /// isolate; `false` when the origin answered `304 Not Modified`.
+    #[must_use]
+    pub fn modified(&self) -> bool {
+        self.modified
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+#[cfg(any(feature = "fingerprint", feature = "test-util"))]
+use std::fmt::Write as _;
+
+use figment2::value::{Dict, Num, Value};
+
+/// Render a scalar value the way it would appear in a string binding, e.g.
+/// `3` or `true`. Returns `None` for dicts and arrays.
+pub(crate) fn scalar(value: &Value) -> Option<String> {
+    Some(match value {
+        Value::String(_, string) => string.clone(),
+        Value::Char(_, char) => char.to_string(),
+        Value::Bool(_, bool) => bool.to_string(),
+        Value::Num(_, num) => match *num {
+            Num::F32(float) => float.to_string(),
+            Num::F64(float) => float.to_string(),
+            num => num
+                .to_u128()
+                .map(|num| num.to_string())
+                .or_else(|| num.to_i128().map(|num| num.to_string()))?,
+        },
+        Value::Empty(..) => "null".to_owned(),
+        Value::Dict(..) | Value::Array(..) => return None,
+    })
+}
+
+/// Flatten `dict` into dotted keys mapped to rendered scalars, dropping
+/// `null`s so that an absent key and a `None` field compare equal.
+pub(crate) fn flatten(dict: &Dict) -> BTreeMap<String, String> {
+    fn visit(prefix: String, value: &Value, leaves: &mut BTreeMap<String, String>) {
+        match value {
+            Value::Dict(_, dict) => {
+                for (key, value) in dict {
+                    visit(join(&prefix, key), value, leaves);
+                }
+            }
+            Value::Array(_, array) => {
+                for (index, value) in array.iter().enumerate() {
+                    visit(join(&prefix, &index.to_string()), value, leaves);
+                }
+            }
+            Value::Empty(..) => {}
+            leaf => {
+                leaves.insert(prefix, scalar(leaf).unwrap_or_default());
+            }
+        }
+    }
+
+    fn join(prefix: &str, key: &str) -> String {
+        if prefix.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{prefix}.{key}")
+        }
+    }
+
+    let mut leaves = BTreeMap::new();
+    for (key, value) in dict {
+        visit(key.clone(), value, &mut leaves);
+    }
+    leaves
+}
+
+/// Encode `bytes` as lowercase hex.
+#[cfg(any(feature = "fingerprint", feature = "test-util"))]
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
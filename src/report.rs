@@ -0,0 +1,182 @@
+use std::fmt;
+
+use serde::Serialize;
+
+/// A report of how a provider resolves each of its fields, created with
+/// [`CloudflareWorkersBindings::diagnostics`](crate::CloudflareWorkersBindings::diagnostics).
+///
+/// Serialised (e.g. as the JSON body of a debugging endpoint), it lists each
+/// field's binding, where it was found, and its value, with the values of
+/// secret fields masked as [`REDACTED`](crate::REDACTED):
+///
+/// ```json
+/// {
+///   "fields": [
+///     { "field": "api_base_url", "binding": "API_BASE_URL", "source": "primary", "accessor": "var", "value": "https://api.example.com/v1" },
+///     { "field": "api_key", "binding": "API_KEY", "source": "primary", "accessor": "var", "value": "[REDACTED]" },
+///     { "field": "log_level", "binding": "LOG_LEVEL", "source": null, "accessor": null, "value": null }
+///   ],
+///   "error": null
+/// }
+/// ```
+///
+/// Displayed, it renders as a compact table, one row per field:
+///
+/// ```text
+/// field         binding       source   accessor  value
+/// api_base_url  API_BASE_URL  primary  var       https://api.example.com/v1
+/// api_key       API_KEY       primary  var       [REDACTED]
+/// log_level     LOG_LEVEL     -        -         -
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Diagnostics {
+    fields: Vec<FieldDiagnostics>,
+    error: Option<String>,
+}
+
+impl Diagnostics {
+    pub(crate) fn new(fields: Vec<FieldDiagnostics>, error: Option<String>) -> Self {
+        Self { fields, error }
+    }
+
+    /// The fields of the provider, in order.
+    #[must_use]
+    pub fn fields(&self) -> &[FieldDiagnostics] {
+        &self.fields
+    }
+
+    /// The error resolving the provider fails with, if any, e.g. for missing
+    /// required bindings.
+    #[must_use]
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<[&str; 5]> = self
+            .fields
+            .iter()
+            .map(|field| {
+                [
+                    &*field.field,
+                    &*field.binding,
+                    field.source().unwrap_or("-"),
+                    field.accessor().unwrap_or("-"),
+                    field.value().unwrap_or("-"),
+                ]
+            })
+            .collect();
+        let header = ["field", "binding", "source", "accessor", "value"];
+        let mut widths = header.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        for [cells @ .., value] in std::iter::once(&header).chain(&rows) {
+            for (cell, width) in cells.iter().zip(widths) {
+                write!(f, "{cell:width$}  ")?;
+            }
+            writeln!(f, "{value}")?;
+        }
+        if let Some(error) = &self.error {
+            writeln!(f, "error: {error}")?;
+        }
+        Ok(())
+    }
+}
+
+/// How one field of a provider resolves; see [`Diagnostics`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FieldDiagnostics {
+    field: String,
+    binding: String,
+    source: Option<String>,
+    accessor: Option<&'static str>,
+    value: Option<String>,
+}
+
+impl FieldDiagnostics {
+    pub(crate) fn new(
+        field: String,
+        binding: String,
+        found: Option<(String, &'static str, String)>,
+    ) -> Self {
+        let (source, accessor, value) = match found {
+            Some((source, accessor, value)) => (Some(source), Some(accessor), Some(value)),
+            None => (None, None, None),
+        };
+        Self {
+            field,
+            binding,
+            source,
+            accessor,
+            value,
+        }
+    }
+
+    /// The name of the field.
+    #[must_use]
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// The name of the binding the field is read from.
+    #[must_use]
+    pub fn binding(&self) -> &str {
+        &self.binding
+    }
+
+    /// The source the binding was found in: `primary`, or `fallback <n>` for
+    /// the `n`th [`fallback`](crate::CloudflareWorkersBindings::fallback).
+    /// `None` if it is not bound.
+    #[must_use]
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// The accessor the binding was read through, `var` or `secret`.
+    #[must_use]
+    pub fn accessor(&self) -> Option<&str> {
+        self.accessor
+    }
+
+    /// The value of the binding, masked as [`REDACTED`](crate::REDACTED) for
+    /// secret fields.
+    #[must_use]
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+}
+
+/// Serve `provider`'s [`diagnostics`](crate::CloudflareWorkersBindings::diagnostics)
+/// as a JSON response, for mounting at e.g. `/__config` behind the app's own
+/// authentication to debug a deployment.
+///
+/// Vars and secrets are indistinguishable on a [`worker::Env`], so only the
+/// fields the provider knows to be secret are masked: declare them with
+/// [`secret`](crate::CloudflareWorkersBindings::secret) or
+/// `#[binding(secret)]`.
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{diagnostics_response, CloudflareWorkersBindings};
+///
+/// router.get("/__config", |request, context| {
+///     authorize(&request)?;
+///     diagnostics_response(&CloudflareWorkersBindings::from_config::<Config>(&context.env))
+/// })
+/// ```
+///
+/// # Errors
+///
+/// Fails if the report cannot be serialised into a response.
+#[cfg(feature = "worker")]
+pub fn diagnostics_response(
+    provider: &crate::CloudflareWorkersBindings<'_>,
+) -> worker::Result<worker::Response> {
+    let mut response = worker::Response::from_json(&provider.diagnostics())?;
+    response.headers_mut().set("Cache-Control", "no-store")?;
+    Ok(response)
+}
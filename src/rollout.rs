@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::{BindingError, BindingSource};
+
+/// A binding source that resolves staged values of another source for one
+/// request, so a configuration change can be rolled out to a growing share
+/// of traffic.
+///
+/// A value is staged by storing a JSON document in place of the plain
+/// value, e.g. in a [`ConfigStore`](crate::ConfigStore):
+///
+/// ```json
+/// {"old": "https://v1.example.com", "new": "https://v2.example.com", "percent": 20}
+/// ```
+///
+/// Wrapping the source in a `Rollout` keyed by something stable about the
+/// request, such as a user or session id, resolves each staged value to
+/// `new` for `percent` per cent of keys and to `old` for the rest; values
+/// that are not staged documents pass through unchanged:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, ConfigStore, Rollout};
+///
+/// let kv = ConfigStore::new(&env, "CONFIG")?.load::<Config>().await?;
+/// let rollout = Rollout::new(&kv, &user_id);
+/// let config: Config = Figment::new()
+///     .merge(CloudflareWorkersBindings::from_sources::<Config>(&[&rollout, &env]))
+///     .extract_lossy()?;
+/// ```
+///
+/// The choice is deterministic: a key lands in the same bucket on every
+/// request and in every isolate, so raising `percent` only ever moves keys
+/// from `old` to `new`. Buckets are drawn separately for each binding, so
+/// the keys that see one change first are not the ones that see every
+/// change first.
+pub struct Rollout<'a> {
+    source: &'a dyn BindingSource,
+    key: String,
+}
+
+impl<'a> Rollout<'a> {
+    /// Resolve the staged values of `source` for the stable key `key`.
+    #[must_use]
+    pub fn new(source: &'a dyn BindingSource, key: impl Into<String>) -> Self {
+        Self {
+            source,
+            key: key.into(),
+        }
+    }
+
+    /// The bucket, from 0 to 99, of this rollout's key for the binding
+    /// `name`: staged values of `name` resolve to `new` when it is below
+    /// their `percent`.
+    #[must_use]
+    pub fn bucket(&self, name: &str) -> u8 {
+        u8::try_from(stable_hash(&self.key, name) % 100).unwrap_or_default()
+    }
+
+    /// `value` with a staged document resolved for the binding `name`.
+    fn resolve(&self, name: &str, value: String) -> String {
+        match serde_json::from_str::<Staged>(&value) {
+            Ok(staged) if self.bucket(name) < staged.percent.min(100) => scalar(staged.new),
+            Ok(staged) => scalar(staged.old),
+            Err(_) => value,
+        }
+    }
+}
+
+impl BindingSource for Rollout<'_> {
+    fn var(&self, name: &str) -> Result<Option<String>, BindingError> {
+        Ok(self
+            .source
+            .var(name)?
+            .map(|value| self.resolve(name, value)))
+    }
+
+    fn secret(&self, name: &str) -> Result<Option<String>, BindingError> {
+        Ok(self
+            .source
+            .secret(name)?
+            .map(|value| self.resolve(name, value)))
+    }
+
+    fn names(&self) -> Option<Vec<String>> {
+        self.source.names()
+    }
+
+    fn prefetch(&self) -> Option<HashMap<String, String>> {
+        let values = self.source.prefetch()?;
+        Some(
+            values
+                .into_iter()
+                .map(|(name, value)| {
+                    let value = self.resolve(&name, value);
+                    (name, value)
+                })
+                .collect(),
+        )
+    }
+
+    fn fetch_ms(&self) -> f64 {
+        self.source.fetch_ms()
+    }
+}
+
+/// The key is not shown; it may identify a user.
+impl std::fmt::Debug for Rollout<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Rollout").finish_non_exhaustive()
+    }
+}
+
+/// A staged value.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Staged {
+    old: serde_json::Value,
+    new: serde_json::Value,
+    percent: u8,
+}
+
+/// A hash of `key` under `salt`, the same on every platform and release.
+pub(crate) fn stable_hash(key: &str, salt: &str) -> u64 {
+    // 64-bit FNV-1a, which unlike the standard library's hashers is stable
+    // across releases and platforms.
+    key.bytes()
+        .chain([0])
+        .chain(salt.bytes())
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// `value` as a binding string: strings as they are, other values as JSON.
+fn scalar(value: serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(value) => value,
+        value => value.to_string(),
+    }
+}
@@ -0,0 +1,325 @@
+use figment2::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::CloudflareConfig;
+
+/// The Cloudflare API endpoint a [`SecretRotator`] talks to by default.
+pub const CLOUDFLARE_API: &str = "https://api.cloudflare.com/client/v4";
+
+/// Pushes new secret values to a deployed worker through the Cloudflare
+/// API, as `wrangler secret put` does, so rotation scripts can be written
+/// against the crate that defines the configuration:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::SecretRotator;
+///
+/// let rotator = SecretRotator::new(&account_id, "checkout-api", &api_token).environment("production");
+/// rotator.put_field::<Config>("api_key", &new_key)?;
+/// ```
+///
+/// The API token needs the *Workers Scripts: Edit* permission. Each call
+/// deploys the secret at once, without a new version of the worker's code.
+#[derive(Clone)]
+pub struct SecretRotator {
+    account_id: String,
+    script: String,
+    api_token: String,
+    api: String,
+    environment: Option<String>,
+}
+
+impl SecretRotator {
+    /// Rotate secrets of the worker named `script` in the account
+    /// `account_id`, authenticating with `api_token`.
+    #[must_use]
+    pub fn new(account_id: &str, script: &str, api_token: &str) -> Self {
+        Self {
+            account_id: account_id.to_owned(),
+            script: script.to_owned(),
+            api_token: api_token.to_owned(),
+            api: CLOUDFLARE_API.to_owned(),
+            environment: None,
+        }
+    }
+
+    /// Target the worker deployed for the wrangler environment
+    /// `environment`, which wrangler names `<script>-<environment>`.
+    #[must_use]
+    pub fn environment(mut self, environment: impl Into<String>) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
+
+    /// Talk to the API at `api` instead of [`CLOUDFLARE_API`], e.g. a mock
+    /// server in tests.
+    #[must_use]
+    pub fn api(mut self, api: impl Into<String>) -> Self {
+        self.api = api.into();
+        self
+    }
+
+    /// Set the secret `name` to `value`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails or the API rejects it, with the errors it
+    /// reports.
+    pub fn put(&self, name: &str, value: &str) -> Result<(), Error> {
+        let script = match &self.environment {
+            None => self.script.clone(),
+            Some(environment) => format!("{}-{environment}", self.script),
+        };
+        let url = format!(
+            "{}/accounts/{}/workers/scripts/{script}/secrets",
+            self.api.trim_end_matches('/'),
+            self.account_id,
+        );
+        let body = serde_json::to_string(&SecretBody {
+            name,
+            text: value,
+            kind: "secret_text",
+        })
+        .map_err(|error| Error::from(error.to_string()))?;
+        let agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .build()
+            .new_agent();
+        let failed = |error: &dyn std::fmt::Display| {
+            Error::from(format!("putting secret `{name}` on `{script}`: {error}"))
+        };
+        let mut response = agent
+            .put(&url)
+            .header("Authorization", &format!("Bearer {}", self.api_token))
+            .content_type("application/json")
+            .send(&body)
+            .map_err(|error| failed(&error))?;
+        let status = response.status();
+        let text = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|error| failed(&error))?;
+        let reply: Option<ApiReply> = serde_json::from_str(&text).ok();
+        match reply {
+            Some(reply) if reply.success && status.is_success() => Ok(()),
+            Some(reply) if !reply.errors.is_empty() => {
+                let errors = reply
+                    .errors
+                    .iter()
+                    .map(|error| format!("{} (code {})", error.message, error.code))
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                Err(failed(&errors))
+            }
+            _ => Err(failed(&format!("the API answered {status}"))),
+        }
+    }
+
+    /// Set the secret bound to `field` of `T` to `value`, refusing fields
+    /// that `T` does not declare secret, so a rotation script cannot drift
+    /// from the configuration it rotates.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `T` has no secret field `field`, or as [`put`](Self::put)
+    /// does.
+    pub fn put_field<T: CloudflareConfig>(&self, field: &str, value: &str) -> Result<(), Error> {
+        let binding = T::bindings()
+            .iter()
+            .find(|binding| binding.field() == field)
+            .ok_or_else(|| Error::from(format!("`{field}` is not a field of the configuration")))?;
+        if !binding.is_secret() {
+            return Err(Error::from(format!(
+                "`{field}` is not declared secret; declare it with `#[binding(secret)]`"
+            )));
+        }
+        self.put(binding.binding(), value)
+    }
+}
+
+/// Only the account and script are shown, never the token.
+impl std::fmt::Debug for SecretRotator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretRotator")
+            .field("account_id", &self.account_id)
+            .field("script", &self.script)
+            .field("environment", &self.environment)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The request body of the secrets endpoint.
+#[derive(Serialize)]
+struct SecretBody<'a> {
+    name: &'a str,
+    text: &'a str,
+    #[serde(rename = "type")]
+    kind: &'static str,
+}
+
+/// The envelope of every Cloudflare API response.
+#[derive(Deserialize)]
+struct ApiReply {
+    success: bool,
+    #[serde(default)]
+    errors: Vec<ApiError>,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    code: i64,
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        thread::{self, JoinHandle},
+    };
+
+    use super::*;
+    use crate::{CloudflareConfig, FieldBinding};
+
+    struct Config;
+
+    impl CloudflareConfig for Config {
+        fn bindings() -> &'static [FieldBinding] {
+            const BINDINGS: &[FieldBinding] = &[
+                FieldBinding::new("api_base_url", "API_BASE_URL").var(),
+                FieldBinding::new("api_key", "API_KEY").secret(),
+            ];
+            BINDINGS
+        }
+    }
+
+    /// A request received by [`serve_once`]: its request line, headers and
+    /// body.
+    struct Received {
+        line: String,
+        headers: Vec<String>,
+        body: String,
+    }
+
+    /// Answer the first request to the returned API base with `status` and
+    /// the JSON `reply`.
+    fn serve_once(status: u16, reply: &'static str) -> (String, JoinHandle<Received>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let api = format!("http://{}/client/v4", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let mut headers = Vec::new();
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                let header = header.trim_end().to_owned();
+                if header.is_empty() {
+                    break;
+                }
+                headers.push(header);
+            }
+            let length = headers
+                .iter()
+                .find_map(|header| {
+                    let (name, value) = header.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().unwrap())
+                })
+                .unwrap_or_default();
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 {status} Status\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{reply}",
+                reply.len()
+            )
+            .unwrap();
+            Received {
+                line: line.trim_end().to_owned(),
+                headers,
+                body: String::from_utf8(body).unwrap(),
+            }
+        });
+        (api, server)
+    }
+
+    #[test]
+    fn puts_the_secret_of_the_environment_script() {
+        let (api, server) = serve_once(200, r#"{"success": true, "errors": [], "result": {}}"#);
+        SecretRotator::new("account", "checkout-api", "api-token")
+            .environment("production")
+            .api(api)
+            .put_field::<Config>("api_key", "new-key")
+            .unwrap();
+
+        let received = server.join().unwrap();
+        assert_eq!(
+            received.line,
+            "PUT /client/v4/accounts/account/workers/scripts/checkout-api-production/secrets HTTP/1.1"
+        );
+        assert!(received
+            .headers
+            .iter()
+            .any(|header| header.eq_ignore_ascii_case("authorization: Bearer api-token")));
+        assert!(received
+            .headers
+            .iter()
+            .any(|header| header.eq_ignore_ascii_case("content-type: application/json")));
+        let body: serde_json::Value = serde_json::from_str(&received.body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({ "name": "API_KEY", "text": "new-key", "type": "secret_text" })
+        );
+    }
+
+    #[test]
+    fn reports_the_errors_of_a_rejected_request() {
+        let (api, server) = serve_once(
+            403,
+            r#"{"success": false, "errors": [{"code": 10000, "message": "Authentication error"}]}"#,
+        );
+        let error = SecretRotator::new("account", "checkout-api", "expired")
+            .api(api)
+            .put("API_KEY", "new-key")
+            .unwrap_err();
+        server.join().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "putting secret `API_KEY` on `checkout-api`: Authentication error (code 10000)"
+        );
+    }
+
+    #[test]
+    fn reports_the_status_of_a_reply_without_errors() {
+        let (api, server) = serve_once(502, "<html>Bad gateway</html>");
+        let error = SecretRotator::new("account", "checkout-api", "api-token")
+            .api(api)
+            .put("API_KEY", "new-key")
+            .unwrap_err();
+        server.join().unwrap();
+        assert!(
+            error
+                .to_string()
+                .starts_with("putting secret `API_KEY` on `checkout-api`: the API answered 502"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn refuses_fields_not_declared_secret() {
+        let rotator =
+            SecretRotator::new("account", "checkout-api", "api-token").api("http://127.0.0.1:9");
+        let error = rotator
+            .put_field::<Config>("api_base_url", "x")
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("is not declared secret"),
+            "{error}"
+        );
+        let error = rotator.put_field::<Config>("missing", "x").unwrap_err();
+        assert!(error.to_string().contains("is not a field"), "{error}");
+    }
+}
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use worker::{Env, Router};
+
+use crate::{extract_config, CachedConfig};
+
+/// A [`Router`] whose route data is the configuration extracted from the
+/// bindings of `env`, so handlers read it from `context.data` instead of
+/// threading the `Env` and a figment through every function:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::config_router;
+///
+/// #[event(fetch)]
+/// async fn fetch(request: Request, env: Env, _context: Context) -> Result<Response> {
+///     config_router::<Config>(&env)?
+///         .get("/", |_, context| Response::ok(&context.data.greeting))
+///         .run(request, env)
+///         .await
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Fails, with the extraction error as a [`worker::Error::RustError`], if
+/// the configuration cannot be extracted.
+pub fn config_router<'a, T: DeserializeOwned + 'static>(
+    env: &Env,
+) -> worker::Result<Router<'a, Arc<T>>> {
+    let config =
+        extract_config(env).map_err(|error| worker::Error::RustError(error.to_string()))?;
+    Ok(Router::with_data(Arc::new(config)))
+}
+
+/// A [`Router`] whose route data is the configuration cached in `cache`,
+/// extracted from the bindings of `env` by the first request of the
+/// isolate; see [`config_router`] and [`CachedConfig`].
+///
+/// ```rust,ignore
+/// static CONFIG: CachedConfig<Config> = CachedConfig::new();
+///
+/// cached_config_router(&CONFIG, &env)?
+///     .get("/", |_, context| Response::ok(&context.data.greeting))
+///     .run(request, env)
+///     .await
+/// ```
+///
+/// # Errors
+///
+/// Fails, with the extraction error as a [`worker::Error::RustError`], if
+/// the configuration is not cached and cannot be extracted.
+pub fn cached_config_router<'a, T: DeserializeOwned + 'static>(
+    cache: &CachedConfig<T>,
+    env: &Env,
+) -> worker::Result<Router<'a, Arc<T>>> {
+    let config = cache
+        .get(env)
+        .map_err(|error| worker::Error::RustError(error.to_string()))?;
+    Ok(Router::with_data(config))
+}
@@ -0,0 +1,54 @@
+//! Decoders for binary secrets, such as keys and certificates, that land
+//! directly in a [`SecretBox<[u8]>`](secrecy::SecretBox).
+//!
+//! Cloudflare bindings are strings, so binary key material is usually
+//! provisioned base64 or hex encoded. Use these functions with
+//! `#[serde(deserialize_with = "...")]` to decode such a binding without the
+//! raw bytes ever existing as a plain `Vec<u8>` in user code; intermediate
+//! buffers are zeroised.
+//!
+//! ```rust,ignore
+//! use figment2_cloudflare_workers::secret_bytes;
+//! use secrecy::SecretBox;
+//!
+//! #[derive(Deserialize)]
+//! struct Config {
+//!     #[serde(deserialize_with = "secret_bytes::base64")]
+//!     signing_key: SecretBox<[u8]>,
+//!     #[serde(deserialize_with = "secret_bytes::hex")]
+//!     webhook_secret: SecretBox<[u8]>,
+//! }
+//! ```
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use secrecy::{zeroize::Zeroizing, ExposeSecretMut, SecretBox};
+use serde::{de, Deserialize, Deserializer};
+
+/// Decode a standard base64 string into a [`SecretBox<[u8]>`].
+///
+/// # Errors
+///
+/// Fails if the value is not a string or not valid base64.
+pub fn base64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SecretBox<[u8]>, D::Error> {
+    let encoded = Zeroizing::new(String::deserialize(deserializer)?);
+    let decoded = Zeroizing::new(
+        STANDARD
+            .decode(encoded.trim())
+            .map_err(|error| de::Error::custom(format_args!("invalid base64: {error}")))?,
+    );
+    Ok(SecretBox::new(Box::from(decoded.as_slice())))
+}
+
+/// Decode a hex string into a [`SecretBox<[u8]>`].
+///
+/// # Errors
+///
+/// Fails if the value is not a string or not valid hex.
+pub fn hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SecretBox<[u8]>, D::Error> {
+    let encoded = Zeroizing::new(String::deserialize(deserializer)?);
+    let encoded = encoded.trim();
+    let mut decoded = SecretBox::new(vec![0; encoded.len() / 2].into_boxed_slice());
+    ::hex::decode_to_slice(encoded, decoded.expose_secret_mut())
+        .map_err(|error| de::Error::custom(format_args!("invalid hex: {error}")))?;
+    Ok(decoded)
+}
@@ -0,0 +1,136 @@
+use std::ops::Deref;
+
+use figment2::{
+    value::{Dict, Map},
+    Error, Metadata, Profile, Provider,
+};
+
+/// A provider held behind a shared pointer, such as a
+/// [`CloudflareWorkersBindings`](crate::CloudflareWorkersBindings) in an
+/// `Rc` or a [`Snapshot`](crate::Snapshot) in an `Arc`, as a provider of its
+/// own.
+///
+/// Figment implements [`Provider`] for references but not for `Rc` or
+/// `Arc`, and neither can this crate. Wrapping the pointer lets one
+/// configured instance be merged by value into figments that outlive the
+/// borrow, each clone of the wrapper reading through the same instance:
+///
+/// ```rust,ignore
+/// use std::rc::Rc;
+///
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, SharedProvider};
+///
+/// let provider = SharedProvider::new(Rc::new(
+///     CloudflareWorkersBindings::from_struct::<Config>(&env),
+/// ));
+/// let production = Figment::new().merge(provider.clone());
+/// let staging = Figment::from(Toml::file("staging.toml")).merge(provider);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SharedProvider<P> {
+    pointer: P,
+}
+
+impl<P> SharedProvider<P> {
+    /// Wrap `pointer`, an `Rc`, `Arc` or other pointer to a provider.
+    #[must_use]
+    pub const fn new(pointer: P) -> Self {
+        Self { pointer }
+    }
+
+    /// The pointer wrapped.
+    #[must_use]
+    pub fn into_inner(self) -> P {
+        self.pointer
+    }
+}
+
+impl<P> From<P> for SharedProvider<P> {
+    fn from(pointer: P) -> Self {
+        Self::new(pointer)
+    }
+}
+
+impl<P> Deref for SharedProvider<P> {
+    type Target = P;
+
+    fn deref(&self) -> &P {
+        &self.pointer
+    }
+}
+
+impl<P> Provider for SharedProvider<P>
+where
+    P: Deref,
+    P::Target: Provider,
+{
+    fn metadata(&self) -> Metadata {
+        self.pointer.metadata()
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        self.pointer.data()
+    }
+
+    fn profile(&self) -> Option<Profile> {
+        self.pointer.profile()
+    }
+}
+
+#[cfg(all(test, feature = "test-util"))]
+mod tests {
+    use std::{rc::Rc, sync::Arc};
+
+    use figment2::{providers::Serialized, Figment};
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{CloudflareWorkersBindings, MockBindings};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Config {
+        api_key: String,
+        max_retries: u8,
+    }
+
+    fn expected() -> Config {
+        Config {
+            api_key: "key".to_owned(),
+            max_retries: 3,
+        }
+    }
+
+    #[test]
+    fn one_provider_is_merged_into_several_figments() {
+        let bindings = MockBindings::new()
+            .with_secret("API_KEY", "key")
+            .with_var("MAX_RETRIES", "3");
+        let provider = SharedProvider::new(Rc::new(
+            CloudflareWorkersBindings::from_struct::<Config>(&bindings),
+        ));
+        let first: Config = Figment::from(provider.clone()).extract_lossy().unwrap();
+        let second: Config = Figment::from(Serialized::default("max_retries", 5))
+            .merge(provider.clone())
+            .extract_lossy()
+            .unwrap();
+        assert_eq!(first, expected());
+        assert_eq!(second, expected());
+        assert_eq!(Rc::strong_count(&provider.into_inner()), 1);
+    }
+
+    #[test]
+    fn snapshots_are_merged_from_an_arc() {
+        let bindings = MockBindings::new()
+            .with_secret("API_KEY", "key")
+            .with_var("MAX_RETRIES", "3");
+        let snapshot = Arc::new(
+            CloudflareWorkersBindings::from_struct::<Config>(&bindings)
+                .snapshot()
+                .unwrap(),
+        );
+        let provider = SharedProvider::from(Arc::clone(&snapshot));
+        assert_eq!(provider.metadata().name, snapshot.metadata().name);
+        let config: Config = Figment::from(provider).extract_lossy().unwrap();
+        assert_eq!(config, expected());
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::{Arc, PoisonError, RwLock};
+
+use figment2::{Error, Figment};
+use serde::de::DeserializeOwned;
+
+use crate::{extract_config, BindingSource};
+
+/// Configuration that can change while an isolate is alive, shared by every
+/// request it serves.
+///
+/// Where a [`CachedConfig`](crate::CachedConfig) extracts once and keeps the
+/// value, a `SharedConfig` is [`load`](Self::load)ed explicitly, e.g. at the
+/// start of a request or from a background refresh handed to
+/// `Context::wait_until`, and every load swaps the new value in atomically:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, ConfigStore, SharedConfig};
+///
+/// static CONFIG: SharedConfig<Config> = SharedConfig::new();
+///
+/// #[event(fetch)]
+/// async fn fetch(request: Request, env: Env, context: Context) -> Result<Response> {
+///     let config = match CONFIG.get() {
+///         Some(config) => config,
+///         None => CONFIG.load(&env)?,
+///     };
+///     context.wait_until(async move {
+///         let Ok(store) = ConfigStore::new(&env, "CONFIG") else { return };
+///         if let Ok(kv) = store.load::<Config>().await {
+///             let provider = CloudflareWorkersBindings::from_sources::<Config>(&[&kv, &env]);
+///             let _ = CONFIG.load_figment(&Figment::new().merge(provider));
+///         }
+///     });
+///     // ...
+/// }
+/// ```
+///
+/// [`get`](Self::get) hands out an [`Arc`], so a handler reads one
+/// consistent configuration for as long as it holds it, however many swaps
+/// happen meanwhile. A failed load leaves the current configuration in
+/// place.
+#[derive(Debug, Default)]
+pub struct SharedConfig<T> {
+    value: RwLock<Option<Arc<T>>>,
+}
+
+impl<T> SharedConfig<T> {
+    /// Create a holder with no configuration yet.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            value: RwLock::new(None),
+        }
+    }
+
+    /// The current configuration, if one has been loaded.
+    #[must_use]
+    pub fn get(&self) -> Option<Arc<T>> {
+        // A panic elsewhere cannot leave the `Option` half-written.
+        self.value
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Swap in `value` as the current configuration and return it.
+    pub fn replace(&self, value: T) -> Arc<T> {
+        let value = Arc::new(value);
+        *self.value.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::clone(&value));
+        value
+    }
+
+    /// Swap in the configuration returned by `extract`, keeping the current
+    /// one if it fails.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `extract` fails.
+    pub fn load_with(&self, extract: impl FnOnce() -> Result<T, Error>) -> Result<Arc<T>, Error> {
+        Ok(self.replace(extract()?))
+    }
+}
+
+impl<T: DeserializeOwned + 'static> SharedConfig<T> {
+    /// Swap in the configuration extracted from the bindings of `source`
+    /// with [`extract_config`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if the configuration cannot be extracted; the current one is
+    /// kept then.
+    pub fn load(&self, source: &dyn BindingSource) -> Result<Arc<T>, Error> {
+        self.load_with(|| extract_config(source))
+    }
+
+    /// Swap in the configuration extracted from `figment`, e.g. one layering
+    /// several providers. Strings are read leniently, as by
+    /// [`extract_config`], so numbers and booleans can come from bindings.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the configuration cannot be extracted; the current one is
+    /// kept then.
+    pub fn load_figment(&self, figment: &Figment) -> Result<Arc<T>, Error> {
+        self.load_with(|| figment.extract_lossy())
+    }
+}
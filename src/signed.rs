@@ -0,0 +1,151 @@
+use std::marker::PhantomData;
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use ed25519_dalek::Verifier as _;
+use figment2::{
+    providers::Format,
+    value::{Dict, Map},
+    Error, Metadata, Profile, Provider,
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::BindingSource;
+
+/// A key used to verify the signature of a configuration document.
+#[derive(Clone)]
+pub enum VerifyingKey {
+    /// An HMAC-SHA256 shared key.
+    HmacSha256(Vec<u8>),
+    /// An Ed25519 public key.
+    Ed25519(ed25519_dalek::VerifyingKey),
+}
+
+impl VerifyingKey {
+    /// Use the UTF-8 bytes of `key` as an HMAC-SHA256 key.
+    #[must_use]
+    pub fn hmac_sha256(key: impl Into<Vec<u8>>) -> Self {
+        Self::HmacSha256(key.into())
+    }
+
+    /// Decode a standard base64 Ed25519 public key.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `public_key` is not the base64 encoding of a valid 32-byte
+    /// Ed25519 public key.
+    pub fn ed25519(public_key: &str) -> Result<Self, Error> {
+        STANDARD
+            .decode(public_key.trim())
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .and_then(|bytes| ed25519_dalek::VerifyingKey::from_bytes(&bytes).ok())
+            .map(Self::Ed25519)
+            .ok_or_else(|| Error::from("invalid Ed25519 public key"))
+    }
+
+    /// Read an HMAC-SHA256 key from the `binding` secret.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the binding is missing.
+    pub fn hmac_sha256_from_binding(
+        source: &dyn BindingSource,
+        binding: &str,
+    ) -> Result<Self, Error> {
+        read_key(source, binding).map(Self::hmac_sha256)
+    }
+
+    /// Read a standard base64 Ed25519 public key from the `binding` var or
+    /// secret.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the binding is missing or does not hold a valid key.
+    pub fn ed25519_from_binding(source: &dyn BindingSource, binding: &str) -> Result<Self, Error> {
+        Self::ed25519(&read_key(source, binding)?)
+            .map_err(|_| Error::from(format!("binding `{binding}` is not an Ed25519 public key")))
+    }
+
+    /// Check that `signature`, encoded as standard base64, signs `document`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the signature is malformed or does not match.
+    pub fn verify(&self, document: &[u8], signature: &str) -> Result<(), Error> {
+        let signature = STANDARD
+            .decode(signature.trim())
+            .map_err(|_| Error::from("configuration signature is not valid base64"))?;
+        let verified = match self {
+            Self::HmacSha256(key) => Hmac::<Sha256>::new_from_slice(key)
+                .is_ok_and(|mac| mac.chain_update(document).verify_slice(&signature).is_ok()),
+            Self::Ed25519(key) => ed25519_dalek::Signature::from_slice(&signature)
+                .is_ok_and(|signature| key.verify(document, &signature).is_ok()),
+        };
+        if verified {
+            Ok(())
+        } else {
+            Err(Error::from("configuration signature verification failed"))
+        }
+    }
+}
+
+fn read_key(source: &dyn BindingSource, binding: &str) -> Result<String, Error> {
+    source
+        .secret(binding)
+        .ok()
+        .flatten()
+        .or_else(|| source.var(binding).ok().flatten())
+        .ok_or_else(|| Error::from(format!("signing key binding `{binding}` is missing")))
+}
+
+/// A [figment2] provider for a configuration document, such as one fetched
+/// from R2, KV or a remote origin, that is only merged once its signature
+/// has been verified.
+///
+/// The document is parsed with the [`Format`] `F` (e.g. `Json` or `Toml`,
+/// enabled through the corresponding [figment2] features). A missing or invalid signature makes
+/// [`Provider::data`] fail, so a compromised storage layer cannot inject
+/// configuration.
+///
+/// ```rust,ignore
+/// use figment2::providers::Json;
+/// use figment2_cloudflare_workers::{Signed, VerifyingKey};
+///
+/// let key = VerifyingKey::hmac_sha256_from_binding(&env, "CONFIG_SIGNING_KEY")?;
+/// let figment = Figment::new().merge(Signed::<Json>::new(document, signature, key));
+/// ```
+pub struct Signed<F> {
+    document: String,
+    signature: String,
+    key: VerifyingKey,
+    format: PhantomData<F>,
+}
+
+impl<F: Format> Signed<F> {
+    /// Wrap `document` and its base64 `signature`, to be verified with `key`.
+    #[must_use]
+    pub fn new(
+        document: impl Into<String>,
+        signature: impl Into<String>,
+        key: VerifyingKey,
+    ) -> Self {
+        Self {
+            document: document.into(),
+            signature: signature.into(),
+            key,
+            format: PhantomData,
+        }
+    }
+}
+
+impl<F: Format> Provider for Signed<F> {
+    fn metadata(&self) -> Metadata {
+        Metadata::named(format!("signed {} document", F::NAME))
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        self.key.verify(self.document.as_bytes(), &self.signature)?;
+        F::string(&self.document).data()
+    }
+}
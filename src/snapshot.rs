@@ -0,0 +1,211 @@
+use std::collections::BTreeSet;
+
+use figment2::{
+    value::{Dict, Map, Value},
+    Error, Metadata, Profile, Provider,
+};
+use serde::{Deserialize, Serialize, Serializer};
+#[cfg(feature = "fingerprint")]
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "fingerprint")]
+use crate::render;
+use crate::REDACTED;
+
+/// A fully resolved, owned copy of a provider's output.
+///
+/// Created with [`CloudflareWorkersBindings::snapshot`](crate::CloudflareWorkersBindings::snapshot),
+/// a snapshot implements [`Provider`] itself, so it can be replayed into a
+/// [`Figment`](figment2::Figment) — e.g. in a test reproducing a production
+/// configuration issue.
+///
+/// Unlike the provider (and [`worker::Env`] itself), a snapshot is `Send` and
+/// `Sync`, so configuration can be resolved once and then extracted inside
+/// spawned tasks or across threads:
+///
+/// ```rust,ignore
+/// let snapshot = CloudflareWorkersBindings::from_struct::<Config>(&env).snapshot()?;
+/// spawn(async move {
+///     let config: Config = Figment::from(&snapshot).extract()?;
+///     // ...
+/// });
+/// ```
+///
+/// Serialising a snapshot (e.g. to JSON with `serde_json`) masks the values
+/// of secret fields as [`REDACTED`]; a deserialised snapshot therefore
+/// replays those placeholders and should be merged under a provider that
+/// supplies the real secrets.
+///
+/// ```json
+/// {
+///   "profile": "default",
+///   "values": { "api_base_url": "https://api.example.com/v1", "api_key": "[REDACTED]" },
+///   "secrets": ["api_key"]
+/// }
+/// ```
+#[derive(Clone, PartialEq, Deserialize)]
+pub struct Snapshot {
+    profile: Profile,
+    #[serde(default)]
+    path: Option<String>,
+    values: Dict,
+    #[serde(default)]
+    secrets: BTreeSet<String>,
+}
+
+// Keep snapshots movable into spawned tasks.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Snapshot>();
+};
+
+impl Snapshot {
+    pub(crate) fn new(
+        profile: Profile,
+        path: Option<String>,
+        values: Dict,
+        secrets: BTreeSet<String>,
+    ) -> Self {
+        Self {
+            profile,
+            path,
+            values,
+            secrets,
+        }
+    }
+
+    /// The profile the values are emitted into.
+    #[must_use]
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    /// The dotted key the values are emitted under, if any; see
+    /// [`CloudflareWorkersBindings::at`](crate::CloudflareWorkersBindings::at).
+    #[must_use]
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// The resolved values, keyed by field name.
+    #[must_use]
+    pub fn values(&self) -> &Dict {
+        &self.values
+    }
+
+    /// The names of the fields whose values are secret.
+    pub fn secrets(&self) -> impl Iterator<Item = &str> {
+        self.secrets.iter().map(String::as_str)
+    }
+
+    /// Whether the value of `field` is secret.
+    #[must_use]
+    pub fn is_secret(&self, field: &str) -> bool {
+        self.secrets.contains(field)
+    }
+
+    /// A stable fingerprint of the resolved configuration, as 64 lowercase
+    /// hex digits.
+    ///
+    /// The fingerprint is a SHA-256 hash over the profile and every key and
+    /// value, in key order, so it is the same wherever the same configuration
+    /// is resolved — e.g. across colos — and can be reported from a health
+    /// endpoint. Secret values enter the hash only as their own SHA-256 hash
+    /// salted with `salt`, so a fingerprint cannot be used to test guesses of a
+    /// secret without knowing the salt. Workers being compared must use the
+    /// same salt, and fingerprint freshly taken snapshots: deserialised ones
+    /// hold placeholders in place of secret values.
+    #[cfg(feature = "fingerprint")]
+    #[must_use]
+    pub fn fingerprint(&self, salt: &[u8]) -> String {
+        fn update(hasher: &mut Sha256, bytes: &[u8]) {
+            hasher.update((bytes.len() as u64).to_be_bytes());
+            hasher.update(bytes);
+        }
+
+        let mut hasher = Sha256::new();
+        update(
+            &mut hasher,
+            self.profile.as_str().as_str().to_lowercase().as_bytes(),
+        );
+        if let Some(path) = &self.path {
+            update(&mut hasher, path.as_bytes());
+        }
+        for (key, value) in render::flatten(&self.values) {
+            update(&mut hasher, key.as_bytes());
+            let field = key.split('.').next().unwrap_or(&key);
+            if self.is_secret(field) {
+                let mut salted = Sha256::new();
+                update(&mut salted, salt);
+                update(&mut salted, value.as_bytes());
+                update(&mut hasher, &salted.finalize());
+            } else {
+                update(&mut hasher, value.as_bytes());
+            }
+        }
+        render::hex(&hasher.finalize())
+    }
+}
+
+impl Snapshot {
+    /// The values, with those of secret fields masked as [`REDACTED`].
+    fn masked_values(&self) -> Dict {
+        self.values
+            .iter()
+            .map(|(field, value)| {
+                let value = if self.is_secret(field) {
+                    Value::from(REDACTED)
+                } else {
+                    value.clone()
+                };
+                (field.clone(), value)
+            })
+            .collect()
+    }
+}
+
+impl Serialize for Snapshot {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Masked<'a> {
+            profile: &'a Profile,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            path: &'a Option<String>,
+            values: Dict,
+            secrets: &'a BTreeSet<String>,
+        }
+
+        Masked {
+            profile: &self.profile,
+            path: &self.path,
+            values: self.masked_values(),
+            secrets: &self.secrets,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Secret values are shown as [`REDACTED`], as when serialised.
+impl std::fmt::Debug for Snapshot {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("Snapshot")
+            .field("profile", &self.profile)
+            .field("path", &self.path)
+            .field("values", &self.masked_values())
+            .field("secrets", &self.secrets)
+            .finish()
+    }
+}
+
+impl Provider for Snapshot {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("Cloudflare Worker environment snapshot")
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        Ok(self
+            .profile
+            .collect(crate::nest(self.path(), self.values.clone())))
+    }
+}
@@ -0,0 +1,194 @@
+use std::{collections::HashMap, env, fmt, hash::BuildHasher};
+
+#[cfg(feature = "worker")]
+use worker::{
+    js_sys,
+    wasm_bindgen::{JsCast, JsValue},
+};
+
+/// A source of Cloudflare-style bindings: named plain-text vars and secrets.
+///
+/// [`CloudflareWorkersBindings`](crate::CloudflareWorkersBindings) reads
+/// every binding through this trait. It is implemented for [`worker::Env`]
+/// (with the default `worker` feature), for [`ProcessEnv`], and for
+/// [`HashMap<String, String>`] (which answers both var and secret lookups),
+/// so configuration logic can be exercised without a wasm runtime:
+///
+/// ```rust,ignore
+/// let bindings = HashMap::from([("API_KEY".to_owned(), "test-key".to_owned())]);
+/// let config: Config = Figment::new()
+///     .merge(CloudflareWorkersBindings::from_struct::<Config>(&bindings))
+///     .extract()?;
+/// ```
+pub trait BindingSource {
+    /// Look up the plain-text var `name`.
+    ///
+    /// Returns `Ok(None)` if nothing is bound under `name`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the binding exists but cannot be read as a string var.
+    fn var(&self, name: &str) -> Result<Option<String>, BindingError>;
+
+    /// Look up the secret `name`.
+    ///
+    /// Returns `Ok(None)` if nothing is bound under `name`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the binding exists but cannot be read as a secret.
+    fn secret(&self, name: &str) -> Result<Option<String>, BindingError>;
+
+    /// List the names of every var and secret, for sources that can
+    /// enumerate them.
+    ///
+    /// Used by [`CloudflareWorkersBindings::all`](crate::CloudflareWorkersBindings::all).
+    /// Returns `None`, the default, if the source can only look names up.
+    fn names(&self) -> Option<Vec<String>> {
+        None
+    }
+
+    /// Read every var and secret in one pass, for sources where that is
+    /// cheaper than looking each binding up.
+    ///
+    /// A provider calls this at most once per source, before its first
+    /// lookup, and answers both var and secret lookups from the returned map
+    /// instead of calling [`var`](Self::var) and [`secret`](Self::secret).
+    /// Each value is then reported as read through the accessor tried
+    /// first, so secrets go unmasked unless declared with
+    /// [`secret`](crate::CloudflareWorkersBindings::secret). Returns `None`,
+    /// the default, to have every binding looked up.
+    fn prefetch(&self) -> Option<HashMap<String, String>> {
+        None
+    }
+
+    /// The milliseconds the source spent fetching its bindings before it
+    /// could be read, for sources loaded asynchronously, such as
+    /// `KvBindings` (behind the `kv` feature).
+    ///
+    /// Reported in `LoadTimings::fetches_ms` with the `timing` feature.
+    /// Returns 0, the default, for sources read synchronously.
+    fn fetch_ms(&self) -> f64 {
+        0.0
+    }
+}
+
+/// An error reading a binding that exists but could not be read, e.g. a KV
+/// namespace looked up as a var.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BindingError {
+    message: String,
+}
+
+impl BindingError {
+    /// Create an error with the given message.
+    #[must_use]
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for BindingError {}
+
+#[cfg(feature = "worker")]
+impl BindingSource for worker::Env {
+    fn var(&self, name: &str) -> Result<Option<String>, BindingError> {
+        env_binding(self, name, worker::Env::var(self, name))
+    }
+
+    fn secret(&self, name: &str) -> Result<Option<String>, BindingError> {
+        env_binding(self, name, worker::Env::secret(self, name))
+    }
+
+    fn names(&self) -> Option<Vec<String>> {
+        Some(crate::js::string_names(self.unchecked_ref()))
+    }
+
+    fn prefetch(&self) -> Option<HashMap<String, String>> {
+        // One reflection pass over the env object replaces an accessor call
+        // (and its error path) per binding; vars and secrets are
+        // indistinguishable strings either way.
+        Some(crate::js::string_entries(self.unchecked_ref()))
+    }
+}
+
+/// Map the outcome of a [`worker::Env`] accessor, telling an unbound name
+/// apart from a binding of the wrong kind.
+#[cfg(feature = "worker")]
+fn env_binding(
+    env: &worker::Env,
+    name: &str,
+    binding: worker::Result<worker::Var>,
+) -> Result<Option<String>, BindingError> {
+    match binding {
+        Ok(binding) => Ok(Some(binding.to_string())),
+        Err(_)
+            if js_sys::Reflect::get(env, &JsValue::from_str(name))
+                .is_ok_and(|value| value.is_undefined()) =>
+        {
+            Ok(None)
+        }
+        Err(error) => Err(BindingError::new(error.to_string())),
+    }
+}
+
+impl<H: BuildHasher> BindingSource for HashMap<String, String, H> {
+    fn var(&self, name: &str) -> Result<Option<String>, BindingError> {
+        Ok(self.get(name).cloned())
+    }
+
+    fn secret(&self, name: &str) -> Result<Option<String>, BindingError> {
+        Ok(self.get(name).cloned())
+    }
+
+    fn names(&self) -> Option<Vec<String>> {
+        Some(self.keys().cloned().collect())
+    }
+}
+
+/// A [`BindingSource`] backed by the process environment ([`std::env`](mod@std::env)),
+/// answering both var and secret lookups.
+///
+/// This lets shared configuration crates built without the `worker` feature
+/// run the same extraction code on the host. On `wasm32-unknown-unknown`,
+/// where there is no process environment, every lookup misses.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProcessEnv;
+
+impl ProcessEnv {
+    fn get(name: &str) -> Result<Option<String>, BindingError> {
+        match env::var(name) {
+            Ok(value) => Ok(Some(value)),
+            Err(env::VarError::NotPresent) => Ok(None),
+            Err(error @ env::VarError::NotUnicode(_)) => Err(BindingError::new(format!(
+                "environment variable `{name}`: {error}"
+            ))),
+        }
+    }
+}
+
+impl BindingSource for ProcessEnv {
+    fn var(&self, name: &str) -> Result<Option<String>, BindingError> {
+        Self::get(name)
+    }
+
+    fn secret(&self, name: &str) -> Result<Option<String>, BindingError> {
+        Self::get(name)
+    }
+
+    fn names(&self) -> Option<Vec<String>> {
+        Some(
+            env::vars_os()
+                .filter_map(|(name, _)| name.into_string().ok())
+                .collect(),
+        )
+    }
+}
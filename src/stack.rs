@@ -0,0 +1,182 @@
+use figment2::{
+    providers::{Format, Json, Toml},
+    Error, Figment,
+};
+use futures_util::future::try_join_all;
+use serde::de::DeserializeOwned;
+use worker::Env;
+
+use crate::{CloudflareWorkersBindings, ConfigStore, Field, Source};
+
+/// A figment assembled from several Cloudflare sources in one expression,
+/// each given the precedence it usually takes:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::CloudflareStack;
+///
+/// let config: Config = CloudflareStack::new(&env)
+///     .with_bindings::<Config>()
+///     .with_kv("CONFIG")
+///     .with_r2("CONFIG_BUCKET", "app.toml")
+///     .extract()
+///     .await?;
+/// ```
+///
+/// Documents read from R2 are the base, merged in the order they are added;
+/// the vars and secrets of the fields of the [`with_bindings`](Self::with_bindings)
+/// type override them, and values kept in KV under the same binding names
+/// override those in turn, so they can be changed without a deploy. The
+/// format of a document is told by its key, which must end in `.json` or
+/// `.toml`; a missing object contributes nothing.
+///
+/// The R2 and KV reads happen concurrently, each time the stack is
+/// extracted; reading through a [`ConfigStore`] with a `max_age` instead
+/// keeps KV values in the isolate.
+#[derive(Debug)]
+pub struct CloudflareStack<'e> {
+    env: &'e Env,
+    fields: Option<Result<&'static [Field], Error>>,
+    kv: Option<String>,
+    documents: Vec<(String, String)>,
+}
+
+impl<'e> CloudflareStack<'e> {
+    /// Start a stack of the sources bound in `env`.
+    #[must_use]
+    pub fn new(env: &'e Env) -> Self {
+        Self {
+            env,
+            fields: None,
+            kv: None,
+            documents: Vec::new(),
+        }
+    }
+
+    /// Read the vars and secrets of the fields of `T`, as
+    /// [`from_struct`](CloudflareWorkersBindings::from_struct) does.
+    #[must_use]
+    pub fn with_bindings<T: DeserializeOwned + 'static>(mut self) -> Self {
+        self.fields = Some(crate::struct_fields::<T>());
+        self
+    }
+
+    /// Read the fields of the [`with_bindings`](Self::with_bindings) type
+    /// from the KV namespace bound as `binding` too, overriding their vars
+    /// and secrets.
+    #[must_use]
+    pub fn with_kv(mut self, binding: impl Into<String>) -> Self {
+        self.kv = Some(binding.into());
+        self
+    }
+
+    /// Read the document stored under `key` in the R2 bucket bound as
+    /// `binding`, under the bindings and any documents added before it.
+    #[must_use]
+    pub fn with_r2(mut self, binding: impl Into<String>, key: impl Into<String>) -> Self {
+        self.documents.push((binding.into(), key.into()));
+        self
+    }
+
+    /// Read every source and assemble the figment.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a binding is not of the kind it is read as, an R2 or KV
+    /// read fails, a document's format cannot be told from its key, KV is
+    /// read without [`with_bindings`](Self::with_bindings), or the fields
+    /// of its type cannot be discovered.
+    pub async fn figment(&self) -> Result<Figment, Error> {
+        let store = self
+            .kv
+            .as_deref()
+            .map(|binding| ConfigStore::new(self.env, binding))
+            .transpose()?;
+        let fields = match (&self.fields, &store) {
+            (Some(fields), _) => Some(fields.clone()?),
+            (None, Some(_)) => {
+                return Err(Error::from(
+                    "a stack reading KV needs the fields of `with_bindings`",
+                ))
+            }
+            (None, None) => None,
+        };
+        let keys: Vec<&str> = fields
+            .unwrap_or_default()
+            .iter()
+            .map(|field| field.binding.as_ref())
+            .collect();
+        let (documents, kv) = futures_util::future::try_join(
+            try_join_all(
+                self.documents
+                    .iter()
+                    .map(|(binding, key)| self.read_document(binding, key)),
+            ),
+            async {
+                match &store {
+                    Some(store) => store.load_keys(&keys).await.map(Some),
+                    None => Ok(None),
+                }
+            },
+        )
+        .await?;
+
+        let mut figment = Figment::new();
+        for figment_of_document in documents.into_iter().flatten() {
+            figment = figment.merge(figment_of_document);
+        }
+        if let Some(fields) = fields {
+            let provider = match &kv {
+                Some(kv) => {
+                    CloudflareWorkersBindings::with_discovered(Source::Borrowed(kv), Ok(fields))
+                        .fallback(self.env)
+                }
+                None => CloudflareWorkersBindings::with_discovered(
+                    Source::Borrowed(self.env),
+                    Ok(fields),
+                ),
+            };
+            figment = figment.merge(provider);
+        }
+        Ok(figment)
+    }
+
+    /// Read every source and extract `T` from them, lossily as
+    /// [`extract_config`](crate::extract_config) does.
+    ///
+    /// # Errors
+    ///
+    /// Fails as [`figment`](Self::figment) does, or if the sources do not
+    /// form a valid `T`.
+    pub async fn extract<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        self.figment().await?.extract_lossy()
+    }
+
+    /// The document under `key` in the R2 bucket `binding`, as a figment of
+    /// its own, or `None` if there is no such object.
+    async fn read_document(&self, binding: &str, key: &str) -> Result<Option<Figment>, Error> {
+        let error = |error: worker::Error| {
+            Error::from(format!("R2 object `{key}` in bucket `{binding}`: {error}"))
+        };
+        let parse = match key.rsplit_once('.').map(|(_, extension)| extension) {
+            Some("json") => |document: &str| Figment::from(Json::string(document)),
+            Some("toml") => |document: &str| Figment::from(Toml::string(document)),
+            _ => {
+                return Err(Error::from(format!(
+                    "cannot tell the format of R2 object `{key}`: its key must end in `.json` or `.toml`"
+                )))
+            }
+        };
+        let bucket = self
+            .env
+            .bucket(binding)
+            .map_err(|error| Error::from(format!("R2 bucket `{binding}`: {error}")))?;
+        let Some(object) = bucket.get(key).execute().await.map_err(error)? else {
+            return Ok(None);
+        };
+        let Some(body) = object.body() else {
+            return Ok(None);
+        };
+        let document = body.text().await.map_err(error)?;
+        Ok(Some(parse(&document)))
+    }
+}
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use figment2::Error;
+use serde::de::DeserializeOwned;
+use worker::{
+    wasm_bindgen::{self, prelude::wasm_bindgen, JsCast, JsValue},
+    Env,
+};
+
+use crate::ConfigCell;
+
+#[wasm_bindgen(module = "cloudflare:workers")]
+extern "C" {
+    /// The bindings of the worker, importable outside of any request.
+    #[wasm_bindgen(thread_local_v2, js_name = env)]
+    static GLOBAL_ENV: JsValue;
+}
+
+/// The worker's bindings as imported from `cloudflare:workers`, available
+/// outside of any request, e.g. in the `start` event.
+///
+/// # Errors
+///
+/// Fails outside the Workers runtime, where there are no bindings to
+/// import.
+pub fn global_env() -> Result<Env, Error> {
+    let env = GLOBAL_ENV.with(JsValue::clone);
+    if env.is_object() {
+        Ok(env.unchecked_into())
+    } else {
+        Err(Error::from(
+            "the worker's bindings cannot be imported from `cloudflare:workers`".to_owned(),
+        ))
+    }
+}
+
+/// Configuration extracted in the worker's `start` event, while the isolate
+/// starts up, rather than by the first request it serves, so extraction
+/// neither adds to that request's latency nor fails it.
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::StartupConfig;
+///
+/// static CONFIG: StartupConfig<Config> = StartupConfig::new();
+///
+/// #[event(start)]
+/// fn start() {
+///     CONFIG.start();
+/// }
+///
+/// #[event(fetch)]
+/// async fn fetch(request: Request, env: Env, _context: Context) -> Result<Response> {
+///     let config = CONFIG.get()?;
+///     // ...
+/// }
+/// ```
+///
+/// The bindings are read from [`global_env`]. A failed startup extraction
+/// is logged to the console, and every [`get`](Self::get) tries again until
+/// one succeeds, so its error surfaces on the request path as well.
+#[derive(Debug, Default)]
+pub struct StartupConfig<T> {
+    cell: ConfigCell<T>,
+}
+
+impl<T> StartupConfig<T> {
+    /// Create a configuration to extract at startup.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            cell: ConfigCell::new(),
+        }
+    }
+
+    /// The configuration, if it has been extracted.
+    #[must_use]
+    pub fn cached(&self) -> Option<Arc<T>> {
+        self.cell.cached()
+    }
+}
+
+impl<T: DeserializeOwned + 'static> StartupConfig<T> {
+    /// Extract the configuration, logging the error to the console if it
+    /// fails. Call it from the `start` event.
+    pub fn start(&self) {
+        if let Err(error) = self.get() {
+            worker::console_error!("extracting the configuration at startup failed: {error}");
+        }
+    }
+
+    /// The configuration extracted at startup, or, if that failed, extracted
+    /// now.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the configuration was not extracted at startup and cannot be
+    /// extracted now.
+    pub fn get(&self) -> Result<Arc<T>, Error> {
+        self.cell
+            .get_or_init(|| crate::extract_config(&global_env()?))
+    }
+}
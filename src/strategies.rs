@@ -0,0 +1,104 @@
+//! [`proptest`](mod@proptest) strategies for binding sets and field names, and a
+//! round-trip check for configuration types.
+//!
+//! [`assert_round_trip`] serialises a configuration value, binds each field
+//! under its derived binding name, and extracts it back through
+//! [`CloudflareWorkersBindings`], so fuzzing a configuration type surfaces
+//! fields whose binding names clash or whose values do not survive the trip
+//! through a string binding:
+//!
+//! ```rust,ignore
+//! use figment2_cloudflare_workers::strategies::assert_round_trip;
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     #[test]
+//!     fn config_round_trips(database_url in ".*", max_connections: u16) {
+//!         assert_round_trip(&Config { database_url, max_connections })?;
+//!     }
+//! }
+//! ```
+
+use std::collections::BTreeSet;
+
+use figment2::{value::Value, Figment};
+use proptest::{
+    collection::{btree_map, btree_set},
+    prelude::*,
+    test_runner::TestCaseError,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{render, CloudflareWorkersBindings, MockBindings};
+
+/// A Cloudflare-style binding name, e.g. `API_KEY`.
+pub fn binding_name() -> impl Strategy<Value = String> {
+    "[A-Z][A-Z0-9_]{0,15}"
+}
+
+/// A `snake_case` field name, e.g. `api_key`.
+pub fn field_name() -> impl Strategy<Value = String> {
+    "[a-z][a-z0-9_]{0,15}"
+}
+
+/// A set of up to `max` distinct field names.
+pub fn field_names(max: usize) -> impl Strategy<Value = BTreeSet<String>> {
+    btree_set(field_name(), 0..=max)
+}
+
+/// A set of up to `max` bindings with arbitrary values, each bound as either
+/// a var or a secret (never both, as in Cloudflare's model).
+pub fn bindings(max: usize) -> impl Strategy<Value = MockBindings> {
+    btree_map(binding_name(), (any::<String>(), any::<bool>()), 0..=max).prop_map(|bindings| {
+        bindings
+            .into_iter()
+            .fold(MockBindings::new(), |mock, (name, (value, secret))| {
+                if secret {
+                    mock.with_secret(name, value)
+                } else {
+                    mock.with_var(name, value)
+                }
+            })
+    })
+}
+
+/// Check that `value` survives the round trip `struct → binding names →
+/// provider → struct`.
+///
+/// Each field of `value` is rendered as a string and bound as a var under
+/// its derived binding name; `None` fields are left unbound. The bindings
+/// are then extracted back with [`Figment::extract_lossy`], which parses
+/// numbers and booleans out of their string form.
+///
+/// # Errors
+///
+/// Fails the test case if `value` does not serialise to a struct of scalar
+/// fields, if extraction fails, or if the extracted value differs from
+/// `value`.
+pub fn assert_round_trip<T>(value: &T) -> Result<(), TestCaseError>
+where
+    T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug + 'static,
+{
+    let Ok(Value::Dict(_, fields)) = Value::serialize(value) else {
+        return Err(TestCaseError::fail("value did not serialise to a struct"));
+    };
+
+    let mut bindings = MockBindings::new();
+    for (field, value) in fields {
+        if matches!(value, Value::Empty(..)) {
+            continue;
+        }
+        let rendered = render::scalar(&value).ok_or_else(|| {
+            TestCaseError::fail(format!(
+                "field `{field}` is not a scalar and cannot be bound"
+            ))
+        })?;
+        bindings = bindings.with_var(field.to_uppercase(), rendered);
+    }
+
+    let extracted: T = Figment::from(CloudflareWorkersBindings::from_struct::<T>(&bindings))
+        .extract_lossy()
+        .map_err(|error| TestCaseError::fail(error.to_string()))?;
+    prop_assert_eq!(&extracted, value);
+    Ok(())
+}
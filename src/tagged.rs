@@ -0,0 +1,49 @@
+use figment2::{
+    value::{Dict, Map},
+    Error, Metadata, Profile, Provider, Source,
+};
+
+use crate::MergeMode;
+
+/// One value emitted by a [`CloudflareWorkersBindings`](crate::CloudflareWorkersBindings)
+/// provider, as a provider of its own, so that figment tags it apart from
+/// the others.
+pub(crate) struct TaggedValue {
+    /// Where the value came from, such as ``secret `API_KEY` ``.
+    source: Option<String>,
+    pub(crate) mode: MergeMode,
+    data: Result<Map<Profile, Dict>, Error>,
+}
+
+impl TaggedValue {
+    pub(crate) fn new(source: String, mode: MergeMode, data: Map<Profile, Dict>) -> Self {
+        Self {
+            source: Some(source),
+            mode,
+            data: Ok(data),
+        }
+    }
+
+    /// A provider failing with `error`, for bindings that did not resolve.
+    pub(crate) fn failed(error: Error) -> Self {
+        Self {
+            source: None,
+            mode: MergeMode::Merged,
+            data: Err(error),
+        }
+    }
+}
+
+impl Provider for TaggedValue {
+    fn metadata(&self) -> Metadata {
+        let metadata = Metadata::named("Cloudflare Worker environment");
+        match &self.source {
+            Some(source) => metadata.source(Source::Custom(source.clone())),
+            None => metadata,
+        }
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        self.data.clone()
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use worker::Request;
+
+use crate::{BindingError, BindingSource};
+
+/// The placeholders [`RequestTemplated`] fills in.
+const PLACEHOLDERS: [&str; 6] = ["colo", "country", "continent", "region", "city", "hostname"];
+
+/// A [`BindingSource`] filling in placeholders in the values of another
+/// from the request being served, so one template var can configure an
+/// endpoint per region:
+///
+/// ```toml
+/// [vars]
+/// ORIGIN_URL = "https://{colo}.origin.example.com"
+/// ```
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, RequestTemplated};
+///
+/// let templated = RequestTemplated::new(&env, &request)?;
+/// let config: Config = Figment::new()
+///     .merge(CloudflareWorkersBindings::from_struct::<Config>(&templated))
+///     .extract_lossy()?;
+/// ```
+///
+/// `{colo}`, `{country}`, `{continent}`, `{region}` and `{city}` come from
+/// the request's `cf` properties, and `{hostname}` from its URL. A value
+/// using one the request does not carry, such as `{city}` for a request
+/// without geolocation, fails to resolve; other braces, as in JSON values,
+/// are left alone. The configuration extracted is specific to the request,
+/// so it cannot be kept in a [`CachedConfig`](crate::CachedConfig).
+pub struct RequestTemplated<'a> {
+    source: &'a dyn BindingSource,
+    context: HashMap<&'static str, Option<String>>,
+}
+
+impl<'a> RequestTemplated<'a> {
+    /// Fill in placeholders in the values of `source` from `request`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request URL cannot be parsed.
+    pub fn new(source: &'a dyn BindingSource, request: &Request) -> Result<Self, BindingError> {
+        let url = request
+            .url()
+            .map_err(|error| BindingError::new(format!("request URL: {error}")))?;
+        let cf = request.cf();
+        let context = HashMap::from([
+            ("colo", cf.map(worker::Cf::colo)),
+            ("country", cf.and_then(worker::Cf::country)),
+            ("continent", cf.and_then(worker::Cf::continent)),
+            ("region", cf.and_then(worker::Cf::region)),
+            ("city", cf.and_then(worker::Cf::city)),
+            ("hostname", url.host_str().map(str::to_owned)),
+        ]);
+        Ok(Self { source, context })
+    }
+
+    /// `value`, the value of the binding `name`, with its placeholders
+    /// filled in.
+    fn fill(&self, name: &str, value: &str) -> Result<String, BindingError> {
+        let mut filled = value.to_owned();
+        for placeholder in PLACEHOLDERS {
+            let pattern = format!("{{{placeholder}}}");
+            if !filled.contains(&pattern) {
+                continue;
+            }
+            let Some(Some(replacement)) = self.context.get(placeholder) else {
+                return Err(BindingError::new(format!(
+                    "binding `{name}` uses `{pattern}`, which the request does not carry"
+                )));
+            };
+            filled = filled.replace(&pattern, replacement);
+        }
+        Ok(filled)
+    }
+}
+
+impl BindingSource for RequestTemplated<'_> {
+    fn var(&self, name: &str) -> Result<Option<String>, BindingError> {
+        self.source
+            .var(name)?
+            .map(|value| self.fill(name, &value))
+            .transpose()
+    }
+
+    fn secret(&self, name: &str) -> Result<Option<String>, BindingError> {
+        self.source
+            .secret(name)?
+            .map(|value| self.fill(name, &value))
+            .transpose()
+    }
+
+    fn names(&self) -> Option<Vec<String>> {
+        self.source.names()
+    }
+
+    fn prefetch(&self) -> Option<HashMap<String, String>> {
+        // If any value fails to fill in, every lookup goes through the
+        // accessors instead, which report the error for the field it breaks.
+        self.source
+            .prefetch()?
+            .into_iter()
+            .map(|(name, value)| self.fill(&name, &value).map(|value| (name, value)))
+            .collect::<Result<_, _>>()
+            .ok()
+    }
+
+    fn fetch_ms(&self) -> f64 {
+        self.source.fetch_ms()
+    }
+}
+
+/// The request context is not shown; it locates the client.
+impl std::fmt::Debug for RequestTemplated<'_> {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("RequestTemplated")
+            .finish_non_exhaustive()
+    }
+}
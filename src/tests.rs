@@ -0,0 +1,913 @@
+//! Tests of how a provider resolves bindings, and of what it logs, traces
+//! and prints as it does.
+
+/// A source read in one pass, every value of which is secret.
+#[cfg(feature = "test-util")]
+struct Prefetched;
+
+#[cfg(feature = "test-util")]
+impl crate::BindingSource for Prefetched {
+    fn var(&self, _name: &str) -> Result<Option<String>, crate::BindingError> {
+        unreachable!("prefetched sources are not looked up")
+    }
+
+    fn secret(&self, _name: &str) -> Result<Option<String>, crate::BindingError> {
+        unreachable!("prefetched sources are not looked up")
+    }
+
+    fn prefetch(&self) -> Option<std::collections::HashMap<String, String>> {
+        Some(std::collections::HashMap::from([
+            (
+                "API_BASE_URL".to_owned(),
+                "https://api.example.com/v1".to_owned(),
+            ),
+            ("API_KEY".to_owned(), "super-secret-key".to_owned()),
+        ]))
+    }
+}
+
+#[cfg(all(feature = "log", feature = "test-util"))]
+mod log {
+    use std::sync::{Mutex, Once};
+
+    use figment2::Provider;
+    use serde::Deserialize;
+
+    use crate::{CloudflareWorkersBindings, MockBindings};
+
+    /// Every record logged so far, by any test, as its level and message.
+    static RECORDS: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+
+    struct Recorder;
+
+    impl log::Log for Recorder {
+        fn enabled(&self, _metadata: &log::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record<'_>) {
+            let message = record.args().to_string();
+            RECORDS.lock().unwrap().push((record.level(), message));
+        }
+
+        fn flush(&self) {}
+    }
+
+    /// The records logged while `run` ran that mention `field`, which must
+    /// be unique to the test so that tests running alongside do not mix.
+    fn logged(field: &str, run: impl FnOnce()) -> Vec<(log::Level, String)> {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            log::set_logger(&Recorder).unwrap();
+            log::set_max_level(log::LevelFilter::Trace);
+        });
+        run();
+        let needle = format!("`{field}`");
+        RECORDS
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, message)| message.contains(&needle))
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn resolutions_are_logged_at_debug() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Config {
+            log_base_url: String,
+            log_api_key: String,
+            unbound_timeout: Option<u32>,
+        }
+
+        let bindings = MockBindings::new()
+            .with_var("LOG_BASE_URL", "https://api.example.com/v1")
+            .with_secret("LOG_API_KEY", "super-secret-key");
+        let records = logged("log_api_key", || {
+            CloudflareWorkersBindings::from_struct::<Config>(&bindings)
+                .data()
+                .unwrap();
+        });
+        assert_eq!(
+            records,
+            [(
+                log::Level::Debug,
+                "resolved field `log_api_key` from the secret `LOG_API_KEY` (primary source)"
+                    .to_owned()
+            )]
+        );
+        let records = logged("log_base_url", || {});
+        assert_eq!(
+            records,
+            [(
+                log::Level::Debug,
+                "resolved field `log_base_url` from the var `LOG_BASE_URL` (primary source)"
+                    .to_owned()
+            )]
+        );
+        let records = logged("unbound_timeout", || {});
+        assert_eq!(
+            records,
+            [(
+                log::Level::Debug,
+                "binding `UNBOUND_TIMEOUT` for field `unbound_timeout` is not bound".to_owned()
+            )]
+        );
+        assert!(RECORDS
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|(_, message)| !message.contains("super-secret-key")));
+    }
+
+    #[test]
+    fn bindings_found_under_another_name_are_warned_about() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Config {
+            log_region: String,
+        }
+
+        let bindings = MockBindings::new().with_var("log_region", "eu");
+        let records = logged("log_region", || {
+            CloudflareWorkersBindings::from_struct::<Config>(&bindings)
+                .binding_cases(&[crate::BindingCase::Upper, crate::BindingCase::Lower])
+                .data()
+                .unwrap();
+        });
+        assert_eq!(
+            records[0],
+            (
+                log::Level::Warn,
+                "binding `LOG_REGION` for field `log_region` is not bound; found `log_region` instead"
+                    .to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn missing_required_bindings_are_warned_about() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Config {
+            log_database_url: String,
+        }
+
+        let bindings = MockBindings::new();
+        let records = logged("LOG_DATABASE_URL", || {
+            CloudflareWorkersBindings::from_struct::<Config>(&bindings)
+                .require("log_database_url")
+                .data()
+                .unwrap_err();
+        });
+        assert!(
+            records.contains(&(
+                log::Level::Warn,
+                "required bindings are missing: `LOG_DATABASE_URL`".to_owned()
+            )),
+            "{records:?}"
+        );
+    }
+}
+
+#[cfg(all(feature = "tracing", feature = "test-util"))]
+mod tracing {
+    use std::{
+        collections::BTreeMap,
+        fmt,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex,
+        },
+    };
+
+    use ::tracing::{
+        field::{Field, Visit},
+        span, Event, Level, Metadata, Subscriber,
+    };
+    use figment2::Provider;
+    use serde::Deserialize;
+
+    use super::Prefetched;
+    use crate::{CloudflareWorkersBindings, MockBindings};
+
+    /// A span opened or an event emitted: its level, its name or message,
+    /// and its other fields.
+    #[derive(Debug)]
+    struct Recorded {
+        level: Level,
+        message: String,
+        fields: BTreeMap<String, String>,
+    }
+
+    impl Recorded {
+        fn field(&self, name: &str) -> Option<&str> {
+            self.fields.get(name).map(String::as_str)
+        }
+    }
+
+    /// Records every span and event, with their fields.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        recorded: Arc<Mutex<Vec<Recorded>>>,
+        spans: Arc<AtomicU64>,
+    }
+
+    struct Fields<'f>(&'f mut BTreeMap<String, String>);
+
+    impl Visit for Fields<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_owned(), value.to_owned());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.insert(field.name().to_owned(), format!("{value:?}"));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            let mut fields = BTreeMap::new();
+            span.record(&mut Fields(&mut fields));
+            self.recorded.lock().unwrap().push(Recorded {
+                level: *span.metadata().level(),
+                message: span.metadata().name().to_owned(),
+                fields,
+            });
+            span::Id::from_u64(self.spans.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+
+        fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = BTreeMap::new();
+            event.record(&mut Fields(&mut fields));
+            let message = fields.remove("message").unwrap_or_default();
+            self.recorded.lock().unwrap().push(Recorded {
+                level: *event.metadata().level(),
+                message,
+                fields,
+            });
+        }
+
+        fn enter(&self, _span: &span::Id) {}
+
+        fn exit(&self, _span: &span::Id) {}
+    }
+
+    /// What `run` traced.
+    fn traced(run: impl FnOnce()) -> Vec<Recorded> {
+        let recorder = Recorder::default();
+        ::tracing::subscriber::with_default(recorder.clone(), run);
+        Arc::try_unwrap(recorder.recorded)
+            .unwrap()
+            .into_inner()
+            .unwrap()
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Config {
+        api_base_url: String,
+        api_key: String,
+        timeout: Option<u32>,
+    }
+
+    fn assert_no_secrets(recorded: &[Recorded]) {
+        for recorded in recorded {
+            assert!(
+                !recorded.message.contains("super-secret-key")
+                    && recorded
+                        .fields
+                        .values()
+                        .all(|value| !value.contains("super-secret-key")),
+                "{recorded:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn lookups_are_traced_within_a_span() {
+        let bindings = MockBindings::new()
+            .with_var("API_BASE_URL", "https://api.example.com/v1")
+            .with_secret("API_KEY", "super-secret-key")
+            .with_var("TIMEOUT", "soon");
+        let recorded = traced(|| {
+            let _ = CloudflareWorkersBindings::from_struct::<Config>(&bindings).data();
+        });
+        assert_no_secrets(&recorded);
+
+        let span = &recorded[0];
+        assert_eq!(
+            (span.level, span.message.as_str(), span.field("fields")),
+            (Level::DEBUG, "resolve_bindings", Some("3"))
+        );
+        let lookups: Vec<_> = recorded
+            .iter()
+            .filter(|recorded| recorded.message == "binding lookup")
+            .map(|lookup| {
+                assert_eq!(lookup.level, Level::DEBUG);
+                assert_eq!(lookup.field("source"), Some("0"));
+                assert!(lookup.field("elapsed_ms").is_some(), "{lookup:?}");
+                (
+                    lookup.field("binding").unwrap(),
+                    lookup.field("accessor").unwrap(),
+                    lookup.field("hit").unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            lookups,
+            [
+                ("API_BASE_URL", "var", "true"),
+                ("API_KEY", "var", "false"),
+                ("API_KEY", "secret", "true"),
+                ("TIMEOUT", "var", "true"),
+            ]
+        );
+    }
+
+    #[test]
+    fn prefetched_lookups_are_traced() {
+        let recorded = traced(|| {
+            let _ = CloudflareWorkersBindings::from_struct::<Config>(&Prefetched).data();
+        });
+        assert_no_secrets(&recorded);
+
+        let prefetched: Vec<_> = recorded
+            .iter()
+            .filter(|recorded| recorded.message == "prefetched bindings")
+            .collect();
+        assert_eq!(prefetched.len(), 1, "{recorded:?}");
+        assert_eq!(prefetched[0].field("bindings"), Some("2"));
+        let lookups: Vec<_> = recorded
+            .iter()
+            .filter(|recorded| recorded.message == "binding lookup (prefetched)")
+            .map(|lookup| {
+                assert_eq!(lookup.level, Level::TRACE);
+                (
+                    lookup.field("binding").unwrap(),
+                    lookup.field("hit").unwrap(),
+                )
+            })
+            .collect();
+        // A miss is looked up once per accessor.
+        assert_eq!(
+            lookups,
+            [
+                ("API_BASE_URL", "true"),
+                ("API_KEY", "true"),
+                ("TIMEOUT", "false"),
+                ("TIMEOUT", "false"),
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_lookups_are_traced_as_warnings() {
+        let bindings = MockBindings::new()
+            .with_secret("API_KEY", "super-secret-key")
+            .with_error("API_BASE_URL", "binding `API_BASE_URL` is a KV namespace");
+        let recorded = traced(|| {
+            let _ = CloudflareWorkersBindings::from_struct::<Config>(&bindings).data();
+        });
+        assert_no_secrets(&recorded);
+
+        let failed = recorded
+            .iter()
+            .find(|recorded| recorded.message == "binding lookup failed")
+            .expect("a failed lookup");
+        assert_eq!(failed.level, Level::WARN);
+        assert_eq!(failed.field("binding"), Some("API_BASE_URL"));
+        assert_eq!(
+            failed.field("error"),
+            Some("binding `API_BASE_URL` is a KV namespace")
+        );
+    }
+}
+
+#[cfg(all(feature = "console-debug", feature = "test-util"))]
+mod console_debug {
+    use std::cell::RefCell;
+
+    use figment2::Provider;
+    use serde::Deserialize;
+
+    use crate::{CloudflareWorkersBindings, MockBindings};
+
+    thread_local! {
+        /// The reports written to [`record`] on this thread.
+        static CONSOLE: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record(report: &str) {
+        CONSOLE.with(|console| console.borrow_mut().push(report.to_owned()));
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Config {
+        api_base_url: String,
+        api_key: String,
+    }
+
+    /// What merging a provider prints while `CONFIG_DEBUG` is `debug`, or
+    /// unbound if `None`.
+    fn printed(debug: Option<&str>) -> Vec<String> {
+        let mut bindings = MockBindings::new()
+            .with_var("API_BASE_URL", "https://api.example.com/v1")
+            .with_secret("API_KEY", "super-secret-key");
+        if let Some(debug) = debug {
+            bindings = bindings.with_var("CONFIG_DEBUG", debug);
+        }
+        CONSOLE.with(|console| console.borrow_mut().clear());
+        CloudflareWorkersBindings::from_struct::<Config>(&bindings)
+            .secret("api_key")
+            .console_debug("CONFIG_DEBUG")
+            .console_sink(record)
+            .data()
+            .unwrap();
+        CONSOLE.with(RefCell::take)
+    }
+
+    #[test]
+    fn the_table_is_printed_while_the_var_is_set() {
+        for debug in ["1", "true", "yes", " On "] {
+            assert_eq!(
+                printed(Some(debug)),
+                [[
+                    "CONFIG_DEBUG is set; resolved bindings:",
+                    "field         binding       source   accessor  value",
+                    "api_base_url  API_BASE_URL  primary  var       https://api.example.com/v1",
+                    "api_key       API_KEY       primary  secret    [REDACTED]",
+                ]
+                .join("\n")],
+                "{debug:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn nothing_is_printed_while_the_var_is_unset_or_off() {
+        assert!(printed(None).is_empty());
+        for debug in ["", "0", "false", "FALSE", "no", "off", " Off "] {
+            assert!(printed(Some(debug)).is_empty(), "{debug:?}");
+        }
+    }
+}
+
+#[cfg(feature = "test-util")]
+mod ranges {
+    use figment2::Provider;
+    use serde::Deserialize;
+
+    use crate::{BindingCase, CloudflareWorkersBindings, MockBindings};
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Config {
+        max_connections: u16,
+    }
+
+    fn error(
+        bindings: &MockBindings,
+        configure: impl FnOnce(CloudflareWorkersBindings<'_>) -> CloudflareWorkersBindings<'_>,
+    ) -> String {
+        configure(
+            CloudflareWorkersBindings::from_struct::<Config>(bindings)
+                .range("max_connections", 1..=500),
+        )
+        .data()
+        .unwrap_err()
+        .to_string()
+    }
+
+    #[test]
+    fn violations_name_the_binding_read() {
+        let bindings = MockBindings::new().with_var("MAX_CONNECTIONS", "5000");
+        assert_eq!(
+            error(&bindings, |provider| provider),
+            "binding `MAX_CONNECTIONS` for field `max_connections` is `5000`, but must be a number at least 1 and at most 500"
+        );
+
+        let bindings = MockBindings::new().with_var("DB_POOL_SIZE", "5000");
+        assert!(error(&bindings, |provider| provider
+            .alias("max_connections", &["DB_POOL_SIZE"]))
+        .starts_with("binding `DB_POOL_SIZE` for field `max_connections`"));
+
+        let bindings = MockBindings::new().with_var("max_connections", "0");
+        assert!(error(&bindings, |provider| provider
+            .binding_cases(&[BindingCase::Upper, BindingCase::Lower]))
+        .starts_with("binding `max_connections` for field `max_connections` is `0`"));
+    }
+}
+
+#[cfg(feature = "test-util")]
+mod magic {
+    use figment2::{
+        value::magic::{Either, RelativePathBuf, Tagged},
+        Figment,
+    };
+    use serde::Deserialize;
+
+    use crate::{CloudflareWorkersBindings, FigmentExt, MockBindings};
+
+    #[derive(Deserialize)]
+    struct Config {
+        max_retries: Either<Tagged<u8>, String>,
+        config_dir: RelativePathBuf,
+    }
+
+    fn extract(max_retries: &str) -> (Figment, Config) {
+        let bindings = MockBindings::new()
+            .with_var("MAX_RETRIES", max_retries)
+            .with_var("CONFIG_DIR", "conf/app");
+        let figment = Figment::new()
+            .merge_tagged(&CloudflareWorkersBindings::from_struct::<Config>(&bindings));
+        let config = figment.extract_lossy().unwrap();
+        (figment, config)
+    }
+
+    #[test]
+    fn integers_match_the_left_of_an_either() {
+        let (figment, config) = extract("3");
+        let Either::Left(max_retries) = config.max_retries else {
+            panic!("`3` fell through to the right");
+        };
+        assert_eq!(*max_retries, 3);
+        assert_eq!(
+            figment
+                .get_metadata(max_retries.tag())
+                .and_then(|metadata| metadata.source.as_ref())
+                .map(ToString::to_string)
+                .as_deref(),
+            Some("var `MAX_RETRIES`")
+        );
+    }
+
+    #[test]
+    fn other_values_fall_through_to_the_right() {
+        let (_, config) = extract("unlimited");
+        assert!(
+            matches!(config.max_retries, Either::Right(max_retries) if max_retries == "unlimited")
+        );
+    }
+
+    #[test]
+    fn paths_are_used_as_written() {
+        let (_, config) = extract("3");
+        assert_eq!(
+            config.config_dir.original(),
+            std::path::Path::new("conf/app")
+        );
+        assert_eq!(
+            config.config_dir.relative(),
+            std::path::Path::new("conf/app")
+        );
+    }
+}
+
+#[cfg(feature = "test-util")]
+mod field_cache {
+    use std::cell::Cell;
+
+    use serde::{
+        de::{self, Deserializer, IgnoredAny, MapAccess, Visitor},
+        Deserialize,
+    };
+
+    use crate::{extract_config, CloudflareWorkersBindings, MockBindings};
+
+    #[test]
+    fn fields_are_discovered_once_per_type() {
+        thread_local! {
+            static DESERIALIZED: Cell<usize> = const { Cell::new(0) };
+        }
+
+        /// Counts how often it is deserialised, field discovery included.
+        struct Counted;
+
+        impl<'de> Deserialize<'de> for Counted {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct CountedVisitor;
+
+                impl<'de> Visitor<'de> for CountedVisitor {
+                    type Value = Counted;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut std::fmt::Formatter<'_>,
+                    ) -> std::fmt::Result {
+                        formatter.write_str("a struct")
+                    }
+
+                    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Counted, A::Error> {
+                        while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+                        Ok(Counted)
+                    }
+                }
+
+                DESERIALIZED.with(|count| count.set(count.get() + 1));
+                deserializer
+                    .deserialize_struct("Counted", &["api_key"], CountedVisitor)
+                    .map_err(de::Error::custom)
+            }
+        }
+
+        let bindings = MockBindings::new();
+        let _ = CloudflareWorkersBindings::from_struct::<Counted>(&bindings);
+        let discovered = DESERIALIZED.with(Cell::get);
+        assert!(discovered > 0);
+        for _ in 0..3 {
+            let _ = CloudflareWorkersBindings::from_struct::<Counted>(&bindings);
+        }
+        assert_eq!(DESERIALIZED.with(Cell::get), discovered);
+    }
+
+    #[test]
+    fn same_named_types_keep_their_own_fields() {
+        let bindings = MockBindings::new()
+            .with_var("API_BASE_URL", "https://api.example.com")
+            .with_var("MAX_RETRIES", "3");
+        {
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Config {
+                api_base_url: String,
+            }
+            assert_eq!(
+                extract_config::<Config>(&bindings).unwrap(),
+                Config {
+                    api_base_url: "https://api.example.com".to_owned()
+                }
+            );
+        }
+        {
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Config {
+                max_retries: u8,
+            }
+            assert_eq!(
+                extract_config::<Config>(&bindings).unwrap(),
+                Config { max_retries: 3 }
+            );
+        }
+    }
+}
+
+#[cfg(feature = "test-util")]
+mod masking {
+    use serde::Deserialize;
+
+    use super::Prefetched;
+    use crate::{CloudflareWorkersBindings, LookupOrder, MockBindings};
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Config {
+        api_base_url: String,
+        api_key: String,
+    }
+
+    #[test]
+    fn secrets_looked_up_one_by_one_are_masked() {
+        let bindings = MockBindings::new()
+            .with_var("API_BASE_URL", "https://api.example.com/v1")
+            .with_secret("API_KEY", "super-secret-key");
+        let snapshot = CloudflareWorkersBindings::from_struct::<Config>(&bindings)
+            .snapshot()
+            .unwrap();
+        assert_eq!(snapshot.secrets().collect::<Vec<_>>(), ["api_key"]);
+    }
+
+    #[test]
+    fn prefetched_secrets_are_masked_only_when_declared() {
+        // Every value is read through whichever accessor is tried first.
+        let secrets = |order| {
+            CloudflareWorkersBindings::from_struct::<Config>(&Prefetched)
+                .lookup_order(order)
+                .snapshot()
+                .unwrap()
+                .secrets()
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        };
+        assert!(secrets(LookupOrder::VarThenSecret).is_empty());
+        assert_eq!(
+            secrets(LookupOrder::SecretThenVar),
+            ["api_base_url", "api_key"]
+        );
+
+        let snapshot = CloudflareWorkersBindings::from_struct::<Config>(&Prefetched)
+            .secret("api_key")
+            .snapshot()
+            .unwrap();
+        assert_eq!(snapshot.secrets().collect::<Vec<_>>(), ["api_key"]);
+    }
+}
+
+#[cfg(feature = "test-util")]
+mod lookup_order {
+    use figment2::Figment;
+    use serde::Deserialize;
+
+    use crate::{CloudflareWorkersBindings, LookupOrder, MockBindings};
+
+    #[derive(Deserialize)]
+    struct Config {
+        api_key: String,
+    }
+
+    #[test]
+    fn the_accessor_tried_first_wins_a_collision() {
+        let bindings = MockBindings::new()
+            .with_var("API_KEY", "stray-var")
+            .with_secret("API_KEY", "super-secret-key");
+        let read = |order| {
+            Figment::from(
+                CloudflareWorkersBindings::from_struct::<Config>(&bindings).lookup_order(order),
+            )
+            .extract::<Config>()
+            .unwrap()
+            .api_key
+        };
+        assert_eq!(read(LookupOrder::VarThenSecret), "stray-var");
+        assert_eq!(read(LookupOrder::SecretThenVar), "super-secret-key");
+    }
+}
+
+#[cfg(feature = "test-util")]
+mod declared_kinds {
+    use std::collections::HashMap;
+
+    use figment2::Provider;
+
+    use crate::{
+        BindingError, BindingSource, CloudflareConfig, CloudflareWorkersBindings, FieldBinding,
+    };
+
+    /// A source that reads its bindings in one pass yet keeps vars and
+    /// secrets apart when looked up one at a time.
+    struct Prefetching {
+        vars: HashMap<String, String>,
+        secrets: HashMap<String, String>,
+    }
+
+    impl BindingSource for Prefetching {
+        fn var(&self, name: &str) -> Result<Option<String>, BindingError> {
+            Ok(self.vars.get(name).cloned())
+        }
+
+        fn secret(&self, name: &str) -> Result<Option<String>, BindingError> {
+            Ok(self.secrets.get(name).cloned())
+        }
+
+        fn prefetch(&self) -> Option<HashMap<String, String>> {
+            Some(
+                self.vars
+                    .clone()
+                    .into_iter()
+                    .chain(self.secrets.clone())
+                    .collect(),
+            )
+        }
+    }
+
+    struct Config;
+
+    impl CloudflareConfig for Config {
+        fn bindings() -> &'static [FieldBinding] {
+            const BINDINGS: &[FieldBinding] = &[
+                FieldBinding::new("api_base_url", "API_BASE_URL").var(),
+                FieldBinding::new("api_key", "API_KEY").secret(),
+            ];
+            BINDINGS
+        }
+    }
+
+    fn resolve(vars: &[(&str, &str)], secrets: &[(&str, &str)]) -> Result<(), figment2::Error> {
+        let owned = |bindings: &[(&str, &str)]| {
+            bindings
+                .iter()
+                .map(|(name, value)| ((*name).to_owned(), (*value).to_owned()))
+                .collect()
+        };
+        let source = Prefetching {
+            vars: owned(vars),
+            secrets: owned(secrets),
+        };
+        CloudflareWorkersBindings::from_config::<Config>(&source)
+            .data()
+            .map(drop)
+    }
+
+    #[test]
+    fn declared_kinds_are_checked_on_prefetched_sources() {
+        resolve(
+            &[("API_BASE_URL", "https://api.example.com")],
+            &[("API_KEY", "key")],
+        )
+        .unwrap();
+        assert_eq!(
+            resolve(
+                &[
+                    ("API_BASE_URL", "https://api.example.com"),
+                    ("API_KEY", "key")
+                ],
+                &[]
+            )
+            .unwrap_err()
+            .to_string(),
+            "binding `API_KEY` is declared a secret but is not bound as one"
+        );
+        assert_eq!(
+            resolve(
+                &[],
+                &[
+                    ("API_BASE_URL", "https://api.example.com"),
+                    ("API_KEY", "key")
+                ]
+            )
+            .unwrap_err()
+            .to_string(),
+            "binding `API_BASE_URL` is declared a var but is not bound as one"
+        );
+    }
+}
+
+#[cfg(feature = "test-util")]
+mod field_names {
+    use std::collections::HashMap;
+
+    use figment2::Figment;
+    use serde::Deserialize;
+
+    use crate::{from_struct, CloudflareWorkersBindings, FieldNames, MockBindings};
+
+    /// Deserialised through a map, which field discovery cannot see into.
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(from = "HashMap<String, String>")]
+    struct MapConfig {
+        api_base_url: Option<String>,
+    }
+
+    impl From<HashMap<String, String>> for MapConfig {
+        fn from(mut map: HashMap<String, String>) -> Self {
+            Self {
+                api_base_url: map.remove("api_base_url"),
+            }
+        }
+    }
+
+    impl FieldNames for MapConfig {
+        fn field_names() -> &'static [&'static str] {
+            &["api_base_url"]
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct PlainConfig {
+        api_base_url: String,
+    }
+
+    fn bindings() -> MockBindings {
+        MockBindings::new().with_var("API_BASE_URL", "https://api.example.com")
+    }
+
+    #[test]
+    fn the_macro_prefers_declared_field_names() {
+        let bindings = bindings();
+        let config: MapConfig = Figment::from(from_struct!(MapConfig, &bindings))
+            .extract()
+            .unwrap();
+        assert_eq!(
+            config.api_base_url.as_deref(),
+            Some("https://api.example.com")
+        );
+        assert!(
+            Figment::from(CloudflareWorkersBindings::from_struct::<MapConfig>(
+                &bindings
+            ))
+            .extract::<MapConfig>()
+            .unwrap_err()
+            .to_string()
+            .contains("it is deserialised from a map")
+        );
+    }
+
+    #[test]
+    fn the_macro_discovers_undeclared_field_names() {
+        let bindings = bindings();
+        let config: PlainConfig = Figment::from(from_struct!(PlainConfig, &bindings))
+            .extract()
+            .unwrap();
+        assert_eq!(config.api_base_url, "https://api.example.com");
+    }
+}
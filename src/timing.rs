@@ -0,0 +1,72 @@
+use serde::Serialize;
+
+/// The wall time a provider spent loading configuration, from
+/// [`CloudflareWorkersBindings::timings`](crate::CloudflareWorkersBindings::timings),
+/// to see how much config loading contributes to cold-start latency.
+///
+/// ```rust,ignore
+/// let store = ConfigStore::new(&env, "CONFIG")?;
+/// let kv = store.load::<Config>().await?;
+/// let provider = CloudflareWorkersBindings::from_struct::<Config>(&env).fallback(&kv);
+/// let config: Config =
+///     provider.time_extraction(|| Figment::new().merge(&provider).extract())?;
+/// console_log!("{}", serde_json::to_string(&provider.timings())?);
+/// ```
+///
+/// Inside the Workers runtime the clock only advances across I/O, so the
+/// synchronous parts, looking bindings up in the `Env` and extracting, read
+/// as zero in production and only show up under `wrangler dev` or natively;
+/// the KV fetches are measured everywhere.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct LoadTimings {
+    #[serde(rename = "lookups_ms")]
+    lookups: f64,
+    #[serde(rename = "fetches_ms")]
+    fetches: f64,
+    #[serde(rename = "extraction_ms")]
+    extraction: f64,
+}
+
+impl LoadTimings {
+    /// Milliseconds spent looking bindings up in the provider's sources.
+    #[must_use]
+    pub fn lookups_ms(&self) -> f64 {
+        self.lookups
+    }
+
+    /// Milliseconds the provider's sources spent fetching their bindings
+    /// before being read, e.g. a `ConfigStore` reading
+    /// KV; see [`BindingSource::fetch_ms`](crate::BindingSource::fetch_ms).
+    #[must_use]
+    pub fn fetches_ms(&self) -> f64 {
+        self.fetches
+    }
+
+    /// Milliseconds spent in
+    /// [`time_extraction`](crate::CloudflareWorkersBindings::time_extraction)
+    /// other than looking bindings up, i.e. in figment merging and
+    /// deserialising.
+    #[must_use]
+    pub fn extraction_ms(&self) -> f64 {
+        self.extraction
+    }
+
+    /// The sum of the other timings.
+    #[must_use]
+    pub fn total_ms(&self) -> f64 {
+        self.lookups + self.fetches + self.extraction
+    }
+
+    pub(crate) fn record_lookup(&mut self, elapsed_ms: f64) {
+        self.lookups += elapsed_ms;
+    }
+
+    pub(crate) fn record_extraction(&mut self, elapsed_ms: f64) {
+        self.extraction += elapsed_ms;
+    }
+
+    pub(crate) fn with_fetches(mut self, fetches_ms: f64) -> Self {
+        self.fetches = fetches_ms;
+        self
+    }
+}
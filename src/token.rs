@@ -0,0 +1,12 @@
+/// Whether `presented` equals `expected`, comparing every byte so the time
+/// taken does not reveal how much of a guessed token was right. An empty
+/// `expected` token matches nothing.
+pub(crate) fn tokens_match(presented: &str, expected: &str) -> bool {
+    !expected.is_empty()
+        && presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
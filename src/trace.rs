@@ -0,0 +1,46 @@
+use serde::Serialize;
+
+/// The trace of a provider's resolution, serialised by
+/// [`CloudflareWorkersBindings::trace_json`](crate::CloudflareWorkersBindings::trace_json).
+#[derive(Serialize)]
+pub(crate) struct ResolutionTrace {
+    pub(crate) profile: String,
+    pub(crate) path: Option<String>,
+    pub(crate) lookup_order: &'static str,
+    /// The layers a field can be resolved from, highest precedence first.
+    pub(crate) layers: Vec<String>,
+    pub(crate) fields: Vec<FieldTrace>,
+    pub(crate) error: Option<String>,
+}
+
+/// How one field resolved.
+#[derive(Serialize)]
+pub(crate) struct FieldTrace {
+    pub(crate) field: String,
+    pub(crate) binding: String,
+    pub(crate) required: bool,
+    pub(crate) deferred: bool,
+    /// Every source and accessor tried, in order, up to the first hit.
+    pub(crate) candidates: Vec<Candidate>,
+    /// The layer the value came from, if any.
+    pub(crate) winner: Option<Winner>,
+}
+
+/// One lookup tried for a field.
+#[derive(Serialize)]
+pub(crate) struct Candidate {
+    /// The name looked up, if another casing of the field's binding.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) binding: Option<String>,
+    pub(crate) source: String,
+    pub(crate) accessor: &'static str,
+    pub(crate) hit: bool,
+}
+
+/// The layer a field's value came from: a source and the accessor that
+/// answered, or the provider's default, with no accessor.
+#[derive(Serialize)]
+pub(crate) struct Winner {
+    pub(crate) source: String,
+    pub(crate) accessor: Option<&'static str>,
+}
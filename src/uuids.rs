@@ -0,0 +1,57 @@
+//! Parsers for [UUIDs](uuid::Uuid), such as tenant or workspace
+//! identifiers.
+//!
+//! Use these functions with `#[serde(deserialize_with = "...")]` so that a
+//! binding that is not a valid UUID fails extraction, naming the value,
+//! rather than the first request that looks the identifier up:
+//!
+//! ```rust,ignore
+//! use figment2_cloudflare_workers::uuids;
+//! use uuid::Uuid;
+//!
+//! #[derive(Deserialize)]
+//! struct Config {
+//!     #[serde(deserialize_with = "uuids::uuid")]
+//!     workspace_id: Uuid,
+//!     #[serde(deserialize_with = "uuids::list")]
+//!     tenant_ids: Vec<Uuid>,
+//! }
+//! ```
+//!
+//! UUIDs are accepted hyphenated, as 32 hex digits, braced or as a
+//! `urn:uuid:` URN; surrounding whitespace is ignored.
+
+use serde::{de, Deserialize, Deserializer};
+use uuid::Uuid;
+
+/// Parse a UUID.
+///
+/// # Errors
+///
+/// Fails if the value is not a string or not a valid UUID.
+pub fn uuid<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Uuid, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse(value.trim())
+}
+
+/// Parse a comma-separated list of UUIDs, e.g.
+/// `6f1c0a1e-8d5b-4b8e-9a43-0d2f7c1e5b6a, 0b5e1f4c-...`. An empty value is
+/// an empty list.
+///
+/// # Errors
+///
+/// Fails if the value is not a string, or if any item is not a valid UUID.
+pub fn list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Uuid>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(parse)
+        .collect()
+}
+
+fn parse<E: de::Error>(value: &str) -> Result<Uuid, E> {
+    Uuid::try_parse(value)
+        .map_err(|error| E::custom(format_args!("invalid UUID `{value}`: {error}")))
+}
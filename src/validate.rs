@@ -0,0 +1,199 @@
+use figment2::{Error, Figment, Metadata, Profile};
+use serde::de::DeserializeOwned;
+
+use crate::{BindingSource, CloudflareWorkersBindings};
+
+/// Extract `T` from its bindings, as [`extract_config`](crate::extract_config)
+/// does, then run its [`validator::Validate`] rules, so a configuration that
+/// deserialises but breaks them fails extraction too:
+///
+/// ```rust,ignore
+/// use validator::Validate;
+///
+/// #[derive(Deserialize, Validate)]
+/// struct Config {
+///     #[validate(url)]
+///     api_base_url: String,
+///     #[validate(range(min = 1, max = 500))]
+///     max_connections: u16,
+/// }
+///
+/// let config: Config = figment2_cloudflare_workers::extract_validated(&env)?;
+/// ```
+///
+/// Each violation is reported as its own error, chained together, with the
+/// key path of the field and the binding it was read from, like the errors
+/// of extraction itself. Values are not shown, since the field may be
+/// secret; a rule without a message is described by its code and
+/// parameters.
+///
+/// # Errors
+///
+/// Fails if the bindings cannot be resolved, do not form a valid `T`, or
+/// break its validation rules.
+#[cfg(feature = "validator")]
+pub fn extract_validated<T: DeserializeOwned + validator::Validate + 'static>(
+    source: &dyn BindingSource,
+) -> Result<T, Error> {
+    let provider = CloudflareWorkersBindings::from_struct::<T>(source);
+    let config: T = Figment::from(&provider).extract_lossy()?;
+    let Err(errors) = config.validate() else {
+        return Ok(config);
+    };
+    let mut violations = Vec::new();
+    collect_validator_errors(&errors, &mut Vec::new(), &mut violations);
+    // `ValidationErrors` is unordered.
+    violations.sort_by(|(path, _), (other, _)| path.cmp(other));
+    Err(chain(&provider, violations))
+}
+
+/// Extract `T` from its bindings, as [`extract_validated`] does for
+/// `validator`, then run its [`garde::Validate`] rules:
+///
+/// ```rust,ignore
+/// #[derive(Deserialize, garde::Validate)]
+/// struct Config {
+///     #[garde(url)]
+///     api_base_url: String,
+///     #[garde(range(min = 1, max = 500))]
+///     max_connections: u16,
+/// }
+///
+/// let config: Config = figment2_cloudflare_workers::extract_validated_garde(&env)?;
+/// ```
+///
+/// Violations are reported in the order `garde` finds them, each with the
+/// key path of the field and the binding it was read from.
+///
+/// # Errors
+///
+/// Fails if the bindings cannot be resolved, do not form a valid `T`, or
+/// break its validation rules.
+#[cfg(feature = "garde")]
+pub fn extract_validated_garde<T>(source: &dyn BindingSource) -> Result<T, Error>
+where
+    T: DeserializeOwned + garde::Validate + 'static,
+    T::Context: Default,
+{
+    let provider = CloudflareWorkersBindings::from_struct::<T>(source);
+    let config: T = Figment::from(&provider).extract_lossy()?;
+    let Err(report) = config.validate() else {
+        return Ok(config);
+    };
+    let violations = report
+        .iter()
+        .map(|(path, error)| (garde_path(&path.to_string()), error.message().to_owned()))
+        .collect();
+    Err(chain(&provider, violations))
+}
+
+/// The components of a garde path as displayed, e.g. `servers[0].port`:
+/// each key, and each index on its own.
+#[cfg(feature = "garde")]
+fn garde_path(path: &str) -> Vec<String> {
+    path.split('.')
+        .flat_map(|segment| segment.split(['[', ']']))
+        .filter(|component| !component.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Flatten `errors`, found under `path`, into `violations` of the paths
+/// they concern.
+#[cfg(feature = "validator")]
+fn collect_validator_errors(
+    errors: &validator::ValidationErrors,
+    path: &mut Vec<String>,
+    violations: &mut Vec<(Vec<String>, String)>,
+) {
+    use validator::ValidationErrorsKind;
+
+    for (field, kind) in errors.errors() {
+        path.push(field.to_string());
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                for error in errors {
+                    violations.push((path.clone(), describe_validator_error(error)));
+                }
+            }
+            ValidationErrorsKind::Struct(errors) => {
+                collect_validator_errors(errors, path, violations);
+            }
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    path.push(index.to_string());
+                    collect_validator_errors(errors, path, violations);
+                    path.pop();
+                }
+            }
+        }
+        path.pop();
+    }
+}
+
+/// The message of `error`, or its code and parameters other than the
+/// offending value.
+#[cfg(feature = "validator")]
+fn describe_validator_error(error: &validator::ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+    let mut params: Vec<_> = error
+        .params
+        .iter()
+        .filter(|(name, _)| *name != "value")
+        .map(|(name, value)| format!("{name} = {value}"))
+        .collect();
+    if params.is_empty() {
+        return error.code.to_string();
+    }
+    params.sort_unstable();
+    format!("{} ({})", error.code, params.join(", "))
+}
+
+/// The `violations` of `provider`'s configuration as one chained error,
+/// in order.
+fn chain(
+    provider: &CloudflareWorkersBindings<'_>,
+    violations: Vec<(Vec<String>, String)>,
+) -> Error {
+    let mut errors = violations.into_iter().rev().map(|(path, message)| {
+        // Top-level fields name the binding they were read from.
+        let binding = match path.as_slice() {
+            [field] => provider
+                .fields
+                .iter()
+                .find(|candidate| candidate.name == field.as_str())
+                .map(|field| format!("binding `{}` ", field.binding)),
+            _ => None,
+        };
+        let mut error = Error::from(format!(
+            "{}failed validation: {message}",
+            binding.unwrap_or_default()
+        ));
+        error.path = path;
+        error.profile = Some(Profile::Default);
+        error.metadata = Some(Metadata::named("Cloudflare Worker environment"));
+        error
+    });
+    let first = errors.next().expect("at least one violation");
+    // Errors chained later are displayed first.
+    errors.fold(first, Error::chain)
+}
+
+#[cfg(all(test, feature = "garde"))]
+mod tests {
+    use super::garde_path;
+
+    #[test]
+    fn garde_paths_are_split_into_keys_and_indexes() {
+        assert_eq!(garde_path("port"), ["port"]);
+        assert_eq!(garde_path("database.url"), ["database", "url"]);
+        assert_eq!(
+            garde_path("servers[0].ports[12]"),
+            ["servers", "0", "ports", "12"]
+        );
+        assert_eq!(garde_path("[3]"), ["3"]);
+        assert!(garde_path("").is_empty());
+    }
+}
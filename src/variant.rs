@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+
+use serde::{de, de::DeserializeOwned, Deserialize, Deserializer};
+
+use crate::rollout::stable_hash;
+
+/// A configuration field holding the variants of an A/B experiment, each
+/// chosen for a share of requests by a stable request attribute, so
+/// experiments are configured entirely through bindings or KV.
+///
+/// The binding holds a JSON object of named variants, each with a value
+/// and an optional weight, 1 by default:
+///
+/// ```json
+/// {"control": {"value": 3}, "aggressive": {"value": 9, "weight": 3}}
+/// ```
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::Variant;
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     max_retries: Variant<u8>,
+/// }
+///
+/// let config: Config = extract_config(&env)?;
+/// let (variant, max_retries) = config.max_retries.select(&user_id);
+/// console_log!("user {user_id} is in the {variant} arm");
+/// ```
+///
+/// Here a quarter of users get the `control` value and three quarters the
+/// `aggressive` one. The choice is deterministic: a key gets the same
+/// variant on every request and in every isolate for as long as the set
+/// of variants and their weights stay the same.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Variant<T> {
+    arms: BTreeMap<String, Arm<T>>,
+    total: u64,
+}
+
+impl<T> Variant<T> {
+    /// The name and value of the variant chosen for the stable key `key`,
+    /// e.g. a user or session id.
+    #[must_use]
+    pub fn select(&self, key: &str) -> (&str, &T) {
+        let salt = self
+            .arms
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(",");
+        let mut point = stable_hash(key, &salt) % self.total;
+        let mut chosen = None;
+        for (name, arm) in &self.arms {
+            if arm.weight > 0 {
+                chosen = Some((name.as_str(), &arm.value));
+            }
+            if point < u64::from(arm.weight) {
+                break;
+            }
+            point -= u64::from(arm.weight);
+        }
+        // Deserialisation guarantees an arm of non-zero weight, so the loop
+        // always chooses one.
+        chosen.unwrap_or_else(|| unreachable!())
+    }
+
+    /// The value of the variant chosen for `key`; see
+    /// [`select`](Self::select).
+    #[must_use]
+    pub fn get(&self, key: &str) -> &T {
+        self.select(key).1
+    }
+
+    /// The names of the variants, in the order their shares are laid out.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.arms.keys().map(String::as_str)
+    }
+}
+
+/// One variant.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Arm<T> {
+    value: T,
+    #[serde(default = "default_weight")]
+    weight: u32,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for Variant<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        /// Bindings hold the variants as a JSON string; other providers may
+        /// hand them over as a map.
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr<T> {
+            Json(String),
+            Arms(BTreeMap<String, Arm<T>>),
+        }
+
+        let arms = match Repr::<T>::deserialize(deserializer)? {
+            Repr::Json(json) => serde_json::from_str(&json)
+                .map_err(|error| de::Error::custom(format!("invalid variants: {error}")))?,
+            Repr::Arms(arms) => arms,
+        };
+        let total = arms.values().map(|arm| u64::from(arm.weight)).sum();
+        if total == 0 {
+            return Err(de::Error::custom(
+                "variants need at least one variant with a non-zero weight",
+            ));
+        }
+        Ok(Self { arms, total })
+    }
+}
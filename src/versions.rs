@@ -0,0 +1,59 @@
+//! Parsers for [semver](https://semver.org/) versions and version
+//! requirements, such as a minimum supported client version.
+//!
+//! Use these functions with `#[serde(deserialize_with = "...")]` so that a
+//! binding that is not valid semver fails extraction with an error naming
+//! the value and what is wrong with it:
+//!
+//! ```rust,ignore
+//! use figment2_cloudflare_workers::versions;
+//! use semver::{Version, VersionReq};
+//!
+//! #[derive(Deserialize)]
+//! struct Config {
+//!     #[serde(deserialize_with = "versions::version")]
+//!     min_client_version: Version,
+//!     #[serde(deserialize_with = "versions::requirement")]
+//!     supported_api: VersionReq,
+//! }
+//! ```
+//!
+//! Surrounding whitespace is ignored.
+
+use semver::{Version, VersionReq};
+use serde::{de, Deserialize, Deserializer};
+
+/// Parse a semver version, such as `1.4.0` or `2.0.0-beta.1`.
+///
+/// # Errors
+///
+/// Fails if the value is not a string or not a valid semver version.
+pub fn version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Version, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    let value = value.trim();
+    Version::parse(value).map_err(|error| {
+        let hint = if value.starts_with(['v', 'V']) {
+            " (write it without the leading `v`)"
+        } else {
+            ""
+        };
+        de::Error::custom(format_args!(
+            "invalid semver version `{value}`{hint}: {error}"
+        ))
+    })
+}
+
+/// Parse a semver version requirement, such as `>=1.4, <2`.
+///
+/// # Errors
+///
+/// Fails if the value is not a string or not a valid version requirement.
+pub fn requirement<'de, D: Deserializer<'de>>(deserializer: D) -> Result<VersionReq, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    let value = value.trim();
+    VersionReq::parse(value).map_err(|error| {
+        de::Error::custom(format_args!(
+            "invalid semver requirement `{value}`: {error}"
+        ))
+    })
+}
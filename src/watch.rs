@@ -0,0 +1,130 @@
+use std::{
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use figment2::{Error, Figment};
+use serde::de::DeserializeOwned;
+use worker::Env;
+
+use crate::{clock, CloudflareWorkersBindings, ConfigStore};
+
+/// Watches a [`ConfigStore`] for changes, polling its
+/// [version key](ConfigStore::version_key) at most once per interval and
+/// handing the reloaded configuration to a callback whenever its
+/// [fingerprint](crate::Snapshot::fingerprint) changes.
+///
+/// Keep the watcher in a `static` and poll it from every request, or from a
+/// `scheduled` event, whichever is more frequent: polls within the interval
+/// of the last one return at once without reading KV.
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{ConfigStore, KvWatcher, SharedConfig};
+///
+/// static WATCHER: KvWatcher = KvWatcher::new(Duration::from_secs(30));
+/// static CONFIG: SharedConfig<Config> = SharedConfig::new();
+///
+/// #[event(fetch)]
+/// async fn fetch(request: Request, env: Env, _context: Context) -> Result<Response> {
+///     let store = ConfigStore::new(&env, "CONFIG")?;
+///     WATCHER.poll(&store, &env, |config: Config| { CONFIG.replace(config); }).await?;
+///     // ...
+/// }
+/// ```
+///
+/// On a new version, the fields of `T` are read from the store and layered
+/// over the bindings of `env`, as with
+/// [`extract_tenant`](ConfigStore::extract_tenant) but without a tenant
+/// prefix. The first poll in each isolate always calls back, with the
+/// configuration to start from; later ones only when the version moved and
+/// the resolved values differ, so touching the version alone is harmless.
+/// A store with a [`max_age`](ConfigStore::max_age) may answer that reload
+/// from values cached in the isolate, so keep its age below the interval.
+#[derive(Debug)]
+pub struct KvWatcher {
+    state: Mutex<WatchState>,
+    interval: Duration,
+}
+
+#[derive(Debug)]
+struct WatchState {
+    /// When, in milliseconds since the epoch, the last poll started.
+    polled_at: f64,
+    version: Option<u64>,
+    fingerprint: Option<String>,
+}
+
+impl KvWatcher {
+    /// Create a watcher polling at most once every `interval`.
+    #[must_use]
+    pub const fn new(interval: Duration) -> Self {
+        Self {
+            state: Mutex::new(WatchState {
+                polled_at: f64::NEG_INFINITY,
+                version: None,
+                fingerprint: None,
+            }),
+            interval,
+        }
+    }
+
+    /// Poll `store` unless the last poll was within the interval, calling
+    /// `on_change` with the reloaded configuration if it changed.
+    ///
+    /// Returns whether `on_change` was called.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a KV read fails or the layered bindings do not form a valid
+    /// `T`; the version is then not recorded, so the next poll past the
+    /// interval tries again.
+    pub async fn poll<T: DeserializeOwned + 'static>(
+        &self,
+        store: &ConfigStore,
+        env: &Env,
+        on_change: impl FnOnce(T),
+    ) -> Result<bool, Error> {
+        let now = clock::now_ms();
+        let seen = {
+            let mut state = self.lock();
+            if now < state.polled_at + self.interval.as_secs_f64() * 1000.0 {
+                return Ok(false);
+            }
+            // Concurrent requests within the interval skip the poll rather
+            // than read KV again.
+            state.polled_at = now;
+            state.version
+        };
+
+        let version = store.version().await?;
+        if seen == Some(version) {
+            return Ok(false);
+        }
+        let kv = store.load::<T>().await?;
+        let snapshot = CloudflareWorkersBindings::from_sources::<T>(&[&kv, env]).snapshot()?;
+        // The fingerprint never leaves the isolate, so it needs no salt.
+        let fingerprint = snapshot.fingerprint(&[]);
+        let changed = self.lock().fingerprint.as_ref() != Some(&fingerprint);
+        if changed {
+            on_change(Figment::from(snapshot).extract_lossy()?);
+        }
+        let mut state = self.lock();
+        state.version = Some(version);
+        state.fingerprint = Some(fingerprint);
+        Ok(changed)
+    }
+
+    /// Forget the last version and poll, so the next poll reloads and calls
+    /// back.
+    pub fn reset(&self) {
+        let mut state = self.lock();
+        state.polled_at = f64::NEG_INFINITY;
+        state.version = None;
+        state.fingerprint = None;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, WatchState> {
+        // A panic elsewhere cannot leave the state half-written.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
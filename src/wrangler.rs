@@ -0,0 +1,286 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use figment2::{
+    providers::{Format, Json, Serialized},
+    value::{Dict, Map},
+    Error, Metadata, Profile, Provider,
+};
+use serde::Deserialize;
+
+use crate::{BindingError, BindingSource, CloudflareConfig, FieldBinding};
+
+/// A [`BindingSource`] reading the JSON file format consumed by
+/// `wrangler secret bulk`: a flat object mapping secret names to values.
+///
+/// Every name in the file resolves as a secret and var lookups always miss,
+/// so tests and local tools resolve exactly the secrets a deployment would
+/// push:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{CloudflareWorkersBindings, SecretsFile};
+///
+/// // secrets.json: { "API_KEY": "test-key", "DATABASE_URL": "postgres://localhost" }
+/// let secrets = SecretsFile::read("secrets.json")?;
+/// let config: Config = Figment::new()
+///     .merge(CloudflareWorkersBindings::from_struct::<Config>(&secrets))
+///     .extract()?;
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SecretsFile {
+    secrets: BTreeMap<String, String>,
+}
+
+impl SecretsFile {
+    /// Parse secrets from the contents of a `wrangler secret bulk` file.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `json` is not an object whose values are all strings.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        Json::from_str(json)
+            .map(|secrets| Self { secrets })
+            .map_err(|error| Error::from(format!("invalid secrets file: {error}")))
+    }
+
+    /// Read secrets from the `wrangler secret bulk` file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file cannot be read, or is not an object whose values are
+    /// all strings.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::from_json(&read(path.as_ref(), "secrets file")?)
+    }
+
+    /// The secret names and values in the file, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.secrets
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+}
+
+impl BindingSource for SecretsFile {
+    fn var(&self, _name: &str) -> Result<Option<String>, BindingError> {
+        Ok(None)
+    }
+
+    fn secret(&self, name: &str) -> Result<Option<String>, BindingError> {
+        Ok(self.secrets.get(name).cloned())
+    }
+
+    fn names(&self) -> Option<Vec<String>> {
+        Some(self.secrets.keys().cloned().collect())
+    }
+}
+
+/// The vars declared in a `wrangler.toml`, at the top level and for each
+/// `[env.<name>]` environment.
+///
+/// Wrangler does not inherit `[vars]` into environments, so each one
+/// declares its vars in full.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct WranglerToml {
+    #[serde(default)]
+    vars: Dict,
+    #[serde(default, rename = "env")]
+    environments: BTreeMap<String, WranglerEnvironment>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct WranglerEnvironment {
+    #[serde(default)]
+    vars: Dict,
+}
+
+impl WranglerToml {
+    /// Parse the contents of a `wrangler.toml`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `toml` is not valid TOML, or its `vars` tables are malformed.
+    pub fn from_toml(toml: &str) -> Result<Self, Error> {
+        toml::from_str(toml).map_err(|error| Error::from(format!("invalid wrangler.toml: {error}")))
+    }
+
+    /// Read the `wrangler.toml` at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file cannot be read or parsed.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::from_toml(&read(path.as_ref(), "wrangler.toml")?)
+    }
+
+    /// The vars of `environment`, or the top-level `[vars]` for `None`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `environment` is not declared.
+    pub fn vars(&self, environment: Option<&str>) -> Result<&Dict, Error> {
+        match environment {
+            None => Ok(&self.vars),
+            Some(name) => self
+                .environments
+                .get(name)
+                .map(|environment| &environment.vars)
+                .ok_or_else(|| {
+                    Error::from(format!("wrangler.toml declares no `{name}` environment"))
+                }),
+        }
+    }
+
+    /// The bindings of `T` that are required but not declared as vars of
+    /// `environment`.
+    ///
+    /// Fields declared secret or optional are not checked, since secrets are
+    /// provisioned with `wrangler secret put` rather than declared.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `environment` is not declared.
+    pub fn missing_bindings<T: CloudflareConfig>(
+        &self,
+        environment: Option<&str>,
+    ) -> Result<Vec<&'static str>, Error> {
+        let vars = self.vars(environment)?;
+        Ok(T::bindings()
+            .iter()
+            .filter(|binding| !binding.is_secret() && !binding.is_optional())
+            .map(FieldBinding::binding)
+            .filter(|binding| !vars.contains_key(*binding))
+            .collect())
+    }
+}
+
+/// Fail the build if a required binding of `T` is not declared as a var of
+/// `environment` in the `wrangler.toml` at `path`; see
+/// [`WranglerToml::missing_bindings`].
+///
+/// Call it from a build script, which also reruns when the file changes.
+/// A build script cannot use the types of the crate it builds, so declare
+/// the configuration in a module the crate and the build script share:
+///
+/// ```rust,ignore
+/// // build.rs, with `figment2-cloudflare-workers` (features `derive` and
+/// // `wrangler`) and `serde` as build dependencies.
+/// #[path = "src/config.rs"]
+/// mod config;
+///
+/// fn main() {
+///     figment2_cloudflare_workers::check_wrangler_toml::<config::Config>(
+///         "wrangler.toml",
+///         Some("production"),
+///     );
+/// }
+/// ```
+///
+/// # Panics
+///
+/// Panics, failing the build, if the file cannot be read, `environment` is
+/// not declared, or bindings are missing.
+#[track_caller]
+pub fn check_wrangler_toml<T: CloudflareConfig>(path: impl AsRef<Path>, environment: Option<&str>) {
+    let path = path.as_ref();
+    println!("cargo::rerun-if-changed={}", path.display());
+    let missing = WranglerToml::read(path)
+        .and_then(|manifest| manifest.missing_bindings::<T>(environment))
+        .unwrap_or_else(|error| panic!("{error}"));
+    assert!(
+        missing.is_empty(),
+        "`{}` does not declare the required bindings {} in the {} vars",
+        path.display(),
+        missing
+            .iter()
+            .map(|binding| format!("`{binding}`"))
+            .collect::<Vec<_>>()
+            .join(", "),
+        environment.map_or_else(|| "top-level".to_owned(), |name| format!("`{name}`")),
+    );
+}
+
+/// Embed the vars a `wrangler.toml` declares as a [`WranglerDefaults`]
+/// provider, so local binaries and tests share the deployment's default
+/// values without reading the file at runtime.
+///
+/// The path is relative to the crate root (`CARGO_MANIFEST_DIR`), where
+/// `wrangler.toml` usually lives, and `env = "..."` selects an
+/// `[env.<name>]` environment instead of the top-level `[vars]`:
+///
+/// ```rust,ignore
+/// use figment2_cloudflare_workers::{wrangler_defaults, CloudflareWorkersBindings};
+///
+/// let config: Config = Figment::from(wrangler_defaults!("wrangler.toml", env = "production"))
+///     .merge(CloudflareWorkersBindings::from_struct::<Config>(&env))
+///     .extract()?;
+/// ```
+#[macro_export]
+macro_rules! wrangler_defaults {
+    ($path:literal) => {
+        $crate::WranglerDefaults::embedded(
+            ::core::include_str!(::core::concat!(
+                ::core::env!("CARGO_MANIFEST_DIR"),
+                "/",
+                $path
+            )),
+            ::core::option::Option::None,
+        )
+    };
+    ($path:literal, env = $environment:literal) => {
+        $crate::WranglerDefaults::embedded(
+            ::core::include_str!(::core::concat!(
+                ::core::env!("CARGO_MANIFEST_DIR"),
+                "/",
+                $path
+            )),
+            ::core::option::Option::Some($environment),
+        )
+    };
+}
+
+/// The vars of an embedded `wrangler.toml`, created by [`wrangler_defaults!`].
+///
+/// Like [`Serialized::defaults`], the provider emits the vars into the
+/// default profile, each under its lowercased binding name so that its keys
+/// line up with the fields
+/// [`from_struct`](crate::CloudflareWorkersBindings::from_struct) reads.
+/// Merge the worker's bindings on top to let deployed values win.
+#[derive(Clone, Copy, Debug)]
+pub struct WranglerDefaults {
+    toml: &'static str,
+    environment: Option<&'static str>,
+}
+
+impl WranglerDefaults {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn embedded(toml: &'static str, environment: Option<&'static str>) -> Self {
+        Self { toml, environment }
+    }
+}
+
+impl Provider for WranglerDefaults {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("wrangler.toml defaults")
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        let manifest = WranglerToml::from_toml(self.toml)?;
+        let vars: Dict = manifest
+            .vars(self.environment)?
+            .iter()
+            .map(|(binding, value)| (binding.to_lowercase(), value.clone()))
+            .collect();
+        Serialized::defaults(vars).data()
+    }
+}
+
+/// Read the file at `path`, describing it as `what` in errors.
+fn read(path: &Path, what: &str) -> Result<String, Error> {
+    fs::read_to_string(path).map_err(|error| {
+        Error::from(format!(
+            "failed to read {what} `{}`: {error}",
+            path.display()
+        ))
+    })
+}
@@ -0,0 +1,16 @@
+// Fail the build if `wrangler.toml` stops declaring a var the declared
+// configurations require.
+#[allow(dead_code)]
+#[path = "src/declared.rs"]
+mod declared;
+
+fn main() {
+    figment2_cloudflare_workers::check_wrangler_toml::<declared::DerivedConfig>(
+        "wrangler.toml",
+        None,
+    );
+    figment2_cloudflare_workers::check_wrangler_toml::<declared::DeclaredKindsConfig>(
+        "wrangler.toml",
+        None,
+    );
+}
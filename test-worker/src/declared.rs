@@ -0,0 +1,25 @@
+//! Configuration with declared bindings, shared with the build script,
+//! which checks it against `wrangler.toml`.
+
+use figment2_cloudflare_workers::CloudflareConfig;
+use serde::{Deserialize, Serialize};
+
+/// Bindings declared at compile time, one of them under another name.
+#[derive(Deserialize, Serialize, CloudflareConfig)]
+pub struct DerivedConfig {
+    api_base_url: String,
+    #[binding(name = "API_KEY", secret)]
+    token: String,
+    #[serde(rename = "retries")]
+    #[binding(name = "MAX_RETRIES")]
+    max_retries: String,
+}
+
+/// Bindings declared as a var and a secret.
+#[derive(Deserialize, Serialize, CloudflareConfig)]
+pub struct DeclaredKindsConfig {
+    #[binding(var)]
+    api_base_url: String,
+    #[binding(secret)]
+    api_key: String,
+}
@@ -1,8 +1,31 @@
-use figment2::Figment;
-use figment2_cloudflare_workers::CloudflareWorkersBindings;
+mod declared;
+
+use std::collections::HashMap;
+
+use figment2::{
+    Figment,
+    providers::{Format, Json},
+    value::magic::{Either, RelativePathBuf, Tagged},
+};
+use figment2_cloudflare_workers::{
+    AdminEndpoint, Audit, AuditEvent, BindingCase, BindingSource, CachedConfig, Canary,
+    ChangeNotifier, CloudflareStack, CloudflareWorkersBindings, Config, ConfigCell, ConfigHub,
+    ConfigLayer, ConfigStore, D1ConfigStore, FailureAnalytics, FeatureFlags, FieldNames,
+    FigmentExt, HeaderOverrides, HubClient, Interpolated, JsBindings, JsonBinding, KvWatcher,
+    LookupOrder, Migrations, MockBindings, PagesEnv, QueryOverrides, Redacted, RemoteDocument,
+    RequestTemplated, RetryPolicy, Rollout, SharedConfig, Signed, Snapshot, StartupConfig, Variant,
+    VerifyingKey, assert_config_matches, cached_config_router, config_router, describe,
+    diagnostics_response, extract_config, extract_validated, extract_validated_garde, from_env,
+    secret_bytes, uuids, versions, wrangler_defaults,
+};
+use secrecy::{ExposeSecret, SecretBox};
 use serde::{Deserialize, Serialize};
+use tower_layer::Layer as _;
+use tower_service::Service as _;
 use worker::*;
 
+use crate::declared::{DeclaredKindsConfig, DerivedConfig};
+
 /// All fields required — tests that every binding is read.
 #[derive(Deserialize, Serialize)]
 struct FullConfig {
@@ -19,12 +42,226 @@ struct PartialConfig {
     api_key: Option<String>,
 }
 
+/// Deserialised through a map, which field discovery cannot see into.
+#[derive(Deserialize, Serialize, FieldNames)]
+#[serde(try_from = "HashMap<String, String>")]
+struct MapConfig {
+    api_base_url: String,
+    max_retries: String,
+}
+
+impl TryFrom<HashMap<String, String>> for MapConfig {
+    type Error = String;
+
+    fn try_from(mut map: HashMap<String, String>) -> std::result::Result<Self, Self::Error> {
+        let mut take = |name: &str| map.remove(name).ok_or(format!("missing `{name}`"));
+        Ok(Self {
+            api_base_url: take("api_base_url")?,
+            max_retries: take("max_retries")?,
+        })
+    }
+}
+
+/// Untagged, so deserialised from any self-describing value.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum UntaggedConfig {
+    Remote { api_base_url: String },
+    Local { local_path: String },
+}
+
 /// Single required field.
 #[derive(Deserialize, Serialize)]
 struct SingleConfig {
     api_base_url: String,
 }
 
+/// A larger configuration with a section populated from bindings.
+#[derive(Deserialize, Serialize)]
+struct AppConfig {
+    name: String,
+    service: SingleConfig,
+}
+
+/// Typed fields parsed from string bindings.
+#[derive(Deserialize, Serialize)]
+struct TypedConfig {
+    api_base_url: String,
+    max_retries: u8,
+}
+
+/// `MAX_RETRIES` read as the wrong type.
+#[derive(Deserialize)]
+struct MistypedConfig {
+    #[allow(dead_code)]
+    max_retries: bool,
+}
+
+/// Configuration with feature flags extracted into it.
+#[derive(Deserialize, Serialize)]
+struct FlaggedConfig {
+    max_retries: u8,
+    flags: Flags,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Flags {
+    new_checkout: bool,
+    dark_mode: bool,
+    checkout_layout: String,
+}
+
+/// An experiment over the retry count.
+#[derive(Deserialize)]
+struct ExperimentConfig {
+    max_retries: Variant<u8>,
+}
+
+/// Required, optional and defaulted fields, for describing.
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct DescribedConfig {
+    api_base_url: String,
+    max_retries: u8,
+    api_key: Option<String>,
+    #[serde(default)]
+    verbose: bool,
+}
+
+/// Encrypted var decrypted with a key from a secret.
+#[derive(Deserialize, Serialize)]
+struct EncryptedConfig {
+    api_base_url: String,
+    api_token: String,
+}
+
+/// Binary secrets decoded straight into `SecretBox<[u8]>`.
+#[derive(Deserialize)]
+struct BinaryConfig {
+    #[serde(deserialize_with = "secret_bytes::base64")]
+    base64_key: SecretBox<[u8]>,
+    #[serde(deserialize_with = "secret_bytes::hex")]
+    hex_key: SecretBox<[u8]>,
+}
+
+/// Semver fields, parsed with clear errors.
+#[derive(Deserialize)]
+struct VersionConfig {
+    #[serde(deserialize_with = "versions::version")]
+    min_client_version: semver::Version,
+    #[serde(deserialize_with = "versions::requirement")]
+    supported_api: semver::VersionReq,
+}
+
+/// UUID fields, validated at extraction.
+#[derive(Deserialize)]
+struct TenantConfig {
+    #[serde(deserialize_with = "uuids::uuid")]
+    workspace_id: uuid::Uuid,
+    #[serde(deserialize_with = "uuids::list")]
+    tenant_ids: Vec<uuid::Uuid>,
+}
+
+/// Typed fields with `validator` rules the bound values break.
+#[derive(Deserialize, validator::Validate)]
+struct ValidatedConfig {
+    #[validate(length(max = 10))]
+    api_base_url: String,
+    #[validate(range(min = 1, max = 2, message = "at most two retries"))]
+    max_retries: u8,
+}
+
+/// The same rules, written for `garde`.
+#[derive(Deserialize, garde::Validate)]
+struct GardeConfig {
+    #[garde(length(max = 10))]
+    api_base_url: String,
+    #[garde(range(min = 1, max = 2))]
+    max_retries: u8,
+}
+
+/// Fields of figment's magic types.
+#[derive(Deserialize)]
+struct MagicConfig {
+    api_base_url: Tagged<String>,
+    max_retries: Either<Tagged<u8>, String>,
+    #[serde(default)]
+    config_dir: Option<RelativePathBuf>,
+}
+
+/// A list gathered from every layer rather than replaced by each.
+#[derive(Deserialize, Serialize)]
+struct OriginsConfig {
+    allowed_origins: Vec<String>,
+    max_retries: u8,
+}
+
+/// A binding only looked up, and required, in production.
+#[derive(Deserialize, Serialize)]
+struct ProfiledConfig {
+    api_base_url: String,
+    sentry_dsn: Option<String>,
+}
+
+/// A JSON document and its HMAC-SHA256 signature under `SIGNING_KEY`.
+const SIGNED_DOCUMENT: &str = r#"{"api_base_url":"https://signed.example.com"}"#;
+const DOCUMENT_SIGNATURE: &str = "sKyzpvAqp5+p6o9Sa9hCM7t8VE6oIUJgnq8co9zvY0Y=";
+
+/// A config hub, for following pushed changes.
+#[durable_object]
+pub struct ConfigObject {
+    hub: ConfigHub,
+}
+
+impl DurableObject for ConfigObject {
+    fn new(state: State, _environment: Env) -> Self {
+        Self {
+            hub: ConfigHub::new(state),
+        }
+    }
+
+    async fn fetch(&self, request: Request) -> Result<Response> {
+        self.hub.fetch(request).await
+    }
+}
+
+/// Configuration extracted while the isolate starts up.
+static STARTUP_CONFIG: StartupConfig<TypedConfig> = StartupConfig::new();
+
+#[event(start)]
+fn start() {
+    STARTUP_CONFIG.start();
+}
+
+/// Pass `future` through, checking at compile time that it could be spawned
+/// on a multi-threaded executor.
+fn require_send<F: Future + Send>(future: F) -> F {
+    future
+}
+
+/// A service answering with the configuration in its request's extensions.
+struct ExtensionEcho;
+
+impl tower_service::Service<http::Request<()>> for ExtensionEcho {
+    type Response = std::sync::Arc<TypedConfig>;
+    type Error = worker::Error;
+    type Future = std::future::Ready<Result<Self::Response>>;
+
+    fn poll_ready(&mut self, _context: &mut std::task::Context<'_>) -> std::task::Poll<Result<()>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<()>) -> Self::Future {
+        std::future::ready(
+            request
+                .extensions()
+                .get::<std::sync::Arc<TypedConfig>>()
+                .cloned()
+                .ok_or_else(|| worker::Error::RustError("no configuration".to_owned())),
+        )
+    }
+}
+
 #[event(fetch)]
 async fn fetch(request: Request, environment: Env, _context: Context) -> Result<Response> {
     let url = request.url()?;
@@ -51,6 +288,147 @@ async fn fetch(request: Request, environment: Env, _context: Context) -> Result<
                 .map_err(|error| worker::Error::RustError(error.to_string()))?;
             Response::from_json(&config)
         }
+        "/process-env-fallback" => {
+            // No process environment in the runtime: the fallback is inert.
+            let config: PartialConfig = Figment::new()
+                .merge(
+                    CloudflareWorkersBindings::from_struct::<PartialConfig>(&environment)
+                        .fallback_to_process_env(),
+                )
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&config)
+        }
+        "/defaults" => {
+            // Defaults fill in missing bindings but never override present ones.
+            let config: PartialConfig = Figment::new()
+                .merge(
+                    CloudflareWorkersBindings::from_struct::<PartialConfig>(&environment)
+                        .default("missing_field", "default-value")
+                        .default("api_key", "unused-default"),
+                )
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&config)
+        }
+        "/required" => {
+            // An `Option` field marked required fails when unbound.
+            let result = Figment::new()
+                .merge(
+                    CloudflareWorkersBindings::from_struct::<PartialConfig>(&environment)
+                        .require("api_key")
+                        .require("missing_field"),
+                )
+                .extract::<PartialConfig>();
+            match result {
+                Ok(config) => Response::from_json(&config),
+                Err(error) => Response::from_json(
+                    &serde_json::json!({"error": true, "message": error.to_string()}),
+                ),
+            }
+        }
+        "/only" => {
+            // Only the selected field is resolved and emitted.
+            let provider = CloudflareWorkersBindings::from_struct::<FullConfig>(&environment)
+                .only(&["api_key"]);
+            let api_key: String = Figment::new()
+                .merge(&provider)
+                .extract_inner("api_key")
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let snapshot = provider
+                .snapshot()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let fields: Vec<&String> = snapshot.values().keys().collect();
+            Response::from_json(&serde_json::json!({"api_key": api_key, "fields": fields}))
+        }
+        "/deferred" => {
+            // `max_retries` defers to the file; `api_base_url` overrides it.
+            let file = r#"{"api_base_url": "https://file.example.com", "max_retries": "9"}"#;
+            let config: FullConfig = Figment::from(Json::string(file))
+                .merge_bindings(
+                    &CloudflareWorkersBindings::from_struct::<FullConfig>(&environment)
+                        .defer(&["max_retries"]),
+                )
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&config)
+        }
+        "/tagged" => {
+            // Each value traces back to where it came from.
+            let file = r#"{"api_base_url": "https://file.example.com", "max_retries": "9"}"#;
+            let figment = Figment::from(Json::string(file)).merge_tagged(
+                &CloudflareWorkersBindings::from_struct::<FullConfig>(&environment)
+                    .defer(&["max_retries"])
+                    .default("api_key", "unused"),
+            );
+            let sources: HashMap<&str, Option<String>> = ["api_base_url", "api_key", "max_retries"]
+                .into_iter()
+                .map(|key| {
+                    let metadata = figment.find_metadata(key);
+                    let source = metadata.map(|metadata| match &metadata.source {
+                        Some(source) => source.to_string(),
+                        None => metadata.name.to_string(),
+                    });
+                    (key, source)
+                })
+                .collect();
+            Response::from_json(&sources)
+        }
+        "/magic" => {
+            // An integer `MAX_RETRIES` matches the left side of the `Either`,
+            // anything else falls through to the right; the tags lead to the
+            // bindings read, and `CONFIG_DIR` has no file to be relative to.
+            let figment = Figment::new().merge_tagged(&CloudflareWorkersBindings::from_struct::<
+                MagicConfig,
+            >(&environment));
+            let config: MagicConfig = figment
+                .extract_lossy()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let source = |tag| {
+                figment
+                    .get_metadata(tag)
+                    .and_then(|metadata| metadata.source.as_ref())
+                    .map(ToString::to_string)
+            };
+            let (max_retries, max_retries_source) = match &config.max_retries {
+                Either::Left(max_retries) => {
+                    (serde_json::json!(**max_retries), source(max_retries.tag()))
+                }
+                Either::Right(max_retries) => (serde_json::json!(max_retries), None),
+            };
+            Response::from_json(&serde_json::json!({
+                "api_base_url": *config.api_base_url,
+                "api_base_url_source": source(config.api_base_url.tag()),
+                "max_retries": max_retries,
+                "max_retries_source": max_retries_source,
+                "config_dir": config.config_dir.as_ref().map(|dir| dir.original()),
+                "config_dir_relative": config.config_dir.as_ref().map(|dir| dir.relative()),
+            }))
+        }
+        "/nested" => {
+            // Bindings populate the `service` section only.
+            let config: AppConfig = Figment::from(Json::string(r#"{"name": "app"}"#))
+                .merge(
+                    CloudflareWorkersBindings::from_struct::<SingleConfig>(&environment)
+                        .at("service"),
+                )
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&config)
+        }
+        "/layered" => {
+            // A mock overlay consulted before the environment.
+            let overlay =
+                MockBindings::new().with_var("API_BASE_URL", "https://overlay.example.com");
+            let config: FullConfig = Figment::new()
+                .merge(CloudflareWorkersBindings::from_sources::<FullConfig>(&[
+                    &overlay,
+                    &environment,
+                ]))
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&config)
+        }
         "/single" => {
             // Single field extraction.
             let config: SingleConfig = Figment::new()
@@ -73,6 +451,1386 @@ async fn fetch(request: Request, environment: Env, _context: Context) -> Result<
                 .map_err(|error| worker::Error::RustError(error.to_string()))?;
             Response::from_json(&config)
         }
+        "/derived" => {
+            // Declared bindings, with the secret masked in the snapshot.
+            let snapshot = CloudflareWorkersBindings::from_config::<DerivedConfig>(&environment)
+                .snapshot()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let config: DerivedConfig = Figment::from(&snapshot)
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&serde_json::json!({
+                "config": config,
+                "recorded": serde_json::to_value(&snapshot)?,
+            }))
+        }
+        "/field-names" => {
+            // Listed field names reach through a map-based `Deserialize`,
+            // which discovery reports it cannot see into.
+            let named: MapConfig = Figment::new()
+                .merge(CloudflareWorkersBindings::from_field_names::<MapConfig>(
+                    &environment,
+                ))
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let discovered = Figment::new()
+                .merge(CloudflareWorkersBindings::from_struct::<MapConfig>(
+                    &environment,
+                ))
+                .extract::<MapConfig>()
+                .map_err(|error| error.to_string());
+            Response::from_json(&serde_json::json!({
+                "named": named,
+                "discovered": discovered.err(),
+            }))
+        }
+        "/wrangler-defaults" => {
+            // Embedded vars, overridden by the bindings the worker has.
+            let top_level: HashMap<String, String> =
+                Figment::from(wrangler_defaults!("wrangler.toml"))
+                    .extract()
+                    .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let staging: HashMap<String, String> =
+                Figment::from(wrangler_defaults!("wrangler.toml", env = "staging"))
+                    .merge(CloudflareWorkersBindings::from_struct::<PartialConfig>(
+                        &environment,
+                    ))
+                    .extract()
+                    .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&serde_json::json!({
+                "top_level": top_level,
+                "staging": staging,
+            }))
+        }
+        "/untagged" => {
+            // Fields named explicitly where discovery cannot see them.
+            let named: UntaggedConfig = Figment::new()
+                .merge(
+                    CloudflareWorkersBindings::from_struct::<UntaggedConfig>(&environment)
+                        .field_names(&["api_base_url", "local_path"]),
+                )
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let discovered = Figment::new()
+                .merge(CloudflareWorkersBindings::from_struct::<UntaggedConfig>(
+                    &environment,
+                ))
+                .extract::<UntaggedConfig>()
+                .map_err(|error| error.to_string());
+            Response::from_json(&serde_json::json!({
+                "named": named,
+                "discovered": discovered.err(),
+            }))
+        }
+        "/diagnostics" => {
+            // Where each binding came from, with secrets masked.
+            diagnostics_response(
+                &CloudflareWorkersBindings::from_struct::<PartialConfig>(&environment)
+                    .secret("api_key")
+                    .require("missing_field"),
+            )
+        }
+        "/metrics" => {
+            // Counters read back after extracting through a reference.
+            let count = |source: &dyn BindingSource| {
+                let provider = CloudflareWorkersBindings::from_struct::<PartialConfig>(source);
+                Figment::new()
+                    .merge(&provider)
+                    .extract::<PartialConfig>()
+                    .map(|_| provider.metrics())
+                    .map_err(|error| error.to_string())
+            };
+            let mock = MockBindings::new().with_secret("API_KEY", "mock-secret");
+            Response::from_json(&serde_json::json!({
+                "env": count(&environment)?,
+                "mock": count(&mock)?,
+            }))
+        }
+        "/console-debug" => {
+            // The table printed for `wrangler tail` while the debug var is set.
+            let mock = MockBindings::new()
+                .with_var("CONFIG_DEBUG", "1")
+                .with_var("API_BASE_URL", "https://api.example.com/v1")
+                .with_secret("API_KEY", "mock-secret");
+            let provider = CloudflareWorkersBindings::from_struct::<PartialConfig>(&mock)
+                .console_debug("CONFIG_DEBUG");
+            Figment::new()
+                .merge(&provider)
+                .extract::<PartialConfig>()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::ok(provider.diagnostics().to_string())
+        }
+        "/audit" => {
+            // A provider reports its load, and the app reports an extraction
+            // failure with the same audit.
+            let audit = Audit::new("test-worker").fingerprint(b"salt");
+            let provider = CloudflareWorkersBindings::from_struct::<FullConfig>(&environment)
+                .audit(audit.clone());
+            let error = Figment::new()
+                .merge(provider)
+                .extract::<HashMap<String, u8>>()
+                .expect_err("string bindings are not numbers");
+            let line = audit.failed(&error).to_string();
+            let event = AuditEvent::parse(&line).expect("an audit event");
+            Response::from_json(&serde_json::json!({
+                "event": event,
+                "line": line,
+                "other": AuditEvent::parse(r#"{"level":"info"}"#),
+            }))
+        }
+        "/analytics" => {
+            // A failed load writes a data point instead of failing twice.
+            use worker::{
+                js_sys::{JSON, Object, Reflect},
+                wasm_bindgen::{JsCast, JsValue, closure::Closure},
+            };
+
+            // A dataset recording the data points written to it, as JSON.
+            let points = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+            let write_data_point = Closure::<dyn Fn(JsValue)>::new({
+                let points = points.clone();
+                move |point: JsValue| {
+                    if let Some(point) = JSON::stringify(&point)
+                        .ok()
+                        .and_then(|json| json.as_string())
+                    {
+                        points.borrow_mut().push(point);
+                    }
+                }
+            });
+            let recording = Object::new();
+            Reflect::set(
+                &recording,
+                &"writeDataPoint".into(),
+                write_data_point.as_ref(),
+            )?;
+            let mock = MockBindings::new().with_var("API_BASE_URL", "https://api.example.com/v1");
+            let error = Figment::new()
+                .merge(
+                    CloudflareWorkersBindings::from_struct::<PartialConfig>(&mock)
+                        .require("api_key")
+                        .analytics(
+                            FailureAnalytics::new(recording.unchecked_into())
+                                .worker_version("test-version"),
+                        ),
+                )
+                .extract::<PartialConfig>()
+                .err()
+                .map(|error| error.to_string());
+            let points: Vec<serde_json::Value> = points
+                .borrow()
+                .iter()
+                .map(|point| serde_json::from_str(point))
+                .collect::<std::result::Result<_, _>>()?;
+
+            let analytics = FailureAnalytics::new(environment.analytics_engine("CONFIG_FAILURES")?)
+                .worker_version("test-version");
+            let written = analytics
+                .record(
+                    &["API_KEY".to_owned()],
+                    &figment2::Error::from("extraction failed".to_owned()),
+                )
+                .map_err(|error| error.to_string());
+            Response::from_json(&serde_json::json!({
+                "error": error,
+                "points": points,
+                "written": written.is_ok(),
+            }))
+        }
+        "/timings" => {
+            // Time split between lookups, fetches and extraction.
+            let provider = CloudflareWorkersBindings::from_struct::<FullConfig>(&environment);
+            provider.time_extraction(|| {
+                Figment::new()
+                    .merge(&provider)
+                    .extract::<FullConfig>()
+                    .map_err(|error| worker::Error::RustError(error.to_string()))
+            })?;
+            Response::from_json(&provider.timings())
+        }
+        "/trace" => {
+            // Every lookup tried, across the primary source and a fallback.
+            let fallback = MockBindings::new().with_var("API_KEY", "fallback-key");
+            let trace = CloudflareWorkersBindings::from_struct::<PartialConfig>(&environment)
+                .fallback(&fallback)
+                .only(&["api_key", "missing_field"])
+                .default("missing_field", "default")
+                .trace_json();
+            Response::ok(trace)
+        }
+        "/notify" => {
+            // Only a fingerprint differing from the last one is published.
+            let notifier =
+                ChangeNotifier::new(environment.queue("CONFIG_CHANGES")?, "test-worker", b"salt");
+            let snapshot = |url: &str| {
+                let mock = MockBindings::new().with_var("API_BASE_URL", url);
+                CloudflareWorkersBindings::from_struct::<PartialConfig>(&mock)
+                    .snapshot()
+                    .map_err(|error| worker::Error::RustError(error.to_string()))
+            };
+            let mut published = Vec::new();
+            for url in [
+                "https://one.example.com",
+                "https://one.example.com",
+                "https://two.example.com",
+            ] {
+                let change = notifier
+                    .notify_if_changed(&snapshot(url)?)
+                    .await
+                    .map_err(|error| worker::Error::RustError(error.to_string()))?;
+                published.push(change);
+            }
+            Response::from_json(&published)
+        }
+        "/describe" => {
+            // The bindings a type reads, without reading any.
+            let specs = describe::<DescribedConfig>()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&specs)
+        }
+        "/declared-kinds" => {
+            // A secret provisioned as a var is rejected.
+            let extract = |bindings: &MockBindings| {
+                Figment::new()
+                    .merge(CloudflareWorkersBindings::from_config::<DeclaredKindsConfig>(bindings))
+                    .extract::<DeclaredKindsConfig>()
+                    .map_err(|error| error.to_string())
+            };
+            let provisioned = MockBindings::new()
+                .with_var("API_BASE_URL", "https://mock.example.com")
+                .with_secret("API_KEY", "mock-secret");
+            let misprovisioned = MockBindings::new()
+                .with_var("API_BASE_URL", "https://mock.example.com")
+                .with_var("API_KEY", "mock-secret");
+            Response::from_json(&serde_json::json!({
+                "provisioned": extract(&provisioned).map_err(worker::Error::RustError)?,
+                "misprovisioned": extract(&misprovisioned).err(),
+            }))
+        }
+        "/shared" => {
+            // One provider, shared behind an `Rc`, merged into two stacks.
+            let provider = std::rc::Rc::new(
+                CloudflareWorkersBindings::from_struct::<SingleConfig>(&environment),
+            );
+            let default: SingleConfig = Figment::new()
+                .merge(&*provider)
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let staging: SingleConfig = Figment::new()
+                .merge((*provider).clone().profile("staging"))
+                .select("staging")
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&serde_json::json!({"default": default, "staging": staging}))
+        }
+        "/secret-first" => {
+            // Secrets consulted before vars. The runtime binds a name only
+            // once, so the collision is staged on bindings that keep a
+            // stray var apart from the secret of the same name.
+            let config: FullConfig = Figment::new()
+                .merge(
+                    CloudflareWorkersBindings::from_struct::<FullConfig>(&environment)
+                        .lookup_order(LookupOrder::SecretThenVar),
+                )
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let api_key = environment.secret("API_KEY")?.to_string();
+            let colliding = MockBindings::new()
+                .with_var("API_KEY", "stray-var")
+                .with_secret("API_KEY", api_key);
+            let read = |order| {
+                Figment::new()
+                    .merge(
+                        CloudflareWorkersBindings::from_struct::<PartialConfig>(&colliding)
+                            .lookup_order(order),
+                    )
+                    .extract::<PartialConfig>()
+                    .map(|config| config.api_key)
+                    .map_err(|error| worker::Error::RustError(error.to_string()))
+            };
+            Response::from_json(&serde_json::json!({
+                "config": config,
+                "colliding": {
+                    "var_then_secret": read(LookupOrder::VarThenSecret)?,
+                    "secret_then_var": read(LookupOrder::SecretThenVar)?,
+                },
+            }))
+        }
+        "/redacted" => {
+            // Secret-derived fields masked on serialisation.
+            let config: FullConfig = Figment::new()
+                .merge(CloudflareWorkersBindings::from_struct::<FullConfig>(
+                    &environment,
+                ))
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&Redacted::new(&config).mask("api_key"))
+        }
+        "/encrypted" => {
+            // `API_TOKEN` is stored as `enc:v1:...`, keyed by `CONFIG_KEY`.
+            let config: EncryptedConfig = Figment::new()
+                .merge(
+                    CloudflareWorkersBindings::from_struct::<EncryptedConfig>(&environment)
+                        .decrypt_with("CONFIG_KEY"),
+                )
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&config)
+        }
+        "/signed" | "/signed-tampered" => {
+            // Signed document verified before merging; tampering is rejected.
+            let document = if path == "/signed" {
+                SIGNED_DOCUMENT.to_owned()
+            } else {
+                SIGNED_DOCUMENT.replace("signed", "evil")
+            };
+            let result = match VerifyingKey::hmac_sha256_from_binding(&environment, "SIGNING_KEY") {
+                Ok(key) => Figment::new()
+                    .merge(Signed::<Json>::new(document, DOCUMENT_SIGNATURE, key))
+                    .extract::<SingleConfig>(),
+                Err(error) => Err(error),
+            };
+            match result {
+                Ok(config) => Response::from_json(&config),
+                Err(error) => Response::from_json(
+                    &serde_json::json!({"error": true, "message": error.to_string()}),
+                ),
+            }
+        }
+        "/binary-secret" => {
+            // Base64 and hex bindings decoded into secret byte buffers.
+            let config: BinaryConfig = Figment::new()
+                .merge(CloudflareWorkersBindings::from_struct::<BinaryConfig>(
+                    &environment,
+                ))
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&serde_json::json!({
+                "base64_key": config.base64_key.expose_secret(),
+                "hex_key": config.hex_key.expose_secret(),
+            }))
+        }
+        "/mock" => {
+            // In-memory bindings: a var, a secret-only fallback, and a
+            // failing lookup that is skipped.
+            let bindings = MockBindings::new()
+                .with_var("API_BASE_URL", "https://mock.example.com")
+                .with_secret("API_KEY", "mock-secret")
+                .with_error("MISSING_FIELD", "Binding cannot be cast to the type String");
+            let config: PartialConfig = Figment::new()
+                .merge(CloudflareWorkersBindings::from_struct::<PartialConfig>(
+                    &bindings,
+                ))
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&config)
+        }
+        "/inspect" => {
+            // Providers and snapshots log without their secrets.
+            let provider = CloudflareWorkersBindings::from_struct::<FullConfig>(&environment)
+                .secret("api_key")
+                .default("api_key", "fallback-secret-key");
+            let snapshot = provider
+                .snapshot()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&serde_json::json!({
+                "debug": format!("{provider:?}"),
+                "serialized": provider,
+                "snapshot": format!("{snapshot:?}"),
+            }))
+        }
+        "/snapshot" => {
+            // Record the resolved bindings, serialise them with secrets
+            // masked, and replay the serialised snapshot.
+            let snapshot = CloudflareWorkersBindings::from_struct::<FullConfig>(&environment)
+                .secret("api_key")
+                .snapshot()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let recorded = serde_json::to_value(&snapshot)?;
+            let replayed: FullConfig = Figment::new()
+                .merge(serde_json::from_value::<Snapshot>(recorded.clone())?)
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            // The `Env` is read in one pass, so its secrets are read as vars
+            // and only those declared are masked.
+            let undeclared = CloudflareWorkersBindings::from_struct::<FullConfig>(&environment)
+                .snapshot()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&serde_json::json!({
+                "recorded": recorded,
+                "replayed": replayed,
+                "undeclared": serde_json::to_value(&undeclared)?,
+            }))
+        }
+        "/send-snapshot" => {
+            // Extract from a snapshot moved into a `Send` future.
+            let snapshot = CloudflareWorkersBindings::from_struct::<FullConfig>(&environment)
+                .snapshot()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let config: FullConfig = require_send(async move {
+                Figment::from(&snapshot)
+                    .extract()
+                    .map_err(|error| worker::Error::RustError(error.to_string()))
+            })
+            .await?;
+            Response::from_json(&config)
+        }
+        "/golden" => {
+            // Resolved configuration compared with a golden document.
+            assert_config_matches::<FullConfig>(
+                &CloudflareWorkersBindings::from_struct::<FullConfig>(&environment)
+                    .secret("api_key"),
+                r#"{
+                    "api_base_url": "https://api.example.com/v1",
+                    "api_key": "super-secret-key",
+                    "max_retries": 3
+                }"#,
+            );
+            Response::from_json(&serde_json::json!({"matched": true}))
+        }
+        "/fingerprint" => {
+            // Stable configuration hash, with the secret keyed by the salt.
+            let provider = CloudflareWorkersBindings::from_struct::<FullConfig>(&environment)
+                .secret("api_key");
+            let fingerprint = |salt: &str| {
+                provider
+                    .fingerprint(salt.as_bytes())
+                    .map_err(|error| worker::Error::RustError(error.to_string()))
+            };
+            Response::from_json(&serde_json::json!({
+                "fingerprint": fingerprint("salt")?,
+                "repeated": fingerprint("salt")?,
+                "resalted": fingerprint("pepper")?,
+            }))
+        }
+        "/diff" => {
+            // Snapshots before and after a reload compared key by key.
+            let reloaded = MockBindings::new()
+                .with_var("API_BASE_URL", "https://api.example.com/v2")
+                .with_secret("API_KEY", "rotated-key");
+            let snapshot = |source: &dyn BindingSource| {
+                CloudflareWorkersBindings::from_struct::<FullConfig>(source)
+                    .secret("api_key")
+                    .snapshot()
+                    .map_err(|error| worker::Error::RustError(error.to_string()))
+            };
+            let diff =
+                figment2_cloudflare_workers::diff(&snapshot(&environment)?, &snapshot(&reloaded)?);
+            Response::from_json(&diff)
+        }
+        "/all" => {
+            // Every var and secret, without a target struct.
+            let bindings: HashMap<String, String> =
+                Figment::from(CloudflareWorkersBindings::all(&environment))
+                    .extract()
+                    .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&bindings)
+        }
+        "/pages" => {
+            // The bindings as a Pages Function is handed them: the platform
+            // vars and `ASSETS` are left out of `all`, but still readable.
+            let to_worker_error =
+                |error: figment2::Error| worker::Error::RustError(error.to_string());
+            let pages = PagesEnv::from_js(environment.clone().into()).map_err(to_worker_error)?;
+            let bindings: HashMap<String, String> =
+                Figment::from(CloudflareWorkersBindings::all(&pages))
+                    .extract()
+                    .map_err(to_worker_error)?;
+            let config: TypedConfig = extract_config(&pages).map_err(to_worker_error)?;
+            let error = PagesEnv::from_js(worker::wasm_bindgen::JsValue::from_str("env"))
+                .err()
+                .map(|error| error.to_string());
+            Response::from_json(&serde_json::json!({
+                "bindings": bindings,
+                "branch": pages.branch(),
+                "config": config,
+                "error": error,
+            }))
+        }
+        "/js-bindings" => {
+            // The env read as a plain JS object, as workers on another
+            // `worker` release read theirs.
+            let bindings = JsBindings::from_value(environment.clone().into())
+                .ok_or_else(|| worker::Error::RustError("env is not an object".to_owned()))?;
+            let config: TypedConfig = extract_config(&bindings)
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let not_object = JsBindings::from_value(worker::wasm_bindgen::JsValue::from_str("env"));
+            Response::from_json(&serde_json::json!({
+                "config": config,
+                "not_object": not_object.is_none(),
+            }))
+        }
+        "/js-object" => {
+            // A plain JS object standing in for the env, as a custom
+            // runtime would hand one over.
+            use worker::js_sys::{Object, Reflect};
+
+            let object = Object::new();
+            for (name, value) in [
+                ("API_BASE_URL", "https://js.example.com"),
+                ("MAX_RETRIES", "4"),
+            ] {
+                Reflect::set(&object, &name.into(), &value.into())?;
+            }
+            Reflect::set(&object, &"CONFIG".into(), &Object::new())?;
+            let config: TypedConfig = Figment::from(CloudflareWorkersBindings::from_js_object::<
+                TypedConfig,
+            >(&object))
+            .extract_lossy()
+            .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let names: HashMap<String, String> = Figment::from(
+                CloudflareWorkersBindings::all_owned(JsBindings::new(object)),
+            )
+            .extract()
+            .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&serde_json::json!({ "config": config, "all": names }))
+        }
+        "/owned" => {
+            // A `'static` provider owning its `Env` handle, moved into a future.
+            let provider =
+                CloudflareWorkersBindings::from_struct_owned::<FullConfig>(environment.clone());
+            let config: FullConfig = async move { Figment::from(provider).extract() }
+                .await
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&config)
+        }
+        "/figment-ext" => {
+            // Extension trait shorthands over `Figment`.
+            let config: FullConfig = Figment::from_cloudflare::<SingleConfig>(&environment)
+                .with_cloudflare::<FullConfig>(&environment)
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&config)
+        }
+        "/extract-config" => {
+            // One-call extraction, parsing `MAX_RETRIES` into a number.
+            let config: TypedConfig = extract_config(&environment)
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&config)
+        }
+        "/from-env" => {
+            // Deserialised from the bindings without a figment, with errors
+            // naming the field and binding.
+            let config: TypedConfig = from_env(&environment)
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let error = from_env::<MistypedConfig>(&environment)
+                .err()
+                .map(|error| error.to_string());
+            Response::from_json(&serde_json::json!({ "config": config, "error": error }))
+        }
+        "/cached" => {
+            // Extract once, then serve the cached value until invalidated.
+            static CONFIG: CachedConfig<TypedConfig> = CachedConfig::new();
+            CONFIG.invalidate();
+            let to_worker_error =
+                |error: figment2::Error| worker::Error::RustError(error.to_string());
+            let first = CONFIG.get(&environment).map_err(to_worker_error)?;
+            let second = CONFIG.get(&environment).map_err(to_worker_error)?;
+            CONFIG.invalidate();
+            let cached_after_invalidate = CONFIG.cached().is_some();
+            let third = CONFIG.get(&environment).map_err(to_worker_error)?;
+            Response::from_json(&serde_json::json!({
+                "config": &*first,
+                "shared": std::sync::Arc::ptr_eq(&first, &second),
+                "cached_after_invalidate": cached_after_invalidate,
+                "reextracted": !std::sync::Arc::ptr_eq(&first, &third),
+            }))
+        }
+        "/shared-config" => {
+            // Handlers keep the value they read while a reload swaps it.
+            static CONFIG: SharedConfig<TypedConfig> = SharedConfig::new();
+            let to_worker_error =
+                |error: figment2::Error| worker::Error::RustError(error.to_string());
+            let first = CONFIG.load(&environment).map_err(to_worker_error)?;
+            let overrides = MockBindings::new().with_var("MAX_RETRIES", "6");
+            let reloaded = CONFIG
+                .load_figment(
+                    &Figment::new().merge(CloudflareWorkersBindings::from_sources::<TypedConfig>(
+                        &[&overrides, &environment],
+                    )),
+                )
+                .map_err(to_worker_error)?;
+            let failed = CONFIG.load_figment(&Figment::new()).is_err();
+            let current = CONFIG.get().ok_or("no configuration")?;
+            Response::from_json(&serde_json::json!({
+                "first": &*first,
+                "current": &*current,
+                "kept_after_failure": failed && std::sync::Arc::ptr_eq(&reloaded, &current),
+            }))
+        }
+        "/rollout" => {
+            // Staged values resolve by bucket, the same way every time.
+            let staged = |percent: u8| {
+                MockBindings::new()
+                    .with_var("API_BASE_URL", "https://api.example.com/v1")
+                    .with_var(
+                        "MAX_RETRIES",
+                        format!(r#"{{"old": 3, "new": 9, "percent": {percent}}}"#),
+                    )
+            };
+            let resolve = |source: &MockBindings, key: &str| {
+                let rollout = Rollout::new(source, key);
+                Figment::new()
+                    .merge(CloudflareWorkersBindings::from_struct::<TypedConfig>(
+                        &rollout,
+                    ))
+                    .extract_lossy::<TypedConfig>()
+                    .map(|config| config.max_retries)
+                    .map_err(|error| worker::Error::RustError(error.to_string()))
+            };
+            let mut upgraded = Vec::new();
+            for percent in [0, 50, 100] {
+                let source = staged(percent);
+                let mut count = 0;
+                for user in 0..100 {
+                    if resolve(&source, &format!("user-{user}"))? == 9 {
+                        count += 1;
+                    }
+                }
+                upgraded.push(count);
+            }
+            let source = staged(50);
+            let repeated = resolve(&source, "user-7")? == resolve(&source, "user-7")?;
+            Response::from_json(&serde_json::json!({
+                "upgraded": upgraded,
+                "repeated": repeated,
+            }))
+        }
+        "/variant" => {
+            // Weighted arms, chosen the same way for the same key.
+            let bindings = MockBindings::new().with_var(
+                "MAX_RETRIES",
+                r#"{"control": {"value": 3}, "aggressive": {"value": 9, "weight": 3}}"#,
+            );
+            let config: ExperimentConfig = extract_config(&bindings)
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let mut arms = std::collections::BTreeMap::<&str, u32>::new();
+            for user in 0..400 {
+                let (arm, _) = config.max_retries.select(&format!("user-{user}"));
+                *arms.entry(arm).or_default() += 1;
+            }
+            Response::from_json(&serde_json::json!({
+                "arms": arms,
+                "names": config.max_retries.names().collect::<Vec<_>>(),
+                "user": config.max_retries.select("user-7"),
+                "repeated": config.max_retries.get("user-7") == config.max_retries.get("user-7"),
+            }))
+        }
+        "/config-cell" => {
+            // Failures are remembered by the retry policy.
+            static RETRIED: ConfigCell<TypedConfig> = ConfigCell::new();
+            static HELD: ConfigCell<TypedConfig> =
+                ConfigCell::with_retry(RetryPolicy::After(std::time::Duration::from_secs(60)));
+            static ASYNC: ConfigCell<TypedConfig> = ConfigCell::new();
+            let empty = MockBindings::new();
+            let retried = [
+                RETRIED.get(&empty).is_ok(),
+                RETRIED.get(&environment).is_ok(),
+            ];
+            let held = [HELD.get(&empty).is_ok(), HELD.get(&environment).is_ok()];
+            HELD.reset();
+            let after_reset = HELD.get(&environment).is_ok();
+            let first = ASYNC
+                .get_or_init_async(|| async { extract_config(&environment) })
+                .await
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let again = ASYNC
+                .get_or_init_async(|| async { extract_config(&empty) })
+                .await
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&serde_json::json!({
+                "retried": retried,
+                "held": held,
+                "after_reset": after_reset,
+                "shared": std::sync::Arc::ptr_eq(&first, &again),
+            }))
+        }
+        "/startup" => {
+            // Extracted before the first request was served.
+            let extracted_at_startup = STARTUP_CONFIG.cached().is_some();
+            let config = STARTUP_CONFIG
+                .get()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&serde_json::json!({
+                "extracted_at_startup": extracted_at_startup,
+                "config": &*config,
+            }))
+        }
+        "/router" | "/router/cached" => {
+            // Handlers read the configuration from the route data.
+            static CONFIG: CachedConfig<TypedConfig> = CachedConfig::new();
+            let router = if path == "/router" {
+                config_router::<TypedConfig>(&environment)?
+            } else {
+                cached_config_router(&CONFIG, &environment)?
+            };
+            router
+                .get("/router", |_, context| Response::from_json(&*context.data))
+                .get("/router/cached", |_, context| {
+                    let cached = CONFIG
+                        .cached()
+                        .is_some_and(|config| std::sync::Arc::ptr_eq(&config, &context.data));
+                    Response::from_json(&serde_json::json!({
+                        "cached": cached,
+                        "config": &*context.data,
+                    }))
+                })
+                .run(request, environment)
+                .await
+        }
+        "/tower" => {
+            // Services behind the layer pull the configuration from the
+            // request extensions.
+            static CONFIG: CachedConfig<TypedConfig> = CachedConfig::new();
+            let layer = ConfigLayer::from_cache(&CONFIG, &environment)
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let mut service = layer.layer(ExtensionEcho);
+            let config = service.call(http::Request::new(())).await?;
+            Response::from_json(&serde_json::json!({
+                "config": &*config,
+                "shared": CONFIG
+                    .cached()
+                    .is_some_and(|cached| std::sync::Arc::ptr_eq(&cached, &config)),
+            }))
+        }
+        "/axum" => {
+            // Handlers take the configuration the layer inserted.
+            static CONFIG: CachedConfig<TypedConfig> = CachedConfig::new();
+            let layer = ConfigLayer::from_cache(&CONFIG, &environment)
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let mut router = axum::Router::new()
+                .route(
+                    "/axum",
+                    axum::routing::get(|Config(config): Config<TypedConfig>| async move {
+                        serde_json::json!({ "config": &*config }).to_string()
+                    }),
+                )
+                .layer(layer);
+            let response = router
+                .call(http::Request::new(axum::body::Body::empty()))
+                .await
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_bytes(body.to_vec())
+        }
+        "/watch" => {
+            // Each poll reads the version key; only changed values call back.
+            static WATCHER: KvWatcher = KvWatcher::new(std::time::Duration::ZERO);
+            static CONFIG: SharedConfig<TypedConfig> = SharedConfig::new();
+            let store = ConfigStore::new(&environment, "CONFIG")
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let changed = WATCHER
+                .poll(&store, &environment, |config: TypedConfig| {
+                    CONFIG.replace(config);
+                })
+                .await
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&serde_json::json!({
+                "changed": changed,
+                "config": CONFIG.get().as_deref(),
+            }))
+        }
+        "/migrate" => {
+            // Older documents are upgraded step by step to the current shape.
+            let migrations = || {
+                let rename = |from: &'static str, to: &'static str| {
+                    move |document: &mut figment2::value::Dict| {
+                        if let Some(value) = document.remove(from) {
+                            document.insert(to.to_owned(), value);
+                        }
+                        Ok::<_, String>(())
+                    }
+                };
+                Migrations::new(2)
+                    .step(0, rename("retries", "max_retries"))
+                    .step(1, rename("url", "api_base_url"))
+            };
+            let extract = |document: &str| {
+                Figment::new()
+                    .merge(migrations().apply(Json::string(document)))
+                    .extract::<TypedConfig>()
+                    .map_err(|error| error.to_string())
+            };
+            let documents = [
+                r#"{"url": "https://v0.example.com", "retries": 1}"#,
+                r#"{"schema_version": "1", "url": "https://v1.example.com", "max_retries": 2}"#,
+                r#"{"schema_version": 2, "api_base_url": "https://v2.example.com", "max_retries": 3}"#,
+            ];
+            let migrated = documents
+                .iter()
+                .map(|document| extract(document))
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(worker::Error::RustError)?;
+            Response::from_json(&serde_json::json!({
+                "migrated": migrated,
+                "newer": extract(r#"{"schema_version": 3}"#).err(),
+            }))
+        }
+        "/remote" => {
+            // Unchanged documents are revalidated rather than fetched again.
+            let to_worker_error =
+                |error: figment2::Error| worker::Error::RustError(error.to_string());
+            let remote =
+                RemoteDocument::service(&environment, "ORIGIN", "https://config.example.com/")
+                    .map_err(to_worker_error)?;
+            let mut fetches = Vec::new();
+            for _ in 0..3 {
+                let fetched = remote.fetch().await.map_err(to_worker_error)?;
+                fetches.push(serde_json::json!({
+                    "modified": fetched.modified(),
+                    "document": fetched.document(),
+                }));
+            }
+            Response::from_json(&fetches)
+        }
+        "/canary" => {
+            // Canaries are only applied if their configuration passes the checks.
+            let extract = |canary: &str| {
+                Canary::new(
+                    Figment::from_cloudflare::<TypedConfig>(&environment),
+                    Json::string(canary),
+                )
+                .check(|config: &TypedConfig| {
+                    if config.max_retries <= 5 {
+                        Ok(())
+                    } else {
+                        Err(format!("{} retries is too many", config.max_retries))
+                    }
+                })
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))
+            };
+            let outcomes = [
+                r#"{"max_retries": 4}"#,
+                r#"{"max_retries": 9}"#,
+                r#"{"max_retries": "x"}"#,
+            ]
+            .into_iter()
+            .map(|canary| {
+                extract(canary).map(|outcome| {
+                    serde_json::json!({
+                        "applied": outcome.applied(),
+                        "rejection": outcome.rejection(),
+                        "max_retries": outcome.config().max_retries,
+                    })
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+            Response::from_json(&outcomes)
+        }
+        "/override" => {
+            // Requests holding the token override the configuration.
+            match HeaderOverrides::from_request(&request, &environment, "OVERRIDE_TOKEN") {
+                Ok(overrides) => {
+                    let keys: Vec<String> = overrides.keys().map(str::to_owned).collect();
+                    let config: TypedConfig = Figment::from_cloudflare::<TypedConfig>(&environment)
+                        .merge(overrides)
+                        .extract_lossy()
+                        .map_err(|error| worker::Error::RustError(error.to_string()))?;
+                    Response::from_json(&serde_json::json!({ "config": config, "keys": keys }))
+                }
+                Err(error) => Ok(Response::from_json(&serde_json::json!({
+                    "error": error.to_string(),
+                }))?
+                .with_status(403)),
+            }
+        }
+        "/query-override" => {
+            // Preview hostnames take allowed, non-secret fields from the query.
+            let snapshot = CloudflareWorkersBindings::from_struct::<FullConfig>(&environment)
+                .secret("api_key")
+                .snapshot()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            match QueryOverrides::from_request(
+                &request,
+                &snapshot,
+                &["max_retries", "api_key"],
+                &["*.preview.example.com"],
+            ) {
+                Ok(overrides) => {
+                    let keys: Vec<String> = overrides.keys().map(str::to_owned).collect();
+                    let config: FullConfig = Figment::from(snapshot)
+                        .merge(overrides)
+                        .extract_lossy()
+                        .map_err(|error| worker::Error::RustError(error.to_string()))?;
+                    Response::from_json(&serde_json::json!({
+                        "max_retries": config.max_retries,
+                        "keys": keys,
+                    }))
+                }
+                Err(error) => Ok(Response::from_json(&serde_json::json!({
+                    "error": error.to_string(),
+                }))?
+                .with_status(403)),
+            }
+        }
+        "/interpolate" => {
+            // Values reference other bindings, expanded before extraction.
+            let config: TypedConfig = extract_config(&Interpolated::new(&environment))
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let cyclic = MockBindings::new()
+                .with_var("API_BASE_URL", "https://${API_HOST}/v1")
+                .with_var("API_HOST", "${API_BASE_URL}")
+                .with_var("MAX_RETRIES", "3");
+            let cycle = extract_config::<TypedConfig>(&Interpolated::new(&cyclic))
+                .err()
+                .map(|error| error.to_string());
+            Response::from_json(&serde_json::json!({ "config": config, "cycle": cycle }))
+        }
+        "/templated" => {
+            // Placeholders are filled in from the request's `cf` properties.
+            let templated = RequestTemplated::new(&environment, &request)
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let config: TypedConfig = extract_config(&templated)
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let bindings = MockBindings::new()
+                .with_var("API_BASE_URL", "https://{city}.example.com")
+                .with_var("MAX_RETRIES", "3");
+            let missing = RequestTemplated::new(&bindings, &request)
+                .map_err(|error| error.to_string())
+                .and_then(|templated| {
+                    extract_config::<TypedConfig>(&templated).map_err(|error| error.to_string())
+                })
+                .err();
+            Response::from_json(&serde_json::json!({ "config": config, "missing": missing }))
+        }
+        "/json-binding" => {
+            // Individual bindings override the `APP_CONFIG` document.
+            let config: TypedConfig = Figment::new()
+                .merge(JsonBinding::new(&environment, "APP_CONFIG"))
+                .merge(CloudflareWorkersBindings::from_struct::<TypedConfig>(
+                    &environment,
+                ))
+                .extract_lossy()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let bindings = MockBindings::new().with_var("APP_CONFIG", "[1, 2]");
+            let invalid = Figment::new()
+                .merge(JsonBinding::new(&bindings, "APP_CONFIG"))
+                .extract_lossy::<TypedConfig>()
+                .err()
+                .map(|error| error.to_string());
+            Response::from_json(&serde_json::json!({ "config": config, "invalid": invalid }))
+        }
+        "/structs" => {
+            // One provider resolves the fields of both structs.
+            let figment = Figment::new().merge(CloudflareWorkersBindings::from_structs::<(
+                TypedConfig,
+                PartialConfig,
+            )>(&environment));
+            let typed: TypedConfig = figment
+                .extract_lossy()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let partial: PartialConfig = figment
+                .extract()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&serde_json::json!({ "typed": typed, "partial": partial }))
+        }
+        "/focus" => {
+            // Only the focused section resolves; the other's requirement is
+            // never checked.
+            let figment = |focus: &str| {
+                Figment::new()
+                    .merge(
+                        CloudflareWorkersBindings::from_struct::<TypedConfig>(&environment)
+                            .at("service")
+                            .focus(focus),
+                    )
+                    .merge(
+                        CloudflareWorkersBindings::from_struct::<PartialConfig>(&environment)
+                            .at("extra")
+                            .require("missing_field")
+                            .focus(focus),
+                    )
+            };
+            let service: TypedConfig = figment("service")
+                .extract_inner_lossy("service")
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let retries = figment("service.max_retries")
+                .find_value("service")
+                .ok()
+                .and_then(|value| value.into_dict())
+                .map(|dict| dict.into_keys().collect::<Vec<_>>());
+            let extra = figment("extra")
+                .extract_inner::<PartialConfig>("extra")
+                .err()
+                .map(|error| error.to_string());
+            Response::from_json(&serde_json::json!({
+                "service": service,
+                "retries": retries,
+                "extra": extra,
+            }))
+        }
+        "/cases" => {
+            // Bindings are found under any of the given casings.
+            let provider = CloudflareWorkersBindings::from_struct::<TypedConfig>(&environment)
+                .binding_cases(&[BindingCase::Upper, BindingCase::Exact, BindingCase::Lower]);
+            let config: TypedConfig = Figment::new()
+                .merge(&provider)
+                .extract_lossy()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let trace: serde_json::Value = serde_json::from_str(&provider.trace_json())?;
+            let strict = extract_config::<TypedConfig>(&environment)
+                .err()
+                .map(|error| error.to_string());
+            Response::from_json(&serde_json::json!({
+                "config": config,
+                "candidates": trace["fields"][0]["candidates"],
+                "strict": strict,
+            }))
+        }
+        "/alias" => {
+            // The first alias bound wins.
+            let config: TypedConfig = Figment::new()
+                .merge(
+                    CloudflareWorkersBindings::from_struct::<TypedConfig>(&environment)
+                        .alias("api_base_url", &["LEGACY_API_URL", "API_BASE_URL"]),
+                )
+                .extract_lossy()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&config)
+        }
+        "/versions" => {
+            // Semver bindings parse into typed fields, or fail clearly.
+            let extract = |version: &str| {
+                let bindings = MockBindings::new()
+                    .with_var("MIN_CLIENT_VERSION", version)
+                    .with_var("SUPPORTED_API", ">=1.4, <2");
+                extract_config::<VersionConfig>(&bindings).map_err(|error| error.to_string())
+            };
+            let config = extract(" 1.4.2 ").map_err(worker::Error::RustError)?;
+            let invalid = extract("v1.4").err();
+            Response::from_json(&serde_json::json!({
+                "min_client_version": config.min_client_version.to_string(),
+                "supported": config.supported_api.matches(&config.min_client_version),
+                "invalid": invalid,
+            }))
+        }
+        "/uuids" => {
+            // UUID bindings parse into typed fields, or fail clearly.
+            let extract = |tenants: &str| {
+                let bindings = MockBindings::new()
+                    .with_var("WORKSPACE_ID", "{6F1C0A1E-8D5B-4B8E-9A43-0D2F7C1E5B6A}")
+                    .with_var("TENANT_IDS", tenants);
+                extract_config::<TenantConfig>(&bindings).map_err(|error| error.to_string())
+            };
+            let config =
+                extract("0b5e1f4c-2a3d-4c5e-8f60-718293a4b5c6, 0b5e1f4c2a3d4c5e8f60718293a4b5c7")
+                    .map_err(worker::Error::RustError)?;
+            let invalid = extract("0b5e1f4c-2a3d-4c5e-8f60-718293a4b5c6, tenant-2").err();
+            Response::from_json(&serde_json::json!({
+                "workspace_id": config.workspace_id.to_string(),
+                "tenant_ids": config
+                    .tenant_ids
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>(),
+                "invalid": invalid,
+            }))
+        }
+        "/matches" => {
+            // Values are checked against patterns during extraction.
+            let extract = |pattern: &str| {
+                Figment::new()
+                    .merge(
+                        CloudflareWorkersBindings::from_struct::<TypedConfig>(&environment)
+                            .matches("api_base_url", pattern),
+                    )
+                    .extract_lossy::<TypedConfig>()
+                    .map_err(|error| error.to_string())
+            };
+            let config = extract("^https://").map_err(worker::Error::RustError)?;
+            Response::from_json(&serde_json::json!({
+                "config": config,
+                "mismatch": extract(r"\.internal$").err(),
+                "invalid": extract("(").err(),
+            }))
+        }
+        "/range" => {
+            // Out-of-range values are reported together.
+            let provider = |max_retries: u8| {
+                CloudflareWorkersBindings::from_struct::<TypedConfig>(&environment)
+                    .range("max_retries", 1..=max_retries)
+            };
+            let config: TypedConfig = Figment::new()
+                .merge(provider(5))
+                .extract_lossy()
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            let errors = Figment::new()
+                .merge(provider(2).range("api_base_url", 0..=1))
+                .extract_lossy::<TypedConfig>()
+                .err()
+                .into_iter()
+                .flatten();
+            Response::from_json(&serde_json::json!({
+                "config": config,
+                "errors": errors.map(|error| error.to_string()).collect::<Vec<_>>(),
+            }))
+        }
+        "/validated" => {
+            // Every violation names its key and binding.
+            let errors = extract_validated::<ValidatedConfig>(&environment)
+                .err()
+                .into_iter()
+                .flatten();
+            Response::from_json(&serde_json::json!({
+                "errors": errors.map(|error| error.to_string()).collect::<Vec<_>>(),
+            }))
+        }
+        "/validated-garde" => {
+            let errors = extract_validated_garde::<GardeConfig>(&environment)
+                .err()
+                .into_iter()
+                .flatten();
+            Response::from_json(&serde_json::json!({
+                "errors": errors.map(|error| error.to_string()).collect::<Vec<_>>(),
+            }))
+        }
+        "/kv" => {
+            // KV values, cached in the isolate, take precedence over vars.
+            let to_worker_error =
+                |error: figment2::Error| worker::Error::RustError(error.to_string());
+            let kv = ConfigStore::new(&environment, "CONFIG")
+                .map_err(to_worker_error)?
+                .max_age(std::time::Duration::from_secs(60))
+                .load::<TypedConfig>()
+                .await
+                .map_err(to_worker_error)?;
+            let config: TypedConfig = Figment::new()
+                .merge(CloudflareWorkersBindings::from_sources::<TypedConfig>(&[
+                    &kv,
+                    &environment,
+                ]))
+                .extract_lossy()
+                .map_err(to_worker_error)?;
+            Response::from_json(&config)
+        }
+        "/stack" => {
+            // The R2 document is the base, vars override it and KV
+            // overrides both.
+            let config: TypedConfig = CloudflareStack::new(&environment)
+                .with_bindings::<TypedConfig>()
+                .with_kv("CONFIG")
+                .with_r2("CONFIG_BUCKET", "app.toml")
+                .extract()
+                .await
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&config)
+        }
+        "/when-profile" => {
+            // `SENTRY_DSN` is required in production and not even looked up
+            // elsewhere.
+            let extract = |source: &dyn BindingSource, profile: &str| {
+                let provider = CloudflareWorkersBindings::from_struct::<ProfiledConfig>(source)
+                    .profile(profile)
+                    .when_profile("prod", |provider| provider.require("sentry_dsn"))
+                    .unless_profile("prod", |provider| provider.skip(&["sentry_dsn"]));
+                match Figment::new()
+                    .merge(provider)
+                    .select(profile)
+                    .extract::<ProfiledConfig>()
+                {
+                    Ok(config) => serde_json::json!(config),
+                    Err(error) => serde_json::json!({ "error": error.to_string() }),
+                }
+            };
+            let unbound =
+                MockBindings::new().with_var("API_BASE_URL", "https://api.example.com/v1");
+            Response::from_json(&serde_json::json!({
+                "dev": extract(&environment, "dev"),
+                "prod": extract(&environment, "prod"),
+                "unbound_prod": extract(&unbound, "prod"),
+            }))
+        }
+        "/accumulated" => {
+            // Origins from the file, vars and KV are appended in that
+            // order, while `max_retries` is replaced by each layer.
+            let to_worker_error =
+                |error: figment2::Error| worker::Error::RustError(error.to_string());
+            let kv = ConfigStore::new(&environment, "CONFIG")
+                .map_err(to_worker_error)?
+                .load::<OriginsConfig>()
+                .await
+                .map_err(to_worker_error)?;
+            let file = r#"{"allowed_origins": ["https://app.example.com"], "max_retries": 1}"#;
+            let config: OriginsConfig = Figment::from(Json::string(file))
+                .merge_bindings(
+                    &CloudflareWorkersBindings::from_struct::<OriginsConfig>(&environment)
+                        .accumulate(&["allowed_origins"]),
+                )
+                .merge_bindings(
+                    &CloudflareWorkersBindings::from_struct::<OriginsConfig>(&kv)
+                        .accumulate(&["allowed_origins"]),
+                )
+                .extract_lossy()
+                .map_err(to_worker_error)?;
+            Response::from_json(&config)
+        }
+        "/kv-put" => {
+            // Writes are read back at once, and stale versions are rejected.
+            let to_worker_error =
+                |error: figment2::Error| worker::Error::RustError(error.to_string());
+            let store = ConfigStore::new(&environment, "CONFIG")
+                .map_err(to_worker_error)?
+                .max_age(std::time::Duration::from_secs(60));
+            let version = store.version().await.map_err(to_worker_error)?;
+            let written = store
+                .put_versioned(version, &[("MAX_RETRIES", "9")])
+                .await
+                .map_err(to_worker_error)?;
+            let conflict = store
+                .put_versioned(version, &[("MAX_RETRIES", "1")])
+                .await
+                .err()
+                .map(|error| error.to_string());
+            let kv = store.load::<TypedConfig>().await.map_err(to_worker_error)?;
+            let config: TypedConfig = Figment::new()
+                .merge(CloudflareWorkersBindings::from_sources::<TypedConfig>(&[
+                    &kv,
+                    &environment,
+                ]))
+                .extract_lossy()
+                .map_err(to_worker_error)?;
+            Response::from_json(&serde_json::json!({
+                "version": version,
+                "written": written,
+                "conflict": conflict,
+                "config": config,
+            }))
+        }
+        "/d1" => {
+            // Migrating is idempotent, and rows for other keys are not loaded.
+            let to_worker_error =
+                |error: figment2::Error| worker::Error::RustError(error.to_string());
+            let store = D1ConfigStore::new(&environment, "CONFIG_DB").map_err(to_worker_error)?;
+            store.migrate().await.map_err(to_worker_error)?;
+            store.migrate().await.map_err(to_worker_error)?;
+            store
+                .put_all(&[("MAX_RETRIES", "4"), ("UNRELATED", "x")])
+                .await
+                .map_err(to_worker_error)?;
+            store
+                .put("MAX_RETRIES", "9")
+                .await
+                .map_err(to_worker_error)?;
+            store.delete("STALE").await.map_err(to_worker_error)?;
+            let d1 = store.load::<TypedConfig>().await.map_err(to_worker_error)?;
+            let config: TypedConfig = Figment::new()
+                .merge(CloudflareWorkersBindings::from_sources::<TypedConfig>(&[
+                    &d1,
+                    &environment,
+                ]))
+                .extract_lossy()
+                .map_err(to_worker_error)?;
+            Response::from_json(&serde_json::json!({
+                "loaded": d1.names().map(|names| names.len()),
+                "config": config,
+            }))
+        }
+        "/admin" | "/admin/reload" => {
+            // Serves the cached configuration, and reloads it from KV.
+            static CONFIG: CachedConfig<TypedConfig> = CachedConfig::new();
+            AdminEndpoint::new(&CONFIG, "ADMIN_TOKEN")
+                .reveal("max_retries")
+                .handle(&request, &environment, || async {
+                    let kv = ConfigStore::new(&environment, "CONFIG")?
+                        .load::<TypedConfig>()
+                        .await?;
+                    Figment::new()
+                        .merge(CloudflareWorkersBindings::from_sources::<TypedConfig>(&[
+                            &kv,
+                            &environment,
+                        ]))
+                        .extract_lossy()
+                })
+                .await
+        }
+        "/hub" => {
+            // A follower picks up each publish without being asked to.
+            static CONFIG: SharedConfig<TypedConfig> = SharedConfig::new();
+            let to_worker_error =
+                |error: figment2::Error| worker::Error::RustError(error.to_string());
+            let hub =
+                HubClient::new(&environment, "CONFIG_HUB", "test").map_err(to_worker_error)?;
+            let initial = hub.load().await.map_err(to_worker_error)?.version();
+            let publish = |max_retries: &str| {
+                std::collections::HashMap::from([(
+                    "MAX_RETRIES".to_owned(),
+                    max_retries.to_owned(),
+                )])
+            };
+            let published = hub.publish(&publish("7")).await.map_err(to_worker_error)?;
+            let follower =
+                HubClient::new(&environment, "CONFIG_HUB", "test").map_err(to_worker_error)?;
+            let env = environment.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let _ = follower
+                    .follow(&CONFIG, |values| {
+                        Figment::new().merge(
+                            CloudflareWorkersBindings::from_sources::<TypedConfig>(&[values, &env]),
+                        )
+                    })
+                    .await;
+            });
+            let wait_for = async |max_retries: u8| {
+                for _ in 0..200 {
+                    if CONFIG
+                        .get()
+                        .is_some_and(|config| config.max_retries == max_retries)
+                    {
+                        return true;
+                    }
+                    Delay::from(std::time::Duration::from_millis(10)).await;
+                }
+                false
+            };
+            let loaded = wait_for(7).await;
+            let republished = hub.publish(&publish("8")).await.map_err(to_worker_error)?;
+            let pushed = wait_for(8).await;
+            Response::from_json(&serde_json::json!({
+                "versions": [initial, published, republished],
+                "loaded": loaded,
+                "pushed": pushed,
+            }))
+        }
+        "/tenant" => {
+            // Each hostname's KV overrides are layered over the vars.
+            let config: TypedConfig = ConfigStore::new(&environment, "CONFIG")
+                .map_err(|error| worker::Error::RustError(error.to_string()))?
+                .extract_for_host(&environment, &request)
+                .await
+                .map_err(|error| worker::Error::RustError(error.to_string()))?;
+            Response::from_json(&config)
+        }
+        "/flags" => {
+            // KV flags override the defaults in vars.
+            let to_worker_error =
+                |error: figment2::Error| worker::Error::RustError(error.to_string());
+            let store = ConfigStore::new(&environment, "FLAG_STORE").map_err(to_worker_error)?;
+            let flags = FeatureFlags::load(
+                &store,
+                &environment,
+                &["new_checkout", "dark_mode", "checkout_layout", "unset"],
+            )
+            .await
+            .map_err(to_worker_error)?;
+            let config: FlaggedConfig = Figment::new()
+                .merge(CloudflareWorkersBindings::from_struct::<FlaggedConfig>(
+                    &environment,
+                ))
+                .merge(&flags)
+                .extract_lossy()
+                .map_err(to_worker_error)?;
+            Response::from_json(&serde_json::json!({
+                "new_checkout": flags.enabled("new_checkout"),
+                "dark_mode": flags.enabled("dark_mode"),
+                "unset": flags.enabled("unset"),
+                "layout": flags.variant("checkout_layout"),
+                "names": flags.names().collect::<Vec<_>>(),
+                "config": config,
+            }))
+        }
         "/missing-all" => {
             // All required fields missing — extraction should fail.
             let result = Figment::new()
@@ -0,0 +1,104 @@
+// Helpers for running the `worker-build` output of a workers-rs crate under
+// Miniflare (workerd) and asserting on the JSON its routes return.
+//
+// Each route of the test worker extracts a configuration from its bindings
+// and responds with it as JSON, so binding-surfacing behaviour can be
+// asserted from Node without deploying. The module has no dependencies on
+// this crate and can be copied into other projects as-is.
+
+import assert from "node:assert/strict";
+import { Miniflare } from "miniflare";
+import path from "node:path";
+import { fileURLToPath } from "node:url";
+
+const __dirname = path.dirname(fileURLToPath(import.meta.url));
+
+/** The `worker-build` output directory of the bundled test worker. */
+export const workerBuildPath = path.resolve(__dirname, "../test-worker/build");
+
+/**
+ * Start a worker with the given bindings.
+ *
+ * @param {Record<string, string>} bindings Vars and secrets to expose.
+ * @param {object} [options]
+ * @param {string} [options.buildPath] `worker-build` output directory.
+ * @param {string} [options.compatibilityDate] workerd compatibility date.
+ * @param {string[]} [options.kvNamespaces] KV namespace bindings to create.
+ * @param {Record<string, { dataset: string }>} [options.analyticsEngineDatasets]
+ *   Analytics Engine dataset bindings to create.
+ * @param {Record<string, string>} [options.queueProducers] Queue producer
+ *   bindings to create, mapped to their queue names.
+ * @param {string[]} [options.d1Databases] D1 database bindings to create.
+ * @param {string[]} [options.r2Buckets] R2 bucket bindings to create.
+ * @param {Record<string, string>} [options.durableObjects] Durable Object
+ *   namespace bindings to create, mapped to the classes the worker exports.
+ * @param {Record<string, (request: Request) => Response | Promise<Response>>} [options.serviceBindings]
+ *   Service bindings to create, mapped to the functions answering them.
+ * @returns {Miniflare}
+ */
+export function startWorker(
+  bindings = {},
+  {
+    buildPath = workerBuildPath,
+    compatibilityDate = "2025-01-01",
+    kvNamespaces = [],
+    analyticsEngineDatasets = {},
+    queueProducers = {},
+    d1Databases = [],
+    r2Buckets = [],
+    durableObjects = {},
+    serviceBindings = {},
+  } = {},
+) {
+  return new Miniflare({
+    scriptPath: path.join(buildPath, "worker", "shim.mjs"),
+    modules: true,
+    modulesRules: [
+      { type: "ESModule", include: ["**/*.js"], fallthrough: true },
+      { type: "CompiledWasm", include: ["**/*.wasm"], fallthrough: true },
+    ],
+    compatibilityDate,
+    bindings,
+    kvNamespaces,
+    analyticsEngineDatasets,
+    queueProducers,
+    d1Databases,
+    r2Buckets,
+    durableObjects,
+    serviceBindings,
+  });
+}
+
+/**
+ * Run `callback` against a worker started with `bindings`, disposing of the
+ * worker afterwards.
+ *
+ * @template T
+ * @param {Record<string, string>} bindings Vars and secrets to expose.
+ * @param {(miniflare: Miniflare) => Promise<T>} callback
+ * @param {Parameters<typeof startWorker>[1]} [options]
+ * @returns {Promise<T>}
+ */
+export async function withWorker(bindings, callback, options) {
+  const miniflare = startWorker(bindings, options);
+  try {
+    return await callback(miniflare);
+  } finally {
+    await miniflare.dispose();
+  }
+}
+
+/**
+ * Fetch `route` from the worker, assert the response status (200 by default)
+ * and return the parsed JSON body.
+ *
+ * @param {Miniflare} miniflare
+ * @param {string} route Path of the route, e.g. `/full`.
+ * @param {{ status?: number }} [options]
+ * @returns {Promise<any>}
+ */
+export async function fetchJson(miniflare, route, { status = 200 } = {}) {
+  const response = await miniflare.dispatchFetch(`http://localhost${route}`);
+  assert.equal(response.status, status, `unexpected status for ${route}`);
+  return response.json();
+}
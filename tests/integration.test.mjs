@@ -1,32 +1,28 @@
 import { describe, it, before, after } from "node:test";
 import assert from "node:assert/strict";
-import { Miniflare } from "miniflare";
-import path from "node:path";
-import { fileURLToPath } from "node:url";
-
-const __dirname = path.dirname(fileURLToPath(import.meta.url));
-const workerBuildPath = path.resolve(__dirname, "../test-worker/build");
+import { fetchJson, startWorker, withWorker } from "./harness.mjs";
 
 describe("figment2-cloudflare-workers", () => {
-  /** @type {Miniflare} */
+  /** @type {import("miniflare").Miniflare} */
   let miniflare;
 
   before(async () => {
-    miniflare = new Miniflare({
-      scriptPath: path.join(workerBuildPath, "worker", "shim.mjs"),
-      modules: true,
-      modulesRules: [
-        { type: "ESModule", include: ["**/*.js"], fallthrough: true },
-        { type: "CompiledWasm", include: ["**/*.wasm"], fallthrough: true },
-      ],
-      compatibilityDate: "2025-01-01",
-      bindings: {
-        // Plain vars.
-        API_BASE_URL: "https://api.example.com/v1",
-        MAX_RETRIES: "3",
-        // Simulated secret (not in wrangler.toml [vars]).
-        API_KEY: "super-secret-key",
-      },
+    miniflare = startWorker({
+      // Plain vars.
+      API_BASE_URL: "https://api.example.com/v1",
+      MAX_RETRIES: "3",
+      // Simulated secret (not in wrangler.toml [vars]).
+      API_KEY: "super-secret-key",
+      // AES-256-GCM encrypted var, bound to its name, and its key (bytes
+      // 0..32, base64).
+      API_TOKEN:
+        "enc:v1:BwcHBwcHBwcHBwcHaw/FLhZ/o5GisGfv6zSawEzeeL16qBh0h37frGhgFg==",
+      CONFIG_KEY: "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=",
+      // HMAC-SHA256 key for signed documents.
+      SIGNING_KEY: "signing-key",
+      // Binary key material.
+      BASE64_KEY: "AAECAw==",
+      HEX_KEY: "deadbeef",
     });
   });
 
@@ -35,65 +31,1133 @@ describe("figment2-cloudflare-workers", () => {
   });
 
   it("reads all fields and uppercases binding names", async () => {
-    const response = await miniflare.dispatchFetch("http://localhost/full");
-    assert.equal(response.status, 200);
-
-    const body = await response.json();
+    const body = await fetchJson(miniflare, "/full");
     assert.equal(body.api_base_url, "https://api.example.com/v1");
     assert.equal(body.api_key, "super-secret-key");
     assert.equal(body.max_retries, "3");
   });
 
   it("skips missing bindings (partial config with Option fields)", async () => {
-    const response = await miniflare.dispatchFetch("http://localhost/partial");
-    assert.equal(response.status, 200);
+    const body = await fetchJson(miniflare, "/partial");
+    assert.equal(body.api_base_url, "https://api.example.com/v1");
+    assert.equal(body.api_key, "super-secret-key");
+    assert.equal(body.missing_field, null);
+  });
 
-    const body = await response.json();
+  it("resolves the fields of several structs with one provider", async () => {
+    const body = await fetchJson(miniflare, "/structs");
+    assert.deepEqual(body.typed, { api_base_url: "https://api.example.com/v1", max_retries: 3 });
+    assert.equal(body.partial.api_key, "super-secret-key");
+    assert.equal(body.partial.missing_field, null);
+  });
+
+  it("ignores the process-env fallback inside the runtime", async () => {
+    const body = await fetchJson(miniflare, "/process-env-fallback");
     assert.equal(body.api_base_url, "https://api.example.com/v1");
     assert.equal(body.api_key, "super-secret-key");
     assert.equal(body.missing_field, null);
   });
 
-  it("extracts a single field", async () => {
-    const response = await miniflare.dispatchFetch("http://localhost/single");
-    assert.equal(response.status, 200);
+  it("applies per-field defaults only to missing bindings", async () => {
+    const body = await fetchJson(miniflare, "/defaults");
+    assert.equal(body.api_key, "super-secret-key");
+    assert.equal(body.missing_field, "default-value");
+  });
 
-    const body = await response.json();
+  it("fails when a required binding is missing", async () => {
+    const body = await fetchJson(miniflare, "/required");
+    assert.equal(body.error, true);
+    assert.match(body.message, /required bindings are missing: `MISSING_FIELD`/);
+  });
+
+  it("resolves only the selected fields", async () => {
+    const body = await fetchJson(miniflare, "/only");
+    assert.equal(body.api_key, "super-secret-key");
+    assert.deepEqual(body.fields, ["api_key"]);
+  });
+
+  it("defers selected fields to earlier providers", async () => {
+    const body = await fetchJson(miniflare, "/deferred");
+    assert.equal(body.api_base_url, "https://api.example.com/v1");
+    assert.equal(body.api_key, "super-secret-key");
+    assert.equal(body.max_retries, "9");
+  });
+
+  it("tags each value with the binding it came from", async () => {
+    const body = await fetchJson(miniflare, "/tagged");
+    assert.deepEqual(body, {
+      api_base_url: "var `API_BASE_URL`",
+      api_key: "var `API_KEY`",
+      max_retries: "JSON source string",
+    });
+  });
+
+  it("reads figment magic values from bindings", async () => {
+    const body = await fetchJson(miniflare, "/magic");
+    assert.deepEqual(body, {
+      api_base_url: "https://api.example.com/v1",
+      api_base_url_source: "var `API_BASE_URL`",
+      max_retries: 3,
+      max_retries_source: "var `MAX_RETRIES`",
+      config_dir: null,
+      config_dir_relative: null,
+    });
+  });
+
+  it("reads a non-integer magic value from the right of an `Either`", async () => {
+    await withWorker(
+      {
+        API_BASE_URL: "https://api.example.com/v1",
+        MAX_RETRIES: "unlimited",
+        CONFIG_DIR: "conf/app",
+      },
+      async (magicMiniflare) => {
+        const body = await fetchJson(magicMiniflare, "/magic");
+        assert.deepEqual(body, {
+          api_base_url: "https://api.example.com/v1",
+          api_base_url_source: "var `API_BASE_URL`",
+          max_retries: "unlimited",
+          max_retries_source: null,
+          // A path read from a binding is used as written.
+          config_dir: "conf/app",
+          config_dir_relative: "conf/app",
+        });
+      },
+    );
+  });
+
+  it("resolves only the fields under a focused key path", async () => {
+    const body = await fetchJson(miniflare, "/focus");
+    assert.deepEqual(body.service, { api_base_url: "https://api.example.com/v1", max_retries: 3 });
+    assert.deepEqual(body.retries, ["max_retries"]);
+    assert.match(body.extra, /required bindings are missing: `MISSING_FIELD`/);
+  });
+
+  it("nests emitted values under a key path", async () => {
+    const body = await fetchJson(miniflare, "/nested");
+    assert.deepEqual(body, {
+      name: "app",
+      service: { api_base_url: "https://api.example.com/v1" },
+    });
+  });
+
+  it("resolves each field against layered sources", async () => {
+    const body = await fetchJson(miniflare, "/layered");
+    assert.equal(body.api_base_url, "https://overlay.example.com");
+    assert.equal(body.api_key, "super-secret-key");
+    assert.equal(body.max_retries, "3");
+  });
+
+  it("extracts a single field", async () => {
+    const body = await fetchJson(miniflare, "/single");
     assert.equal(body.api_base_url, "https://api.example.com/v1");
   });
 
   it("supports custom profiles", async () => {
-    const response = await miniflare.dispatchFetch("http://localhost/profile");
-    assert.equal(response.status, 200);
+    const body = await fetchJson(miniflare, "/profile");
+    assert.equal(body.api_base_url, "https://api.example.com/v1");
+  });
+
+  it("reads the bindings a derived config declares", async () => {
+    const body = await fetchJson(miniflare, "/derived");
+    assert.deepEqual(body.config, {
+      api_base_url: "https://api.example.com/v1",
+      token: "super-secret-key",
+      retries: "3",
+    });
+    assert.equal(body.recorded.values.token, "[REDACTED]");
+  });
+
+  it("reads listed field names of a hand-deserialised config", async () => {
+    const body = await fetchJson(miniflare, "/field-names");
+    assert.deepEqual(body.named, {
+      api_base_url: "https://api.example.com/v1",
+      max_retries: "3",
+    });
+    assert.match(body.discovered, /cannot discover the fields of `.*MapConfig`/);
+  });
+
+  it("reads explicitly named fields of an untagged enum", async () => {
+    const body = await fetchJson(miniflare, "/untagged");
+    assert.deepEqual(body.named, { api_base_url: "https://api.example.com/v1" });
+    assert.match(body.discovered, /`.*UntaggedConfig`: it is not deserialised as a struct/);
+  });
+
+  it("serves a redacted diagnostics report", async () => {
+    const response = await miniflare.dispatchFetch("http://localhost/diagnostics");
+    assert.equal(response.headers.get("cache-control"), "no-store");
+    const body = await response.json();
+    assert.deepEqual(body.fields, [
+      {
+        field: "api_base_url",
+        binding: "API_BASE_URL",
+        source: "primary",
+        accessor: "var",
+        value: "https://api.example.com/v1",
+      },
+      {
+        field: "missing_field",
+        binding: "MISSING_FIELD",
+        source: null,
+        accessor: null,
+        value: null,
+      },
+      {
+        field: "api_key",
+        binding: "API_KEY",
+        source: "primary",
+        accessor: "var",
+        value: "[REDACTED]",
+      },
+    ]);
+    assert.match(body.error, /`MISSING_FIELD`/);
+  });
+
+  it("counts lookups, hits and misses", async () => {
+    const body = await fetchJson(miniflare, "/metrics");
+    assert.deepEqual(body.env, {
+      lookups: 3,
+      var_hits: 2,
+      secret_hits: 0,
+      misses: 1,
+      parse_failures: 0,
+    });
+    assert.equal(body.mock.secret_hits, 1);
+    assert.equal(body.mock.misses, 2);
+  });
+
+  it("renders the console debug table", async () => {
+    const response = await miniflare.dispatchFetch("http://localhost/console-debug");
+    assert.equal(
+      await response.text(),
+      [
+        "field          binding        source   accessor  value",
+        "api_base_url   API_BASE_URL   primary  var       https://api.example.com/v1",
+        "missing_field  MISSING_FIELD  -        -         -",
+        "api_key        API_KEY        primary  secret    [REDACTED]",
+        "",
+      ].join("\n"),
+    );
+  });
+
+  it("reports audit events as JSON lines", async () => {
+    const body = await fetchJson(miniflare, "/audit");
+    assert.deepEqual(JSON.parse(body.line), body.event);
+    assert.equal(body.event.audit, "figment2-cloudflare-workers");
+    assert.equal(body.event.version, 1);
+    assert.equal(body.event.event, "config.failed");
+    assert.equal(body.event.source, "test-worker");
+    assert.deepEqual(body.event.missing, []);
+    assert.match(body.event.error, /invalid type/);
+    assert.equal(body.other, null);
+  });
 
+  it("writes an Analytics Engine data point on failure", async () => {
+    await withWorker(
+      {},
+      async (analyticsMiniflare) => {
+        const body = await fetchJson(analyticsMiniflare, "/analytics");
+        assert.match(body.error, /`API_KEY`/);
+        // The failed merge wrote one data point, naming the missing binding.
+        assert.equal(body.points.length, 1);
+        const [point] = body.points;
+        assert.deepEqual(point.indexes, ["test-version"]);
+        assert.deepEqual(point.blobs.slice(0, 2), ["test-version", "API_KEY"]);
+        assert.match(point.blobs[2], /required bindings are missing: `API_KEY`/);
+        assert.deepEqual(point.doubles, [1]);
+        assert.equal(body.written, true);
+      },
+      { analyticsEngineDatasets: { CONFIG_FAILURES: { dataset: "config_failures" } } },
+    );
+  });
+
+  it("reports load timings", async () => {
+    const body = await fetchJson(miniflare, "/timings");
+    assert.deepEqual(Object.keys(body), ["lookups_ms", "fetches_ms", "extraction_ms"]);
+    for (const value of Object.values(body)) {
+      assert.ok(value >= 0, `${value} should be a non-negative duration`);
+    }
+    assert.equal(body.fetches_ms, 0);
+  });
+
+  it("exports a JSON resolution trace", async () => {
+    const response = await miniflare.dispatchFetch("http://localhost/trace");
     const body = await response.json();
+    assert.equal(body.lookup_order, "var_then_secret");
+    assert.deepEqual(body.layers, ["primary", "fallback 1", "default"]);
+    assert.deepEqual(body.fields, [
+      {
+        field: "missing_field",
+        binding: "MISSING_FIELD",
+        required: false,
+        deferred: false,
+        candidates: [
+          { source: "primary", accessor: "var", hit: false },
+          { source: "primary", accessor: "secret", hit: false },
+          { source: "fallback 1", accessor: "var", hit: false },
+          { source: "fallback 1", accessor: "secret", hit: false },
+        ],
+        winner: { source: "default", accessor: null },
+      },
+      {
+        field: "api_key",
+        binding: "API_KEY",
+        required: false,
+        deferred: false,
+        candidates: [{ source: "primary", accessor: "var", hit: true }],
+        winner: { source: "primary", accessor: "var" },
+      },
+    ]);
+    assert.equal(body.error, null);
+  });
+
+  it("publishes configuration changes to a queue", async () => {
+    await withWorker(
+      {},
+      async (queueMiniflare) => {
+        const body = await fetchJson(queueMiniflare, "/notify");
+        assert.deepEqual(body, [false, false, true]);
+      },
+      { queueProducers: { CONFIG_CHANGES: "config-changes" } },
+    );
+  });
+
+  it("describes the bindings a config reads", async () => {
+    const body = await fetchJson(miniflare, "/describe");
+    assert.deepEqual(body, [
+      { field: "api_base_url", binding: "API_BASE_URL", type: "string", required: true },
+      { field: "max_retries", binding: "MAX_RETRIES", type: "u8", required: true },
+      { field: "api_key", binding: "API_KEY", type: "option<string>", required: false },
+      { field: "verbose", binding: "VERBOSE", type: "bool", required: false },
+    ]);
+  });
+
+  it("layers bindings over embedded wrangler.toml vars", async () => {
+    const body = await fetchJson(miniflare, "/wrangler-defaults");
+    assert.deepEqual(body.top_level, {
+      api_base_url: "https://api.example.com/v1",
+      max_retries: "3",
+    });
+    assert.equal(body.staging.api_base_url, "https://api.example.com/v1");
+    assert.equal(body.staging.max_retries, "5");
+    assert.equal(body.staging.log_level, "debug");
+  });
+
+  it("rejects bindings provisioned as the wrong kind", async () => {
+    const body = await fetchJson(miniflare, "/declared-kinds");
+    assert.equal(body.provisioned.api_key, "mock-secret");
+    assert.match(body.misprovisioned, /`API_KEY` is declared a secret/);
+  });
+
+  it("merges one shared provider into several figments", async () => {
+    const body = await fetchJson(miniflare, "/shared");
+    assert.deepEqual(body.default, body.staging);
+    assert.equal(body.default.api_base_url, "https://api.example.com/v1");
+  });
+
+  it("supports secret-first lookup order", async () => {
+    const { config, colliding } = await fetchJson(miniflare, "/secret-first");
+    assert.equal(config.api_base_url, "https://api.example.com/v1");
+    assert.equal(config.api_key, "super-secret-key");
+    assert.equal(config.max_retries, "3");
+    // A name bound as both a var and a secret.
+    assert.deepEqual(colliding, {
+      var_then_secret: "stray-var",
+      secret_then_var: "super-secret-key",
+    });
+  });
+
+  it("masks secret-derived fields when redacting", async () => {
+    const body = await fetchJson(miniflare, "/redacted");
+    assert.equal(body.api_base_url, "https://api.example.com/v1");
+    assert.equal(body.api_key, "[REDACTED]");
+    assert.equal(body.max_retries, "3");
+  });
+
+  it("decrypts encrypted values with a key binding", async () => {
+    const body = await fetchJson(miniflare, "/encrypted");
+    assert.equal(body.api_base_url, "https://api.example.com/v1");
+    assert.equal(body.api_token, "decrypted-token");
+  });
+
+  it("merges signed documents only when the signature verifies", async () => {
+    const signed = await fetchJson(miniflare, "/signed");
+    assert.equal(signed.api_base_url, "https://signed.example.com");
+
+    const body = await fetchJson(miniflare, "/signed-tampered");
+    assert.equal(body.error, true);
+    assert.match(body.message, /signature verification failed/);
+  });
+
+  it("decodes base64 and hex bindings into secret byte buffers", async () => {
+    const body = await fetchJson(miniflare, "/binary-secret");
+    assert.deepEqual(body.base64_key, [0, 1, 2, 3]);
+    assert.deepEqual(body.hex_key, [0xde, 0xad, 0xbe, 0xef]);
+  });
+
+  it("reads from in-memory mock bindings", async () => {
+    const body = await fetchJson(miniflare, "/mock");
+    assert.equal(body.api_base_url, "https://mock.example.com");
+    assert.equal(body.api_key, "mock-secret");
+    assert.equal(body.missing_field, null);
+  });
+
+  it("records and replays snapshots with secrets masked", async () => {
+    const { recorded, replayed } = await fetchJson(miniflare, "/snapshot");
+    assert.equal(recorded.profile, "default");
+    assert.deepEqual(recorded.secrets, ["api_key"]);
+    assert.deepEqual(recorded.values, {
+      api_base_url: "https://api.example.com/v1",
+      api_key: "[REDACTED]",
+      max_retries: "3",
+    });
+    assert.deepEqual(replayed, recorded.values);
+  });
+
+  it("masks the secrets of an Env only when declared", async () => {
+    const { undeclared } = await fetchJson(miniflare, "/snapshot");
+    assert.deepEqual(undeclared.secrets, []);
+    assert.equal(undeclared.values.api_key, "super-secret-key");
+  });
+
+  it("logs providers and snapshots with secrets masked", async () => {
+    const { debug, serialized, snapshot } = await fetchJson(miniflare, "/inspect");
+    for (const logged of [debug, JSON.stringify(serialized), snapshot]) {
+      assert.doesNotMatch(logged, /super-secret-key|fallback-secret-key/);
+    }
+    assert.match(debug, /^CloudflareWorkersBindings \{ fields: \[FieldEntry \{ name: "api_base_url"/);
+    assert.deepEqual(serialized.secrets, ["api_key"]);
+    assert.deepEqual(serialized.defaults, { api_key: "[REDACTED]" });
+    assert.equal(serialized.metrics.lookups, 3);
+    assert.match(snapshot, /"api_key": String\("\[REDACTED\]"\)/);
+  });
+
+  it("extracts from a snapshot inside a Send future", async () => {
+    const body = await fetchJson(miniflare, "/send-snapshot");
+    assert.equal(body.api_base_url, "https://api.example.com/v1");
+    assert.equal(body.api_key, "super-secret-key");
+  });
+
+  it("matches the resolved configuration against a golden document", async () => {
+    const body = await fetchJson(miniflare, "/golden");
+    assert.equal(body.matched, true);
+  });
+
+  it("fingerprints the resolved configuration", async () => {
+    const body = await fetchJson(miniflare, "/fingerprint");
+    assert.match(body.fingerprint, /^[0-9a-f]{64}$/);
+    assert.equal(body.repeated, body.fingerprint);
+    assert.notEqual(body.resalted, body.fingerprint);
+  });
+
+  it("diffs snapshots with secret values redacted", async () => {
+    const body = await fetchJson(miniflare, "/diff");
+    assert.deepEqual(body, {
+      added: {},
+      removed: { max_retries: "3" },
+      changed: {
+        api_base_url: {
+          old: "https://api.example.com/v1",
+          new: "https://api.example.com/v2",
+        },
+        api_key: { old: "[REDACTED]", new: "[REDACTED]" },
+      },
+    });
+  });
+
+  it("reads every binding without a struct", async () => {
+    const body = await fetchJson(miniflare, "/all");
+    assert.equal(body.api_base_url, "https://api.example.com/v1");
+    assert.equal(body.api_key, "super-secret-key");
+    assert.equal(body.max_retries, "3");
+    assert.equal(body.hex_key, "deadbeef");
+  });
+
+  it("reads the bindings of a Pages project", async () => {
+    await withWorker(
+      {
+        API_BASE_URL: "https://pages.example.com",
+        MAX_RETRIES: "2",
+        CF_PAGES: "1",
+        CF_PAGES_BRANCH: "preview",
+        CF_PAGES_COMMIT_SHA: "0a1b2c3",
+        CF_PAGES_URL: "https://0a1b2c3.project.pages.dev",
+      },
+      async (pagesMiniflare) => {
+        assert.deepEqual(await fetchJson(pagesMiniflare, "/pages"), {
+          bindings: { api_base_url: "https://pages.example.com", max_retries: "2" },
+          branch: "preview",
+          config: { api_base_url: "https://pages.example.com", max_retries: 2 },
+          error: "the bindings of a Pages project must be an object",
+        });
+      },
+      { serviceBindings: { ASSETS: () => new Response("asset") } },
+    );
+  });
+
+  it("reads the env as a plain JS object", async () => {
+    assert.deepEqual(await fetchJson(miniflare, "/js-bindings"), {
+      config: { api_base_url: "https://api.example.com/v1", max_retries: 3 },
+      not_object: true,
+    });
+  });
+
+  it("reads a plain JS object in place of the env", async () => {
+    assert.deepEqual(await fetchJson(miniflare, "/js-object"), {
+      config: { api_base_url: "https://js.example.com", max_retries: 4 },
+      all: { api_base_url: "https://js.example.com", max_retries: "4" },
+    });
+  });
+
+  it("reads through an owned provider", async () => {
+    const body = await fetchJson(miniflare, "/owned");
+    assert.equal(body.api_base_url, "https://api.example.com/v1");
+    assert.equal(body.api_key, "super-secret-key");
+    assert.equal(body.max_retries, "3");
+  });
+
+  it("merges bindings through the Figment extension trait", async () => {
+    const body = await fetchJson(miniflare, "/figment-ext");
     assert.equal(body.api_base_url, "https://api.example.com/v1");
+    assert.equal(body.api_key, "super-secret-key");
+    assert.equal(body.max_retries, "3");
+  });
+
+  it("extracts typed configuration in one call", async () => {
+    const body = await fetchJson(miniflare, "/extract-config");
+    assert.equal(body.api_base_url, "https://api.example.com/v1");
+    assert.equal(body.max_retries, 3);
+  });
+
+  it("deserialises bindings directly, without a figment", async () => {
+    assert.deepEqual(await fetchJson(miniflare, "/from-env"), {
+      config: { api_base_url: "https://api.example.com/v1", max_retries: 3 },
+      error:
+        'invalid value string "3", expected a boolean for key "default.max_retries" in binding `MAX_RETRIES`',
+    });
+  });
+
+  it("caches extracted configuration until invalidated", async () => {
+    const body = await fetchJson(miniflare, "/cached");
+    assert.equal(body.config.max_retries, 3);
+    assert.equal(body.shared, true);
+    assert.equal(body.cached_after_invalidate, false);
+    assert.equal(body.reextracted, true);
+  });
+
+  it("extracts configuration in the start event", async () => {
+    const body = await fetchJson(miniflare, "/startup");
+    assert.equal(body.extracted_at_startup, true);
+    assert.equal(body.config.max_retries, 3);
+  });
+
+  it("injects configuration into router data", async () => {
+    const body = await fetchJson(miniflare, "/router");
+    assert.equal(body.max_retries, 3);
+    const cached = await fetchJson(miniflare, "/router/cached");
+    assert.equal(cached.cached, true);
+    assert.equal(cached.config.max_retries, 3);
+  });
+
+  it("inserts configuration into request extensions", async () => {
+    const body = await fetchJson(miniflare, "/tower");
+    assert.equal(body.config.max_retries, 3);
+    assert.equal(body.shared, true);
+  });
+
+  it("extracts configuration in axum handlers", async () => {
+    const body = await fetchJson(miniflare, "/axum");
+    assert.equal(body.config.max_retries, 3);
+  });
+
+  it("migrates older configuration documents to the current schema", async () => {
+    const body = await fetchJson(miniflare, "/migrate");
+    assert.deepEqual(body.migrated, [
+      { api_base_url: "https://v0.example.com", max_retries: 1 },
+      { api_base_url: "https://v1.example.com", max_retries: 2 },
+      { api_base_url: "https://v2.example.com", max_retries: 3 },
+    ]);
+    assert.match(body.newer, /version 3 is newer than the supported version 2/);
+  });
+
+  it("applies canary configuration only when it passes its checks", async () => {
+    const [applied, rejected, invalid] = await fetchJson(miniflare, "/canary");
+    assert.deepEqual(applied, { applied: true, rejection: null, max_retries: 4 });
+    assert.deepEqual(rejected, {
+      applied: false,
+      rejection: "9 retries is too many",
+      max_retries: 3,
+    });
+    assert.equal(invalid.applied, false);
+    assert.match(invalid.rejection, /max_retries/);
+    assert.equal(invalid.max_retries, 3);
+  });
+
+  it("caches extraction failures by a retry policy", async () => {
+    const body = await fetchJson(miniflare, "/config-cell");
+    assert.deepEqual(body.retried, [false, true]);
+    assert.deepEqual(body.held, [false, false]);
+    assert.equal(body.after_reset, true);
+    assert.equal(body.shared, true);
+  });
+
+  it("swaps shared configuration while keeping held values", async () => {
+    const body = await fetchJson(miniflare, "/shared-config");
+    assert.equal(body.first.max_retries, 3);
+    assert.equal(body.current.max_retries, 6);
+    assert.equal(body.kept_after_failure, true);
+  });
+
+  it("resolves staged rollout values by a stable key", async () => {
+    const body = await fetchJson(miniflare, "/rollout");
+    const [none, half, all] = body.upgraded;
+    assert.equal(none, 0);
+    assert.equal(all, 100);
+    assert.ok(half > 30 && half < 70, `${half} of 100 keys upgraded`);
+    assert.equal(body.repeated, true);
+  });
+
+  it("chooses weighted A/B variants by a stable key", async () => {
+    const body = await fetchJson(miniflare, "/variant");
+    assert.deepEqual(body.names, ["aggressive", "control"]);
+    const { aggressive, control } = body.arms;
+    assert.equal(aggressive + control, 400);
+    assert.ok(aggressive > 240 && aggressive < 360, `${aggressive} of 400 aggressive`);
+    const [arm, value] = body.user;
+    assert.equal(value, arm === "control" ? 3 : 9);
+    assert.equal(body.repeated, true);
+  });
+
+  it("layers KV values over vars and caches them in the isolate", async () => {
+    await withWorker(
+      { API_BASE_URL: "https://api.example.com/v1", MAX_RETRIES: "3" },
+      async (kvMiniflare) => {
+        const kv = await kvMiniflare.getKVNamespace("CONFIG");
+        await kv.put("MAX_RETRIES", "5");
+        const body = await fetchJson(kvMiniflare, "/kv");
+        assert.equal(body.api_base_url, "https://api.example.com/v1");
+        assert.equal(body.max_retries, 5);
+
+        // Within `max_age`, the isolate keeps serving the cached value.
+        await kv.put("MAX_RETRIES", "7");
+        const cached = await fetchJson(kvMiniflare, "/kv");
+        assert.equal(cached.max_retries, 5);
+      },
+      { kvNamespaces: ["CONFIG"] },
+    );
+  });
+
+  it("stacks an R2 document, vars and KV in order of precedence", async () => {
+    await withWorker(
+      { MAX_RETRIES: "3" },
+      async (stackMiniflare) => {
+        const bucket = await stackMiniflare.getR2Bucket("CONFIG_BUCKET");
+        await bucket.put(
+          "app.toml",
+          'api_base_url = "https://r2.example.com"\nmax_retries = 1\n',
+        );
+        assert.deepEqual(await fetchJson(stackMiniflare, "/stack"), {
+          api_base_url: "https://r2.example.com",
+          max_retries: 3,
+        });
+
+        const kv = await stackMiniflare.getKVNamespace("CONFIG");
+        await kv.put("MAX_RETRIES", "5");
+        assert.equal((await fetchJson(stackMiniflare, "/stack")).max_retries, 5);
+      },
+      { kvNamespaces: ["CONFIG"], r2Buckets: ["CONFIG_BUCKET"] },
+    );
+  });
+
+  it("requires and looks up bindings only in the profiles named", async () => {
+    await withWorker(
+      { API_BASE_URL: "https://api.example.com/v1", SENTRY_DSN: "https://sentry.example.com/1" },
+      async (profileMiniflare) => {
+        assert.deepEqual(await fetchJson(profileMiniflare, "/when-profile"), {
+          dev: { api_base_url: "https://api.example.com/v1", sentry_dsn: null },
+          prod: {
+            api_base_url: "https://api.example.com/v1",
+            sentry_dsn: "https://sentry.example.com/1",
+          },
+          unbound_prod: {
+            error:
+              "required bindings are missing: `SENTRY_DSN` in Cloudflare Worker environment",
+          },
+        });
+      },
+    );
+  });
+
+  it("accumulates lists across the file, vars and KV", async () => {
+    await withWorker(
+      {
+        ALLOWED_ORIGINS: "https://admin.example.com, https://docs.example.com",
+        MAX_RETRIES: "3",
+      },
+      async (accumulatedMiniflare) => {
+        const kv = await accumulatedMiniflare.getKVNamespace("CONFIG");
+        await kv.put("ALLOWED_ORIGINS", "https://kv.example.com");
+        await kv.put("MAX_RETRIES", "5");
+        assert.deepEqual(await fetchJson(accumulatedMiniflare, "/accumulated"), {
+          allowed_origins: [
+            "https://app.example.com",
+            "https://admin.example.com",
+            "https://docs.example.com",
+            "https://kv.example.com",
+          ],
+          max_retries: 5,
+        });
+      },
+      { kvNamespaces: ["CONFIG"] },
+    );
+  });
+
+  it("layers per-hostname tenant overrides from KV", async () => {
+    await withWorker(
+      { API_BASE_URL: "https://api.example.com/v1", MAX_RETRIES: "3" },
+      async (tenantMiniflare) => {
+        const kv = await tenantMiniflare.getKVNamespace("CONFIG");
+        await kv.put("acme.example.com/MAX_RETRIES", "8");
+        await kv.put("MAX_RETRIES", "5");
+        const tenant = async (host) =>
+          (await tenantMiniflare.dispatchFetch(`http://${host}/tenant`)).json();
+        assert.deepEqual(await tenant("acme.example.com"), {
+          api_base_url: "https://api.example.com/v1",
+          max_retries: 8,
+        });
+        // Tenants without overrides get the shared configuration.
+        assert.equal((await tenant("other.example.com")).max_retries, 3);
+      },
+      { kvNamespaces: ["CONFIG"] },
+    );
+  });
+
+  it("reads feature flags from KV with defaults from vars", async () => {
+    await withWorker(
+      {
+        MAX_RETRIES: "3",
+        FLAG_DARK_MODE: "true",
+        FLAG_CHECKOUT_LAYOUT: "compact",
+      },
+      async (flagsMiniflare) => {
+        const kv = await flagsMiniflare.getKVNamespace("FLAG_STORE");
+        await kv.put("FLAG_NEW_CHECKOUT", "On");
+        await kv.put("FLAG_DARK_MODE", "false");
+        const body = await fetchJson(flagsMiniflare, "/flags");
+        assert.deepEqual(body, {
+          new_checkout: true,
+          dark_mode: false,
+          unset: false,
+          layout: "compact",
+          names: ["checkout_layout", "dark_mode", "new_checkout"],
+          config: {
+            max_retries: 3,
+            flags: {
+              new_checkout: true,
+              dark_mode: false,
+              checkout_layout: "compact",
+            },
+          },
+        });
+      },
+      { kvNamespaces: ["FLAG_STORE"] },
+    );
+  });
+
+  it("calls back when a watched KV version changes the configuration", async () => {
+    await withWorker(
+      { API_BASE_URL: "https://api.example.com/v1", MAX_RETRIES: "3" },
+      async (kvMiniflare) => {
+        const kv = await kvMiniflare.getKVNamespace("CONFIG");
+        await kv.put("MAX_RETRIES", "5");
+        const first = await fetchJson(kvMiniflare, "/watch");
+        assert.equal(first.changed, true);
+        assert.equal(first.config.max_retries, 5);
+        assert.equal((await fetchJson(kvMiniflare, "/watch")).changed, false);
+
+        await kv.put("MAX_RETRIES", "6");
+        await kv.put("CONFIG_VERSION", "1");
+        const second = await fetchJson(kvMiniflare, "/watch");
+        assert.equal(second.changed, true);
+        assert.equal(second.config.max_retries, 6);
+
+        // A new version with the same values does not call back.
+        await kv.put("CONFIG_VERSION", "2");
+        assert.equal((await fetchJson(kvMiniflare, "/watch")).changed, false);
+      },
+      { kvNamespaces: ["CONFIG"] },
+    );
+  });
+
+  it("revalidates remote configuration documents with conditional requests", async () => {
+    const conditions = [];
+    const documents = ['{"max_retries":1}', '{"max_retries":1}', '{"max_retries":2}'];
+    await withWorker(
+      {},
+      async (remoteMiniflare) => {
+        const body = await fetchJson(remoteMiniflare, "/remote");
+        assert.deepEqual(body, [
+          { modified: true, document: '{"max_retries":1}' },
+          { modified: false, document: '{"max_retries":1}' },
+          { modified: true, document: '{"max_retries":2}' },
+        ]);
+        assert.deepEqual(conditions, [null, '"0"', '"0"']);
+      },
+      {
+        serviceBindings: {
+          ORIGIN: (request) => {
+            const condition = request.headers.get("If-None-Match");
+            const document = documents[conditions.length];
+            const etag = `"${documents.indexOf(document)}"`;
+            conditions.push(condition);
+            if (condition === etag) {
+              return new Response(null, { status: 304, headers: { ETag: etag } });
+            }
+            return new Response(document, { headers: { ETag: etag } });
+          },
+        },
+      },
+    );
+  });
+
+  it("applies request header overrides only with the shared secret", async () => {
+    const override = (miniflare, token) =>
+      miniflare.dispatchFetch("http://localhost/override", {
+        headers: {
+          "X-Config-Override": '{"max_retries": 7}',
+          ...(token !== undefined && { "X-Config-Override-Token": token }),
+        },
+      });
+    await withWorker(
+      { API_BASE_URL: "https://api.example.com/v1", MAX_RETRIES: "3", OVERRIDE_TOKEN: "s3cret" },
+      async (previewMiniflare) => {
+        assert.equal((await fetchJson(previewMiniflare, "/override")).config.max_retries, 3);
+        const applied = await (await override(previewMiniflare, "s3cret")).json();
+        assert.equal(applied.config.max_retries, 7);
+        assert.deepEqual(applied.keys, ["max_retries"]);
+        for (const token of ["wrong", undefined]) {
+          const rejected = await override(previewMiniflare, token);
+          assert.equal(rejected.status, 403);
+          assert.match((await rejected.json()).error, /X-Config-Override-Token/);
+        }
+      },
+    );
+    // Without the secret bound, overrides are ignored.
+    await withWorker(
+      { API_BASE_URL: "https://api.example.com/v1", MAX_RETRIES: "3" },
+      async (productionMiniflare) => {
+        const ignored = await (await override(productionMiniflare, "s3cret")).json();
+        assert.equal(ignored.config.max_retries, 3);
+        assert.deepEqual(ignored.keys, []);
+      },
+    );
+    // An empty secret is as good as none, even to an empty token.
+    await withWorker(
+      { API_BASE_URL: "https://api.example.com/v1", MAX_RETRIES: "3", OVERRIDE_TOKEN: "" },
+      async (emptyTokenMiniflare) => {
+        const ignored = await (await override(emptyTokenMiniflare, "")).json();
+        assert.equal(ignored.config.max_retries, 3);
+        assert.deepEqual(ignored.keys, []);
+      },
+    );
+  });
+
+  it("applies query overrides of allowed fields on preview hostnames", async () => {
+    const query = async (host, search) => {
+      const response = await miniflare.dispatchFetch(`http://${host}/query-override?${search}`);
+      return { status: response.status, body: await response.json() };
+    };
+    const preview = "branch.preview.example.com";
+    assert.deepEqual(await query(preview, "cfg.max_retries=9&utm_source=x"), {
+      status: 200,
+      body: { max_retries: "9", keys: ["max_retries"] },
+    });
+    // Other hostnames ignore the query.
+    assert.deepEqual((await query("example.com", "cfg.max_retries=9")).body, {
+      max_retries: "3",
+      keys: [],
+    });
+    const secret = await query(preview, "cfg.api_key=stolen");
+    assert.equal(secret.status, 403);
+    assert.match(secret.body.error, /`api_key` is secret/);
+    const unlisted = await query(preview, "cfg.api_base_url=https://evil.example.com");
+    assert.equal(unlisted.status, 403);
+    assert.match(unlisted.body.error, /may not be overridden/);
+  });
+
+  it("expands references between bindings", async () => {
+    await withWorker(
+      {
+        API_HOST: "api.example.com",
+        API_BASE_URL: "https://${API_HOST}/v1?cost=$${literal}",
+        MAX_RETRIES: "3",
+      },
+      async (interpolatedMiniflare) => {
+        const body = await fetchJson(interpolatedMiniflare, "/interpolate");
+        assert.equal(body.config.api_base_url, "https://api.example.com/v1?cost=${literal}");
+        assert.match(body.cycle, /API_BASE_URL -> API_HOST -> API_BASE_URL/);
+      },
+    );
+  });
+
+  it("fills in placeholders from the request", async () => {
+    await withWorker(
+      { API_BASE_URL: "https://{colo}.{hostname}/v1?q={\"a\":1}", MAX_RETRIES: "3" },
+      async (templatedMiniflare) => {
+        const response = await templatedMiniflare.dispatchFetch(
+          "http://origin.example.com/templated",
+          { cf: { colo: "LHR", country: "GB" } },
+        );
+        assert.equal(response.status, 200);
+        const body = await response.json();
+        assert.equal(body.config.api_base_url, 'https://LHR.origin.example.com/v1?q={"a":1}');
+        assert.match(body.missing, /uses `\{city\}`, which the request does not carry/);
+      },
+    );
+  });
+
+  it("layers bindings over a whole-config JSON binding", async () => {
+    await withWorker(
+      {
+        APP_CONFIG: JSON.stringify({ api_base_url: "https://api.example.com/v1", max_retries: 5 }),
+        MAX_RETRIES: "7",
+      },
+      async (documentMiniflare) => {
+        const body = await fetchJson(documentMiniflare, "/json-binding");
+        assert.deepEqual(body.config, { api_base_url: "https://api.example.com/v1", max_retries: 7 });
+        assert.match(body.invalid, /binding `APP_CONFIG` is not a JSON object/);
+      },
+    );
+  });
+
+  it("reads a field from the first of its aliases that is bound", async () => {
+    assert.equal((await fetchJson(miniflare, "/alias")).api_base_url, "https://api.example.com/v1");
+    await withWorker(
+      {
+        LEGACY_API_URL: "https://legacy.example.com",
+        API_BASE_URL: "https://api.example.com/v1",
+        MAX_RETRIES: "3",
+      },
+      async (legacyMiniflare) => {
+        const body = await fetchJson(legacyMiniflare, "/alias");
+        assert.equal(body.api_base_url, "https://legacy.example.com");
+      },
+    );
+  });
+
+  it("finds bindings under other casings", async () => {
+    await withWorker(
+      { api_base_url: "https://api.example.com/v1", MAX_RETRIES: "3" },
+      async (casedMiniflare) => {
+        const body = await fetchJson(casedMiniflare, "/cases");
+        assert.deepEqual(body.config, { api_base_url: "https://api.example.com/v1", max_retries: 3 });
+        assert.deepEqual(body.candidates, [
+          { source: "primary", accessor: "var", hit: false },
+          { source: "primary", accessor: "secret", hit: false },
+          { binding: "api_base_url", source: "primary", accessor: "var", hit: true },
+        ]);
+        assert.match(body.strict, /api_base_url/);
+      },
+    );
+  });
+
+  it("parses semver versions and requirements", async () => {
+    const body = await fetchJson(miniflare, "/versions");
+    assert.equal(body.min_client_version, "1.4.2");
+    assert.equal(body.supported, true);
+    assert.match(body.invalid, /invalid semver version `v1\.4` \(write it without the leading `v`\)/);
+    assert.match(body.invalid, /min_client_version/);
+  });
+
+  it("parses UUIDs and lists of them", async () => {
+    const body = await fetchJson(miniflare, "/uuids");
+    assert.equal(body.workspace_id, "6f1c0a1e-8d5b-4b8e-9a43-0d2f7c1e5b6a");
+    assert.deepEqual(body.tenant_ids, [
+      "0b5e1f4c-2a3d-4c5e-8f60-718293a4b5c6",
+      "0b5e1f4c-2a3d-4c5e-8f60-718293a4b5c7",
+    ]);
+    assert.match(body.invalid, /invalid UUID `tenant-2`/);
+    assert.match(body.invalid, /tenant_ids/);
+  });
+
+  it("checks values against patterns", async () => {
+    const body = await fetchJson(miniflare, "/matches");
+    assert.equal(body.config.api_base_url, "https://api.example.com/v1");
+    assert.match(
+      body.mismatch,
+      /binding `API_BASE_URL` for field `api_base_url` does not match `\\.internal\$`/,
+    );
+    assert.doesNotMatch(body.mismatch, /api\.example\.com/);
+    assert.match(body.invalid, /invalid pattern `\(` for field `api_base_url`/);
+  });
+
+  it("reports every value outside its range", async () => {
+    const body = await fetchJson(miniflare, "/range");
+    assert.equal(body.config.max_retries, 3);
+    assert.equal(body.errors.length, 2);
+    assert.match(
+      body.errors[0],
+      /binding `MAX_RETRIES` for field `max_retries` is `3`, but must be a number at least 1 and at most 2/,
+    );
+    assert.match(body.errors[1], /binding `API_BASE_URL` .* must be a number at least 0 and at most 1/);
+  });
+
+  it("reports validator violations with their keys and bindings", async () => {
+    const body = await fetchJson(miniflare, "/validated");
+    assert.equal(body.errors.length, 2);
+    assert.match(
+      body.errors[0],
+      /binding `API_BASE_URL` failed validation: length \(max = 10\) for key "default.api_base_url"/,
+    );
+    assert.match(
+      body.errors[1],
+      /binding `MAX_RETRIES` failed validation: at most two retries for key "default.max_retries"/,
+    );
+  });
+
+  it("reports garde violations with their keys and bindings", async () => {
+    const body = await fetchJson(miniflare, "/validated-garde");
+    assert.deepEqual(body.errors, [
+      'binding `API_BASE_URL` failed validation: length is greater than 10 for key "default.api_base_url" in Cloudflare Worker environment',
+      'binding `MAX_RETRIES` failed validation: greater than 2 for key "default.max_retries" in Cloudflare Worker environment',
+    ]);
+  });
+
+  it("writes KV values back with a version check", async () => {
+    await withWorker(
+      { API_BASE_URL: "https://api.example.com/v1", MAX_RETRIES: "3" },
+      async (kvMiniflare) => {
+        const kv = await kvMiniflare.getKVNamespace("CONFIG");
+        await kv.put("MAX_RETRIES", "5");
+        await kv.put("CONFIG_VERSION", "4");
+        const body = await fetchJson(kvMiniflare, "/kv-put");
+        assert.equal(body.version, 4);
+        assert.equal(body.written, 5);
+        assert.match(body.conflict, /expected 4, found 5/);
+        assert.equal(body.config.max_retries, 9);
+        assert.equal(await kv.get("MAX_RETRIES"), "9");
+        assert.equal(await kv.get("CONFIG_VERSION"), "5");
+      },
+      { kvNamespaces: ["CONFIG"] },
+    );
+  });
+
+  it("migrates and writes a D1 config table", async () => {
+    await withWorker(
+      { API_BASE_URL: "https://api.example.com/v1", MAX_RETRIES: "3" },
+      async (d1Miniflare) => {
+        // A table created before the `updated_at` column existed.
+        const db = await d1Miniflare.getD1Database("CONFIG_DB");
+        await db
+          .prepare(
+            "CREATE TABLE config (key TEXT PRIMARY KEY NOT NULL, value TEXT NOT NULL)",
+          )
+          .run();
+        await db
+          .prepare("INSERT INTO config (key, value) VALUES ('STALE', 'y')")
+          .run();
+        const body = await fetchJson(d1Miniflare, "/d1");
+        assert.equal(body.loaded, 1);
+        assert.equal(body.config.max_retries, 9);
+        assert.equal(body.config.api_base_url, "https://api.example.com/v1");
+        const { results } = await db
+          .prepare("SELECT key, value, updated_at FROM config ORDER BY key")
+          .all();
+        assert.deepEqual(
+          results.map(({ key, value }) => [key, value]),
+          [
+            ["MAX_RETRIES", "9"],
+            ["UNRELATED", "x"],
+          ],
+        );
+        assert.ok(results.every(({ updated_at }) => updated_at > 0));
+      },
+      { d1Databases: ["CONFIG_DB"] },
+    );
+  });
+
+  it("serves and reloads configuration behind a bearer token", async () => {
+    await withWorker(
+      {
+        ADMIN_TOKEN: "s3cret",
+        API_BASE_URL: "https://api.example.com/v1",
+        MAX_RETRIES: "3",
+      },
+      async (adminMiniflare) => {
+        const request = (path, { method = "GET", token = "s3cret" } = {}) =>
+          adminMiniflare.dispatchFetch(`http://localhost${path}`, {
+            method,
+            headers: token === null ? {} : { Authorization: `Bearer ${token}` },
+          });
+
+        assert.equal((await request("/admin", { token: null })).status, 401);
+        assert.equal((await request("/admin", { token: "guess!" })).status, 401);
+        assert.equal((await request("/admin", { token: "" })).status, 401);
+
+        const served = await request("/admin");
+        assert.equal(served.status, 200);
+        assert.equal(served.headers.get("Cache-Control"), "no-store");
+        assert.deepEqual(await served.json(), {
+          api_base_url: "[REDACTED]",
+          max_retries: 3,
+        });
+
+        // The cached configuration is served until it is reloaded.
+        const kv = await adminMiniflare.getKVNamespace("CONFIG");
+        await kv.put("MAX_RETRIES", "8");
+        assert.equal((await (await request("/admin")).json()).max_retries, 3);
+        assert.equal((await request("/admin/reload")).status, 405);
+        const reloaded = await request("/admin/reload", { method: "POST" });
+        assert.equal(reloaded.status, 200);
+        assert.equal((await reloaded.json()).max_retries, 8);
+        assert.equal((await (await request("/admin")).json()).max_retries, 8);
+
+        // A failed reload keeps the configuration in place.
+        await kv.put("MAX_RETRIES", "many");
+        const failed = await request("/admin/reload", { method: "POST" });
+        assert.equal(failed.status, 500);
+        assert.match((await failed.json()).error, /max_retries|MAX_RETRIES/);
+        assert.equal((await (await request("/admin")).json()).max_retries, 8);
+      },
+      { kvNamespaces: ["CONFIG"] },
+    );
+  });
+
+  it("disables the admin endpoint while its token is empty", async () => {
+    await withWorker(
+      { ADMIN_TOKEN: "", API_BASE_URL: "https://api.example.com/v1", MAX_RETRIES: "3" },
+      async (adminMiniflare) => {
+        const response = await adminMiniflare.dispatchFetch("http://localhost/admin", {
+          headers: { Authorization: "Bearer " },
+        });
+        assert.equal(response.status, 503);
+        assert.match(await response.text(), /ADMIN_TOKEN/);
+      },
+      { kvNamespaces: ["CONFIG"] },
+    );
+  });
+
+  it("pushes Durable Object config changes to followers", async () => {
+    await withWorker(
+      { API_BASE_URL: "https://api.example.com/v1", MAX_RETRIES: "3" },
+      async (hubMiniflare) => {
+        const body = await fetchJson(hubMiniflare, "/hub");
+        assert.deepEqual(body, {
+          versions: [0, 1, 2],
+          loaded: true,
+          pushed: true,
+        });
+      },
+      { durableObjects: { CONFIG_HUB: "ConfigObject" } },
+    );
   });
 
   it("fails extraction when required fields have no bindings", async () => {
-    // Separate Miniflare instance with no bindings at all.
-    const emptyMiniflare = new Miniflare({
-      scriptPath: path.join(workerBuildPath, "worker", "shim.mjs"),
-      modules: true,
-      modulesRules: [
-        { type: "ESModule", include: ["**/*.js"], fallthrough: true },
-        { type: "CompiledWasm", include: ["**/*.wasm"], fallthrough: true },
-      ],
-      compatibilityDate: "2025-01-01",
-      bindings: {},
-    });
-
-    try {
-      const response = await emptyMiniflare.dispatchFetch(
-        "http://localhost/missing-all",
-      );
-      assert.equal(response.status, 200);
-
-      const body = await response.json();
+    // Separate worker with no bindings at all.
+    await withWorker({}, async (emptyMiniflare) => {
+      const body = await fetchJson(emptyMiniflare, "/missing-all");
       assert.equal(body.error, true);
       assert.ok(body.message.length > 0, "error message should be non-empty");
-    } finally {
-      await emptyMiniflare.dispose();
-    }
+    });
   });
 });